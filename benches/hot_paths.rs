@@ -0,0 +1,64 @@
+// `cargo bench`: parsing and writing todo.txt, filtering, sorting and
+// building the list's rows over synthetic lists of 10,000 and 100,000 todo
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use todo_tui::bench::{self, List, SIZES};
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("todo.txt");
+    group.sample_size(10);
+    for size in SIZES {
+        let text = bench::lines(size).join("\n");
+        group.bench_with_input(BenchmarkId::new("parse", size), &text, |b, text| b.iter(|| bench::parse(black_box(text))));
+        let tasks = bench::tasks(size);
+        group.bench_with_input(BenchmarkId::new("write", size), &tasks, |b, tasks| b.iter(|| bench::write(black_box(tasks))));
+    }
+    group.finish();
+}
+
+fn filter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filter");
+    group.sample_size(10);
+    for size in SIZES {
+        let tasks = bench::tasks(size);
+        for query in ["is:pending", "+project3 @ctx1", "number 99", "is:overdue is:stale"] {
+            group.bench_with_input(BenchmarkId::new(query, size), &tasks, |b, tasks| b.iter(|| bench::matching(black_box(tasks), query)));
+        }
+    }
+    group.finish();
+}
+
+fn sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    group.sample_size(10);
+    for size in SIZES {
+        for name in ["age", "due", "urgency"] {
+            let mut list = List::new(size);
+            list.sort_by(name);
+            group.bench_function(BenchmarkId::new(name, size), |b| b.iter(|| list.sort()));
+        }
+    }
+    group.finish();
+}
+
+// the styled rows, and everything after a change to the list with them
+fn rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rows");
+    group.sample_size(10);
+    for size in SIZES {
+        let mut list = List::new(size);
+        list.sort();
+        group.bench_function(BenchmarkId::new("build", size), |b| b.iter(|| list.build_rows()));
+        group.bench_function(BenchmarkId::new("refresh", size), |b| b.iter(|| list.refresh()));
+        list.group_by_project(true);
+        group.bench_function(BenchmarkId::new("refresh grouped by project", size), |b| b.iter(|| list.refresh()));
+        list.group_by_project(false);
+        list.filter("is:pending @ctx2");
+        group.bench_function(BenchmarkId::new("refresh filtered", size), |b| b.iter(|| list.refresh()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, filter, sort, rows);
+criterion_main!(benches);
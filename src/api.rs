@@ -0,0 +1,229 @@
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+};
+
+use chrono::{Local, NaiveDate};
+
+use crate::{
+    backup,
+    config::Config,
+    git, hooks,
+    http::{self, Request, Response},
+    json::{self, Json},
+    paths, report, storage, sync,
+    task::Task,
+};
+
+pub fn task_json(task: &Task) -> Json {
+    Json::Obj(vec![
+        ("id".to_string(), task.id.as_str().into()),
+        ("title".to_string(), task.title.as_str().into()),
+        ("project".to_string(), task.project.clone().into()),
+        ("contexts".to_string(), Json::Arr(task.contexts.iter().map(|c| c.as_str().into()).collect())),
+        ("priority".to_string(), task.priority.map(|p| p.to_string()).into()),
+        ("due".to_string(), task.due.map(|d| d.to_string()).into()),
+        ("created".to_string(), task.created.to_string().into()),
+        ("done".to_string(), task.completed.map(|d| d.format("%Y-%m-%dT%H:%M").to_string()).into()),
+        ("location".to_string(), task.location.clone().into()),
+        ("estimate".to_string(), task.estimate.map(|m| Json::Num(m as f64)).into()),
+        (
+            "checklist".to_string(),
+            Json::Arr(
+                task.checklist
+                    .iter()
+                    .map(|(done, item)| Json::Obj(vec![("text".to_string(), item.as_str().into()), ("done".to_string(), (*done).into())]))
+                    .collect(),
+            ),
+        ),
+        ("note".to_string(), task.note.clone().into()),
+        ("fields".to_string(), Json::Obj(task.fields.iter().map(|(k, v)| (k.to_string(), v.as_str().into())).collect())),
+    ])
+}
+
+// the token clients must send as `Authorization: Bearer <token>`; made up and
+// stored on first use unless the config sets `api_token`
+pub fn token() -> io::Result<String> {
+    if let Some(token) = Config::load()?.api_token {
+        return Ok(token);
+    }
+    match fs::read_to_string(paths::token_file()) {
+        Ok(token) => Ok(token.trim().to_string()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            // 256 bits from the system's random source, to not be guessed
+            let mut bytes = [0; 32];
+            getrandom::getrandom(&mut bytes)?;
+            let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            storage::write_secret(&paths::token_file(), &token)?;
+            println!("generated an API token in {}", paths::token_file().display());
+            Ok(token)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// `todo-tui serve`: list, add and complete over JSON
+pub fn serve(addr: &str) -> io::Result<()> {
+    let token = token()?;
+    let config = Config::load()?;
+    // write ids for tasks that never had one, so they stay addressable
+    storage::save(&storage::load(Local::now().date_naive())?)?;
+    http::serve(addr, |request| handle(request, &token, &config))
+}
+
+// compared in full whatever the first difference, so the time it takes
+// tells nothing about how much of a guess was right
+fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn handle(request: &Request, token: &str, config: &Config) -> io::Result<Response> {
+    let auth = request.header("authorization").and_then(|h| h.strip_prefix("Bearer "));
+    if !auth.is_some_and(|auth| same_token(auth, token)) {
+        return Ok(Response::error(401, "missing or wrong token"));
+    }
+    let today = Local::now().date_naive();
+    let path = request.path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["tasks"]) => {
+            let tasks = storage::load(today)?;
+            Ok(Response::ok("application/json", Json::Arr(tasks.iter().map(task_json).collect()).to_string()))
+        }
+        // body: {"text": "buy milk +Home due:2026-10-20"}
+        ("POST", ["tasks"]) => {
+            let text = json::parse(&request.body).and_then(|body| body.get("text").and_then(Json::as_str).map(str::to_string));
+            match text {
+                Some(text) if !text.trim().is_empty() => {
+                    let task = config.new_task(&text, None, today);
+                    storage::append(&task)?;
+                    git::commit_if_enabled(config, &format!("add '{}' over the API", task.title))?;
+                    hooks::fire(&config.hooks, hooks::Event::Added, &task);
+                    Ok(Response { status: 201, ..Response::ok("application/json", task_json(&task).to_string()) })
+                }
+                _ => Ok(Response::error(400, "expected {\"text\": \"...\"}")),
+            }
+        }
+        ("POST", ["tasks", id, "complete"]) => {
+            let mut tasks = storage::load(today)?;
+            let task = match tasks.iter_mut().find(|t| t.id == *id) {
+                Some(task) => task,
+                None => return Ok(Response::not_found()),
+            };
+            if task.completed.is_none() {
+                let now = Local::now().naive_local();
+                task.completed = Some(now);
+                task.touched = now;
+                hooks::fire(&config.hooks, hooks::Event::Completed, task);
+            }
+            let body = task_json(task).to_string();
+            let message = format!("complete '{}' over the API", task.title);
+            storage::save(&tasks)?;
+            git::commit_if_enabled(config, &message)?;
+            Ok(Response::ok("application/json", body))
+        }
+        ("GET", ["metrics"]) => Ok(Response::ok("text/plain; version=0.0.4", metrics(&storage::load(today)?, today))),
+        // whole todo.txt, for `todo-tui sync` on another machine, with the
+        // ETag its PUT has to send back
+        ("GET", ["sync"]) => {
+            let text = sync_text(today)?;
+            Ok(Response { etag: Some(etag(&text)), ..Response::ok("text/plain", text) })
+        }
+        // only over the version the peer merged with: a todo added or
+        // completed here since then would be lost, so it pulls again
+        ("PUT", ["sync"]) => match request.header("if-match") {
+            None => Ok(Response::error(428, "send the ETag of GET /sync as If-Match")),
+            Some(sent) if sent != etag(&sync_text(today)?) => Ok(Response::error(412, "the list changed since GET /sync")),
+            Some(_) => {
+                let tasks = sync::from_text(&request.body, today);
+                backup::take(config.backups.max(0) as usize)?;
+                storage::save(&tasks)?;
+                git::commit_if_enabled(config, "sync from a peer")?;
+                let text = sync_text(today)?;
+                Ok(Response { etag: Some(etag(&text)), ..Response::ok("text/plain", format!("{} tasks\n", tasks.len())) })
+            }
+        },
+        _ => Ok(Response::not_found()),
+    }
+}
+
+fn sync_text(today: NaiveDate) -> io::Result<String> {
+    Ok(storage::load(today)?.iter().map(|t| t.to_line() + "\n").collect())
+}
+
+// a version of the list, the same for the same text within one `serve`
+fn etag(text: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// the Prometheus text format, for a dashboard to chart the backlog
+fn metrics(tasks: &[Task], today: NaiveDate) -> String {
+    let open: Vec<&Task> = tasks.iter().filter(|t| !t.is_done()).collect();
+    let done_today = report::done_on(tasks, today).len();
+    let mut text = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(String, usize)>| {
+        text.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+        for (labels, value) in samples {
+            text.push_str(&format!("{}{} {}\n", name, labels, value));
+        }
+    };
+    gauge("todo_pending", "Open todo.", vec![(String::new(), open.len())]);
+    gauge("todo_overdue", "Open todo past their due date.", vec![(String::new(), open.iter().filter(|t| t.is_overdue(today)).count())]);
+    gauge("todo_completed_today", "Todo completed today.", vec![(String::new(), done_today)]);
+    let mut projects: Vec<&str> = open.iter().map(|t| t.project.as_deref().unwrap_or("")).collect();
+    projects.sort();
+    projects.dedup();
+    let by_project = projects
+        .into_iter()
+        .map(|p| {
+            let count = open.iter().filter(|t| t.project.as_deref().unwrap_or("") == p).count();
+            (format!("{{project=\"{}\"}}", p.replace('\\', "\\\\").replace('"', "\\\"")), count)
+        })
+        .collect();
+    gauge("todo_pending_project", "Open todo per project, the inbox as \"\".", by_project);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_match_in_full() {
+        assert!(same_token("f00dfeed", "f00dfeed"));
+        assert!(!same_token("f00dfeed", "f00dfeee"));
+        assert!(!same_token("f00dfee", "f00dfeed"));
+        assert!(!same_token("", "f00dfeed"));
+    }
+
+    fn request(method: &str, path: &str, if_match: Option<&str>, body: &str) -> Request {
+        let mut headers = vec![("Authorization".to_string(), "Bearer t".to_string())];
+        headers.extend(if_match.map(|etag| ("If-Match".to_string(), etag.to_string())));
+        Request { method: method.to_string(), path: path.to_string(), headers, body: body.to_string() }
+    }
+
+    // a todo added between a peer's GET and PUT makes the PUT fail, so the
+    // peer merges it instead of writing over it
+    #[test]
+    fn sync_puts_need_the_current_etag() {
+        let h = crate::harness::Harness::start("call mum id:a\n", "");
+        let config = &h.app.config;
+        let got = handle(&request("GET", "/sync", None, ""), "t", config).unwrap();
+        let etag = got.etag.unwrap();
+        assert_eq!(handle(&request("PUT", "/sync", None, ""), "t", config).unwrap().status, 428);
+        handle(&request("POST", "/tasks", None, r#"{"text": "buy milk"}"#), "t", config).unwrap();
+        let put = handle(&request("PUT", "/sync", Some(&etag), "call dad id:a\n"), "t", config).unwrap();
+        assert_eq!(put.status, 412);
+        assert_eq!(h.stored().len(), 2);
+        let etag = handle(&request("GET", "/sync", None, ""), "t", config).unwrap().etag.unwrap();
+        let put = handle(&request("PUT", "/sync", Some(&etag), "call dad id:a\n"), "t", config).unwrap();
+        assert_eq!(put.status, 200);
+        assert_eq!(h.stored().len(), 1);
+        assert!(h.stored_line("call dad").ends_with("id:a"));
+        // what it replaced is kept
+        assert_eq!(crate::backup::list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use chrono::NaiveDateTime;
+
+use crate::{paths, task::Task};
+
+// lines the activity overlay goes back over
+const LOG_SIZE: usize = 500;
+
+// more todo than this changed the same way are one line, `added 40 todo`
+const LISTED: usize = 3;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// what was done to the list, newest last: `14:02 completed 'ship release'`
+#[derive(Default)]
+pub struct Log {
+    pub entries: Vec<(NaiveDateTime, String)>,
+}
+
+impl Log {
+    // the end of `activity.log`, when it is kept
+    pub fn load() -> io::Result<Log> {
+        let text = match fs::read_to_string(paths::activity_file()) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Log::default()),
+            Err(err) => return Err(err),
+        };
+        let mut entries: Vec<(NaiveDateTime, String)> = text
+            .lines()
+            .filter_map(|line| {
+                let time = NaiveDateTime::parse_from_str(line.get(..16)?, TIME_FORMAT).ok()?;
+                Some((time, line.get(17..)?.to_string()))
+            })
+            .collect();
+        entries.drain(..entries.len().saturating_sub(LOG_SIZE));
+        Ok(Log { entries })
+    }
+    // appended to `activity.log` as well with `persist`
+    pub fn record(&mut self, time: NaiveDateTime, lines: Vec<String>, persist: bool) -> io::Result<()> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        if persist {
+            let text: String = lines.iter().map(|line| format!("{} {}\n", time.format(TIME_FORMAT), line)).collect();
+            fs::create_dir_all(paths::data_dir())?;
+            OpenOptions::new().append(true).create(true).open(paths::activity_file())?.write_all(text.as_bytes())?;
+        }
+        self.entries.extend(lines.into_iter().map(|line| (time, line)));
+        let over = self.entries.len().saturating_sub(LOG_SIZE);
+        self.entries.drain(..over);
+        Ok(())
+    }
+}
+
+// what changed from `before` to `after`, a line per kind of change:
+// `completed 'ship release'`, `added 'a', 'b'`, `moved 'x' to +Work`,
+// `deleted 12 todo`
+pub fn describe(before: &[Task], after: &[Task]) -> Vec<String> {
+    let mut changes: Vec<(String, Vec<String>)> = vec![];
+    let mut note = |verb: String, title: &str| match changes.iter_mut().find(|(v, _)| *v == verb) {
+        Some((_, titles)) => titles.push(title.to_string()),
+        None => changes.push((verb, vec![title.to_string()])),
+    };
+    let old: HashMap<&str, &Task> = before.iter().map(|t| (t.id.as_str(), t)).collect();
+    let new: HashMap<&str, &Task> = after.iter().map(|t| (t.id.as_str(), t)).collect();
+    for task in after {
+        let Some(old) = old.get(task.id.as_str()) else {
+            note("added".to_string(), &task.title);
+            continue;
+        };
+        if old.to_line() == task.to_line() {
+            continue;
+        }
+        let verb = if old.is_done() != task.is_done() {
+            (if task.is_done() { "completed" } else { "reopened" }).to_string()
+        } else if old.project != task.project {
+            format!("moved {{}} to {}", task.project.as_ref().map_or("the Inbox".to_string(), |p| format!("+{}", p)))
+        } else if old.priority != task.priority {
+            format!("set {{}} to {}", task.priority.map_or("no priority".to_string(), |p| format!("({})", p)))
+        } else if old.label != task.label {
+            format!("labeled {{}} {}", task.label.map_or("none", |l| l.name()))
+        } else {
+            "edited".to_string()
+        };
+        note(verb, &task.title);
+    }
+    for task in before.iter().filter(|t| !new.contains_key(t.id.as_str())) {
+        note("deleted".to_string(), &task.title);
+    }
+    changes
+        .into_iter()
+        .map(|(verb, titles)| {
+            let what = match titles.len() {
+                n if n > LISTED => format!("{} todo", n),
+                _ => titles.iter().map(|t| format!("'{}'", t)).collect::<Vec<_>>().join(", "),
+            };
+            match verb.contains("{}") {
+                true => verb.replacen("{}", &what, 1),
+                false => format!("{} {}", verb, what),
+            }
+        })
+        .collect()
+}
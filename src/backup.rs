@@ -0,0 +1,58 @@
+use std::{cmp::Reverse, fs, io, path::PathBuf};
+
+use chrono::{Duration, Local, NaiveDateTime};
+
+use crate::paths;
+
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// a copy of todo.txt in the backups directory, as `todo-<time>.txt`
+pub struct Backup {
+    pub path: PathBuf,
+    pub time: NaiveDateTime,
+}
+
+pub fn dir() -> PathBuf {
+    paths::data_dir().join("backups")
+}
+
+// newest first
+pub fn list() -> io::Result<Vec<Backup>> {
+    let entries = match fs::read_dir(dir()) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut backups: Vec<Backup> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let time = name.strip_prefix("todo-")?.strip_suffix(".txt")?;
+            let time = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+            Some(Backup { path: e.path(), time })
+        })
+        .collect();
+    backups.sort_by_key(|b| Reverse(b.time));
+    Ok(backups)
+}
+
+// called before todo.txt is written: copy it when the newest backup is over
+// an hour old, and drop all but the `keep` newest
+pub fn take(keep: usize) -> io::Result<()> {
+    let todo = paths::todo_file();
+    if keep == 0 || !todo.exists() {
+        return Ok(());
+    }
+    let now = Local::now().naive_local();
+    let backups = list()?;
+    if backups.first().is_some_and(|b| now - b.time < Duration::hours(1)) {
+        return Ok(());
+    }
+    fs::create_dir_all(dir())?;
+    fs::copy(todo, dir().join(format!("todo-{}.txt", now.format(TIME_FORMAT))))?;
+    // the new copy makes one more
+    for old in backups.iter().skip(keep.saturating_sub(1)) {
+        fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// synthetic lists and the paths every change goes through, for the criterion
+// benches in benches/; not part of the app
+use chrono::{Duration, NaiveDate};
+
+use crate::{filter::Filter, rows, sorting::SortMode, sync, task::Task, App, Group};
+
+pub const SIZES: [usize; 2] = [10_000, 100_000];
+
+pub fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+}
+
+// a mix of what real lists hold: priorities, projects, contexts, due
+// dates, estimates and some done
+pub fn lines(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            let mut line = match i % 7 {
+                0 => format!("x {} ", today() - Duration::days((i % 30) as i64)),
+                1 => "(A) ".to_string(),
+                2 => "(C) ".to_string(),
+                _ => String::new(),
+            };
+            line.push_str(&format!("2026-01-{:02} task number {} +Project{} @ctx{}", i % 28 + 1, i, i % 20, i % 5));
+            if i % 3 == 0 {
+                line.push_str(&format!(" due:{}", today() + Duration::days((i % 60) as i64 - 10)));
+            }
+            if i % 4 == 0 {
+                line.push_str(&format!(" est:{}m", 15 * (i % 8 + 1)));
+            }
+            line.push_str(&format!(" id:bench{}", i));
+            line
+        })
+        .collect()
+}
+
+pub fn parse(text: &str) -> Vec<Task> {
+    sync::from_text(text, today())
+}
+
+pub fn write(tasks: &[Task]) -> String {
+    sync::to_text(tasks)
+}
+
+pub fn tasks(n: usize) -> Vec<Task> {
+    lines(n).iter().map(|line| Task::parse(line, today())).collect()
+}
+
+// how many of `tasks` the query keeps
+pub fn matching(tasks: &[Task], query: &str) -> usize {
+    let filter = Filter::parse(query);
+    tasks.iter().filter(|t| filter.matches(t, today(), 30)).count()
+}
+
+// the app holding a synthetic list, without a terminal or data directory
+pub struct List(App);
+
+impl List {
+    pub fn new(n: usize) -> List {
+        let mut app = App::new();
+        app.tasks = tasks(n);
+        List(app)
+    }
+    // `age`, `due`, `urgency` and the other names s cycles through
+    pub fn sort_by(&mut self, name: &str) {
+        self.0.sort = SortMode::parse(name).unwrap_or(SortMode::Manual);
+    }
+    pub fn group_by_project(&mut self, on: bool) {
+        self.0.group = on.then_some(Group::Project);
+    }
+    pub fn filter(&mut self, query: &str) {
+        self.0.filter = Filter::parse(query);
+    }
+    // the rows shown, filtered and sorted
+    pub fn sort(&mut self) {
+        self.0.refresh_items();
+    }
+    // the styled rows of those, grouped
+    pub fn build_rows(&self) -> usize {
+        rows::build(&self.0, today()).len()
+    }
+    // everything after a change to the list: the sidebar, the rows shown and
+    // their styled rows
+    pub fn refresh(&mut self) {
+        self.0.refresh();
+    }
+}
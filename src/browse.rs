@@ -0,0 +1,110 @@
+use std::{fs, path::PathBuf};
+
+use crossterm::event::KeyCode;
+use ratatui::widgets::ListState;
+
+use crate::paths;
+
+#[derive(Clone)]
+pub enum Row {
+    // when choosing where to write: this directory, under the name given
+    SaveHere,
+    Parent,
+    Dir(String),
+    File(String),
+}
+
+pub enum Browsed {
+    Path(PathBuf),
+    Cancelled,
+}
+
+// the file browser overlay, for a file to read or, with `save_as`, where to
+// write one: a file picked there is overwritten
+pub struct Browser {
+    pub dir: PathBuf,
+    pub rows: Vec<Row>,
+    pub state: ListState,
+    pub hidden: bool,
+    pub save_as: Option<String>,
+    // why the last directory couldn't be opened
+    pub error: Option<String>,
+}
+
+impl Browser {
+    pub fn open(dir: PathBuf, save_as: Option<String>) -> Browser {
+        let dir = fs::canonicalize(&dir).unwrap_or(dir);
+        let mut browser = Browser { dir: PathBuf::new(), rows: vec![], state: ListState::default(), hidden: false, save_as, error: None };
+        browser.enter(dir);
+        browser
+    }
+    // directories first, each part by name; the old directory stays when the
+    // new one can't be read
+    fn enter(&mut self, dir: PathBuf) {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.error = Some(format!("could not open {}: {}", dir.display(), err));
+                return;
+            }
+        };
+        let mut entries: Vec<(bool, String)> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| (!e.path().is_dir(), e.file_name().to_string_lossy().to_string()))
+            .filter(|(_, name)| self.hidden || !name.starts_with('.'))
+            .collect();
+        entries.sort_by_key(|(file, name)| (*file, name.to_lowercase()));
+        // back on the directory just left when going up
+        let left = self.dir.strip_prefix(&dir).ok().and_then(|rest| rest.components().next());
+        let left = left.map(|c| c.as_os_str().to_string_lossy().to_string());
+        self.rows = vec![];
+        if self.save_as.is_some() {
+            self.rows.push(Row::SaveHere);
+        }
+        if dir.parent().is_some() {
+            self.rows.push(Row::Parent);
+        }
+        self.rows.extend(entries.into_iter().map(|(file, name)| if file { Row::File(name) } else { Row::Dir(name) }));
+        let row = self.rows.iter().position(|r| matches!(r, Row::Dir(name) if Some(name) == left.as_ref()));
+        self.state.select(Some(row.unwrap_or(0)));
+        self.dir = dir;
+        self.error = None;
+    }
+    // Up and Down move, Enter or Right opens a directory and picks a file,
+    // Left or Backspace goes up, `~` home and `.` shows or hides dotfiles.
+    // None while it stays open
+    pub fn handle(&mut self, code: KeyCode) -> Option<Browsed> {
+        let row = self.state.selected().unwrap_or(0);
+        match code {
+            KeyCode::Esc => return Some(Browsed::Cancelled),
+            KeyCode::Down => self.state.select(Some((row + 1).min(self.rows.len().saturating_sub(1)))),
+            KeyCode::Up => self.state.select(Some(row.saturating_sub(1))),
+            KeyCode::Left | KeyCode::Backspace => self.up(),
+            KeyCode::Char('~') => {
+                if let Some(home) = paths::home() {
+                    self.enter(home);
+                }
+            }
+            KeyCode::Char('.') => {
+                self.hidden = !self.hidden;
+                self.enter(self.dir.clone());
+            }
+            KeyCode::Enter | KeyCode::Right => match self.rows.get(row).cloned() {
+                Some(Row::SaveHere) if code == KeyCode::Enter => {
+                    return self.save_as.as_ref().map(|name| Browsed::Path(self.dir.join(name)));
+                }
+                Some(Row::Parent) => self.up(),
+                Some(Row::Dir(name)) => self.enter(self.dir.join(name)),
+                Some(Row::File(name)) if code == KeyCode::Enter => return Some(Browsed::Path(self.dir.join(name))),
+                _ => {}
+            },
+            _ => {}
+        }
+        None
+    }
+    fn up(&mut self) {
+        if let Some(parent) = self.dir.parent() {
+            self.enter(parent.to_path_buf());
+        }
+    }
+}
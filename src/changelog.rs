@@ -0,0 +1,154 @@
+pub struct Release {
+    pub version: &'static str,
+    pub notes: &'static [&'static str],
+}
+
+// newest first
+pub const CHANGELOG: &[Release] = &[
+    Release {
+        version: "0.2.0",
+        notes: &[
+            "Type due:YYYY-MM-DD in the popup to set a due date.",
+            "O postpones every overdue todo to today.",
+            "u undoes the last change.",
+            "This what's-new screen after each upgrade; Up and Down scroll it.",
+            "s cycles the sort: manual, age, due, label, location, urgency and the config's sort expression. todo.txt keeps the manual order.",
+            "a shows how long ago each todo was added.",
+            "Todo untouched for stale_days (30) are greyed out; is:stale finds them.",
+            "b opens the popup in batch mode, adding a todo on each Enter.",
+            "Pasting several lines into the add popup offers one todo per line, leaving out lines Enter would refuse.",
+            "todo-tui quick adds a todo without opening the app.",
+            "A sidebar with the projects and an Inbox; Tab moves between it and the list.",
+            "t triages the selected todo: text, project, priority and due date.",
+            "c picks a color label; label:red filters by it.",
+            "Space completes a todo and keeps the time; the Done log lists them by day.",
+            "todo-tui summary prints today's completed todo; Y copies it.",
+            "S shows a standup of what was done and what is due today.",
+            "todo-tui ical exports due todo as iCal, or serves them with --serve.",
+            "todo-tui serve runs a JSON API on localhost behind a random token.",
+            "todo-tui sync --peer syncs two machines over that API.",
+            "git = true commits todo.txt on every save; H browses the history.",
+            "A [webdav] section keeps todo.txt on a WebDAV server such as Nextcloud.",
+            "todo-tui org export and org import write and read org-mode files.",
+            "todo-tui todoist syncs with Todoist.",
+            "todo-tui mstodo syncs with Microsoft To Do after mstodo login.",
+            "Configured peers and providers sync in the background; the help line shows when each last synced and what is pending.",
+            "Edits made on both sides since the last sync open a side-by-side dialog: l keeps this one, r theirs, m edits by hand.",
+            "Deleting a todo on one side deletes it on the others at the next sync.",
+            "A peer sync no longer writes over todo added on the other side meanwhile; update both machines.",
+            "[hooks] runs shell commands or a webhook when a todo is added, completed or overdue; the todo's fields reach commands as TODO_TITLE and the like, never as part of the command.",
+            "completion_log appends every completion to a file as a JSON line.",
+            "Rhai plugins in plugins/ bind keys, add todo-tui run commands and decorate rows.",
+            "Plugin calls stop after a million operations; a failing decorator is turned off and its error shown.",
+            "Other key:value words are kept as custom fields, shown and filterable.",
+            "est:30m notes an estimate; the list title sums them and capacity warns about a full day.",
+            "@contexts get their own sidebar entries; # picks one for the selected todo.",
+            "loc: notes where a todo gets done; the location sort groups by it.",
+            "Tab into the detail pane to edit the selected todo's checklist; rows show [2/6].",
+            "e writes a note in $EDITOR, shown with Markdown in the detail pane.",
+            "Code blocks in notes are highlighted; code_theme picks the colors.",
+            "[project.name] sections set defaults, a sort and show_done for a project.",
+            "In the sidebar n creates a project, r renames, Shift+Up/Down moves, A archives and D deletes.",
+            "m moves the selected todo to a project and M copies it; v marks several.",
+            "Drag todo with the mouse to reorder them or onto a project to move them.",
+            "| opens a second list beside the first, w switches and > sends todo across.",
+            "z zooms the focused pane to fill the screen.",
+            "[layout] sets the pane widths and hides the help line; Ctrl+Left/Right move the borders.",
+            "h hides completed todo; hide_done starts with them hidden.",
+            "archive_after moves old completed todo to done.txt.",
+            ":purge older-than 90d (and todo-tui purge) deletes archived todo for good.",
+            "todo.txt starts with a format line, and older files are upgraded with a copy kept.",
+            "todo.txt is backed up hourly; R restores a backup, o there to pick any file.",
+            "Each save goes through a journal, and y replays it after a crash.",
+            ":import adds an org or todo.txt file, updating todo already in the list, and reports what changed; --filter narrows it.",
+            ":export md, org, ics, txt or html copies or writes the rows shown; --selected and --filter narrow it.",
+            "todo-tui print writes a view as aligned text for less, printing or email.",
+            "HTML export writes a standalone styled page.",
+            "GET /metrics serves backlog counts for Prometheus.",
+            "Keys can be rebound under [keys]; todo-tui keys prints them all.",
+            "q records keys and q again stops; @ plays them back.",
+            ". repeats the last change on the selected todo, a cut with its count too.",
+            "A number before a key repeats it, as in 5 Space or 3dd.",
+            "d d cuts and y y copies todo; P pastes the last one, 2P the one before.",
+            "Resting the mouse on a cut-off title shows it whole.",
+            "g groups the list by project, context or due date under sticky headers; - folds a group.",
+            "The Due view sorts todo into Today, Tomorrow, This week, Later and No date; 1 to 5 jump there.",
+            "B charts a project's burndown over the last week, m the last month.",
+            "F shows only the selected todo, with a timer on t.",
+            "The help line counts down to the next deadline; N jumps to it.",
+            "remind:14:30 pops up a reminder with the bell; 1, 2 and 3 snooze it.",
+            "quiet_hours holds reminders and the overdue hook back.",
+            "The urgency sort scores todo as Taskwarrior does; [urgency] sets the weights.",
+            "sort = [\"pinned desc\", \"due asc\"] in the config sorts by any fields.",
+            "f and a few letters jump to the todo with that title.",
+            "` and a letter set a mark, ' and the letter go back to it.",
+            "Ctrl+O and Ctrl+I walk back and forth through the jump list.",
+            "[escalate] raises the priority of todo left untouched for a while.",
+            "[rules] change added and imported todo, as in if tag=#errand then project=Personal.",
+            "Plain words in the popup set things: tomorrow 3pm #work !high.",
+            "The popup colors the words it will read by what they set.",
+            "Empty or untitled todo are refused, and max_length caps the text.",
+            "Long input scrolls sideways and the popup grows with its text.",
+            "Input method text, wide characters and accents are typed and deleted whole.",
+            "The text inputs edit like a shell: Ctrl+A, Ctrl+E, Alt+B, Alt+F, Ctrl+K, Ctrl+U, Ctrl+W, Ctrl+Y.",
+            "Shift selects in the text inputs, and Up and Down go through what was entered before.",
+            "Dialogs stack, and Esc closes the newest one first.",
+            "The label, project and context pickers filter as you type.",
+            "A file browser picks the file for :import, :export --save and restores.",
+            "Mistakes under [keys] are shown with their config line at startup.",
+            "After the first key of a sequence, a box lists the keys that can follow.",
+            "<leader> in a binding is the leader key; key_timeout waits for longer bindings.",
+            "[keys.sidebar], [keys.list] and [keys.detail] bind keys for one pane.",
+            "L shows the activity log; activity_log keeps it in a file.",
+            "Undo says what it took back, and :undolist lists every step.",
+            "The selection stays on the same todo across sorts, filters, syncs and cuts.",
+            "Home and End go to the first and last row; Up and Down wrap round.",
+            "PageDown and PageUp move a screenful.",
+            "Works in Windows Terminal: key releases are ignored, AltGr characters type, and data lives in %APPDATA%\\todo-tui\\data.",
+            "Without a home directory the app stops and asks for TODO_TUI_DIR.",
+            "enhanced_keys = true turns on the kitty keyboard protocol; Shift+Enter then adds a todo and keeps the popup open.",
+            "Pastes in the list are ignored, and the app saves and slows down when it loses the focus.",
+        ],
+    },
+    Release {
+        version: "0.1.0",
+        notes: &[
+            "p opens the popup to add a todo.",
+        ],
+    },
+];
+
+// trailing zeros dropped, so 0.1 and 0.1.0 are the same version
+fn parse_version(version: &str) -> Vec<u32> {
+    let mut parts: Vec<u32> = version.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    parts
+}
+
+// releases newer than the last version the user has seen
+pub fn since(seen: Option<&str>) -> Vec<&'static Release> {
+    match seen {
+        Some(seen) => {
+            let seen = parse_version(seen);
+            CHANGELOG.iter().filter(|r| parse_version(r.version) > seen).collect()
+        }
+        None => CHANGELOG.iter().take(1).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_the_releases_after_the_seen_one() {
+        let versions = |seen| since(seen).iter().map(|r| r.version).collect::<Vec<_>>();
+        assert_eq!(versions(Some("0.1.0")), ["0.2.0"]);
+        assert_eq!(versions(Some("0.2.0")), Vec::<&str>::new());
+        assert_eq!(versions(Some("0.1")), ["0.2.0"]);
+        // a first run shows only the newest
+        assert_eq!(versions(None), [CHANGELOG[0].version]);
+    }
+}
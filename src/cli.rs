@@ -0,0 +1,408 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{self, IsTerminal},
+    path::Path,
+    process,
+};
+
+use chrono::{Local, Utc};
+
+use crate::{
+    api, clipboard,
+    config::Config,
+    export,
+    filter::Filter,
+    git, hooks,
+    http::{self, Response},
+    ical, import,
+    keymap::Keymap,
+    mstodo::{self, MsTodo},
+    org, paths,
+    plugin::{self, Plugins},
+    print,
+    purge::Purge,
+    report::{self, Format},
+    state::State,
+    storage,
+    sync::{self, Peer},
+    task::Task,
+    todoist::Todoist,
+    View,
+};
+
+// `todo-tui quick <title>`: append one todo without starting the TUI
+pub fn quick(args: &[String]) -> io::Result<()> {
+    let text = args.join(" ");
+    if text.trim().is_empty() {
+        eprintln!("usage: todo-tui quick <title>");
+        process::exit(2);
+    }
+    let config = Config::load()?;
+    let task = config.new_task(&text, None, Local::now().date_naive());
+    storage::append(&task)?;
+    git::commit_if_enabled(&config, &format!("add '{}'", task.title))?;
+    println!("added: {}", task.title);
+    for hook in hooks::fire(&config.hooks, hooks::Event::Added, &task) {
+        let _ = hook.join();
+    }
+    Ok(())
+}
+
+enum Target {
+    Stdout,
+    Clipboard,
+    File,
+}
+
+// `todo-tui summary [--text] [--clipboard | --file]`: what got done today
+pub fn summary(args: &[String]) -> io::Result<()> {
+    let mut format = Format::Markdown;
+    let mut target = Target::Stdout;
+    for arg in args {
+        match arg.as_str() {
+            "--md" => format = Format::Markdown,
+            "--text" => format = Format::Text,
+            "--clipboard" => target = Target::Clipboard,
+            "--file" => target = Target::File,
+            _ => {
+                eprintln!("usage: todo-tui summary [--md | --text] [--clipboard | --file]");
+                process::exit(2);
+            }
+        }
+    }
+    let today = Local::now().date_naive();
+    let text = report::daily_summary(&storage::load(today)?, today, format);
+    match target {
+        Target::Stdout => print!("{}", text),
+        Target::Clipboard => {
+            clipboard::copy(&text)?;
+            println!("copied today's summary to the clipboard");
+        }
+        Target::File => {
+            let dir = Config::load()?.summary_dir;
+            fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("{}.{}", today, format.extension()));
+            fs::write(&path, text)?;
+            println!("wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+// `todo-tui ical [--events] [<file> | --serve <addr>]`: due dates for calendar apps
+pub fn ical(args: &[String]) -> io::Result<()> {
+    let mut events = false;
+    let mut serve = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--events" => events = true,
+            "--serve" => serve = args.next().cloned(),
+            _ if !arg.starts_with("--") && path.is_none() => path = Some(arg.to_string()),
+            _ => {
+                eprintln!("usage: todo-tui ical [--events] [<file> | --serve <addr>]");
+                process::exit(2);
+            }
+        }
+    }
+    let export = || -> io::Result<String> {
+        let tasks = storage::load(Local::now().date_naive())?;
+        Ok(ical::export(&tasks, events, Utc::now().naive_utc()))
+    };
+    match (serve, path) {
+        (Some(addr), _) => http::serve(&addr, |request| match request.path.as_str() {
+            "/" | "/todo.ics" if request.method == "GET" => Ok(Response::ok("text/calendar", export()?)),
+            _ => Ok(Response::not_found()),
+        }),
+        (None, Some(path)) => {
+            fs::write(&path, export()?)?;
+            println!("wrote {}", path);
+            Ok(())
+        }
+        (None, None) => {
+            print!("{}", export()?);
+            Ok(())
+        }
+    }
+}
+
+// `todo-tui serve [--addr <addr>]`
+pub fn serve(args: &[String]) -> io::Result<()> {
+    match args {
+        [] => api::serve("127.0.0.1:7878"),
+        [flag, addr] if flag == "--addr" => api::serve(addr),
+        _ => {
+            eprintln!("usage: todo-tui serve [--addr <addr>]");
+            process::exit(2);
+        }
+    }
+}
+
+// `todo-tui sync --peer <addr> [--token <token>]`
+pub fn sync(args: &[String]) -> io::Result<()> {
+    let (peer, token) = match args {
+        [flag, peer] if flag == "--peer" => (peer, None),
+        [flag, peer, tflag, token] if flag == "--peer" && tflag == "--token" => (peer, Some(token.as_str())),
+        _ => {
+            eprintln!("usage: todo-tui sync --peer <host:port> [--token <token>]");
+            process::exit(2);
+        }
+    };
+    let merge = sync::run(&mut Peer::new(peer, token)?)?;
+    println!(
+        "synced with {}: {} pulled, {} pushed, {} conflicts",
+        peer,
+        merge.pulled,
+        merge.pushed,
+        merge.conflicts.len()
+    );
+    Ok(())
+}
+
+// `todo-tui org export [<file>]` / `todo-tui org import <file>`
+pub fn org(args: &[String]) -> io::Result<()> {
+    let now = Local::now().naive_local();
+    match args {
+        [cmd] if cmd == "export" => print!("{}", org::export(&storage::load(now.date())?)),
+        [cmd, path] if cmd == "export" => {
+            fs::write(path, org::export(&storage::load(now.date())?))?;
+            println!("wrote {}", path);
+        }
+        [cmd, path] if cmd == "import" => merge_file(path, org::import(&fs::read_to_string(path)?, now.date()), false)?,
+        _ => {
+            eprintln!("usage: todo-tui org export [<file>] | todo-tui org import <file>");
+            process::exit(2);
+        }
+    }
+    Ok(())
+}
+
+// `todo-tui import <file> [--skip-existing] [--filter <terms>...]`: org or
+// todo.txt, merging the todo we already have
+pub fn import(args: &[String]) -> io::Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (args, filter) = Filter::take_arg(&args);
+    let (path, skip_existing) = match args.as_slice() {
+        [path] => (*path, false),
+        [path, flag] | [flag, path] if *flag == "--skip-existing" => (*path, true),
+        _ => {
+            eprintln!("usage: todo-tui import <file> [--skip-existing] [--filter <terms>...]");
+            process::exit(2);
+        }
+    };
+    let today = Local::now().date_naive();
+    let config = Config::load()?;
+    let imported = import::read(Path::new(path), today)?
+        .into_iter()
+        .filter(|t| filter.matches(t, today, config.stale_days))
+        .map(|t| config.with_rules(t))
+        .collect();
+    merge_file(path, imported, skip_existing)
+}
+
+// `todo-tui export <md|org|ics|txt|html> [<file>] [--filter <terms>...]`
+pub fn export(args: &[String]) -> io::Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (args, filter) = Filter::take_arg(&args);
+    let (format, path) = match args.as_slice() {
+        [name] => (export::Format::parse(name), None),
+        [name, path] => (export::Format::parse(name), Some(*path)),
+        _ => (None, None),
+    };
+    let format = match format {
+        Some(format) => format,
+        None => {
+            eprintln!("usage: todo-tui export <md|org|ics|txt|html> [<file>] [--filter <terms>...]");
+            process::exit(2);
+        }
+    };
+    let today = Local::now().date_naive();
+    let stale_days = Config::load()?.stale_days;
+    let tasks: Vec<Task> = storage::load(today)?.into_iter().filter(|t| filter.matches(t, today, stale_days)).collect();
+    let text = export::render(&tasks, format, Utc::now().naive_utc());
+    match path {
+        Some(path) => {
+            fs::write(path, text)?;
+            println!("wrote {} todo to {}", tasks.len(), path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn merge_file(path: &str, imported: Vec<Task>, skip_existing: bool) -> io::Result<()> {
+    let now = Local::now().naive_local();
+    let mut tasks = storage::load(now.date())?;
+    let report = import::merge(&mut tasks, imported, skip_existing, now);
+    if !report.added.is_empty() || !report.updated.is_empty() {
+        storage::save(&tasks)?;
+        git::commit_if_enabled(&Config::load()?, &format!("import {}", path))?;
+    }
+    println!("imported {}: {}", path, report.summary());
+    Ok(())
+}
+
+// `todo-tui print [<view>] [--color | --plain] [--filter <terms>...]`: the
+// view as aligned text, colored when printing to a terminal
+pub fn print(args: &[String]) -> io::Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (args, filter) = Filter::take_arg(&args);
+    let mut color = io::stdout().is_terminal();
+    let mut view = View::All;
+    for arg in args {
+        match arg {
+            "--color" => color = true,
+            "--plain" => color = false,
+            _ if !arg.starts_with("--") => view = View::parse(arg),
+            _ => {
+                eprintln!("usage: todo-tui print [all | inbox | done | @<context> | <project>] [--color | --plain] [--filter <terms>...]");
+                process::exit(2);
+            }
+        }
+    }
+    let today = Local::now().date_naive();
+    let config = Config::load()?;
+    let archived = State::load()?.archived;
+    let tasks = storage::load(today)?;
+    let mut shown: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| view.matches(t) && filter.matches(t, today, config.stale_days))
+        // archived projects only show in their own view and the done log, as in the app
+        .filter(|t| matches!(view, View::Project(_) | View::DoneLog) || !t.project.as_ref().is_some_and(|p| archived.contains(p)))
+        .collect();
+    if let View::DoneLog = view {
+        shown.sort_by_key(|t| Reverse(t.completed));
+    }
+    print!("{}", print::render(&view.name(), &shown, today, color));
+    Ok(())
+}
+
+// `todo-tui keys [--md]`: the keys as the config leaves them
+pub fn keys(args: &[String]) -> io::Result<()> {
+    let markdown = match args {
+        [] => false,
+        [flag] if flag == "--md" => true,
+        _ => {
+            eprintln!("usage: todo-tui keys [--md]");
+            process::exit(2);
+        }
+    };
+    let config = Config::load()?;
+    let keymap = Keymap::new(&config.keys, &config.leader);
+    for (line, problem) in keymap.diagnose(&config.keys) {
+        eprintln!("{}:{}: {}", paths::config_file().display(), line, problem);
+    }
+    print!("{}", keymap.cheat_sheet(markdown));
+    Ok(())
+}
+
+// `todo-tui todoist [--token <token>]`
+pub fn todoist(args: &[String]) -> io::Result<()> {
+    let token = match args {
+        [] => Config::load()?.todoist_token,
+        [flag, token] if flag == "--token" => Some(token.to_string()),
+        _ => None,
+    };
+    let token = match token {
+        Some(token) => token,
+        None => {
+            eprintln!("usage: todo-tui todoist [--token <token>] (or set token under [todoist] in the config)");
+            process::exit(2);
+        }
+    };
+    let merge = sync::run(&mut Todoist::new(&token, &Config::load()?.mstodo_projects))?;
+    println!("synced with Todoist: {} pulled, {} pushed, {} conflicts", merge.pulled, merge.pushed, merge.conflicts.len());
+    Ok(())
+}
+
+// `todo-tui mstodo [login]`
+pub fn mstodo(args: &[String]) -> io::Result<()> {
+    let config = Config::load()?;
+    let client_id = match &config.mstodo_client_id {
+        Some(id) => id.to_string(),
+        None => {
+            eprintln!("set client_id under [mstodo] in the config to the id of your Azure app registration");
+            process::exit(2);
+        }
+    };
+    match args {
+        [cmd] if cmd == "login" => mstodo::login(&client_id),
+        [] => {
+            if config.mstodo_projects.is_empty() {
+                eprintln!("list the projects to sync as projects = \"Work Errands\" under [mstodo] in the config");
+                process::exit(2);
+            }
+            let merge = sync::run(&mut MsTodo::new(&client_id, &config.mstodo_projects))?;
+            println!("synced with Microsoft To Do: {} pulled, {} pushed, {} conflicts", merge.pulled, merge.pushed, merge.conflicts.len());
+            Ok(())
+        }
+        _ => {
+            eprintln!("usage: todo-tui mstodo [login]");
+            process::exit(2);
+        }
+    }
+}
+
+// `todo-tui run <command> [<args>...]`: a command a plugin registered
+pub fn run(args: &[String]) -> io::Result<()> {
+    let (name, args) = match args.split_first() {
+        Some(split) => split,
+        None => {
+            eprintln!("usage: todo-tui run <command> [<args>...]");
+            process::exit(2);
+        }
+    };
+    let (plugins, errors) = Plugins::load();
+    for err in errors {
+        eprintln!("plugin error: {}", err);
+    }
+    if !plugins.has_command(name) {
+        eprintln!("no plugin in {} registers a `{}` command", plugin::dir().display(), name);
+        process::exit(2);
+    }
+    let mut tasks = storage::load(Local::now().date_naive())?;
+    let actions = plugins.run_command(name, args, &tasks).map_err(io::Error::other)?;
+    let config = Config::load()?;
+    let applied = plugin::apply(actions, &mut tasks, &config);
+    if applied.changed {
+        storage::save(&tasks)?;
+        git::commit_if_enabled(&config, &format!("run {}", name))?;
+    }
+    for message in applied.messages {
+        println!("{}", message);
+    }
+    for hook in applied.hooks {
+        let _ = hook.join();
+    }
+    Ok(())
+}
+
+// `todo-tui purge [older-than <age>] [<filter>...] [--dry-run]`: delete
+// archived todo from done.txt
+pub fn purge(args: &[String]) -> io::Result<()> {
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let args: Vec<&str> = args.iter().map(String::as_str).filter(|a| *a != "--dry-run").collect();
+    let purge = match Purge::parse(&args) {
+        Ok(purge) => purge,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("usage: todo-tui purge [older-than <age>] [<filter>...] [--dry-run]");
+            process::exit(2);
+        }
+    };
+    let today = Local::now().date_naive();
+    let config = Config::load()?;
+    let (doomed, kept): (Vec<_>, Vec<_>) = storage::load_archive(today)?.into_iter().partition(|t| purge.matches(t, today, config.stale_days));
+    for task in &doomed {
+        println!("{}", task.to_line());
+    }
+    if dry_run {
+        println!("would purge {} todo from done.txt", doomed.len());
+    } else if !doomed.is_empty() {
+        storage::save_archive(&kept)?;
+        println!("purged {} todo from done.txt", doomed.len());
+    }
+    Ok(())
+}
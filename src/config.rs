@@ -0,0 +1,409 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::{
+    paths,
+    rules::{self, Rule},
+    task::{self, Task},
+    sorting,
+    urgency::Coefficients,
+};
+
+pub struct Config {
+    pub stale_days: i64,
+    // start with completed todo hidden from the lists
+    pub hide_done: bool,
+    // days after which completed todo move to done.txt, 0 keeps them
+    pub archive_after: i64,
+    pub escalate: Escalation,
+    // hourly copies of todo.txt kept in backups/, 0 turns them off
+    pub backups: i64,
+    // syntect theme for code blocks in notes
+    pub code_theme: String,
+    // characters a todo's text may have, 0 for no limit
+    pub max_length: usize,
+    // minutes of estimated work a day holds before the header warns
+    pub capacity: Option<u32>,
+    // `quiet_hours = "22:00-07:00"`: no reminders or overdue hooks from the
+    // start to the end, they go off once it is over
+    pub quiet_hours: Option<(NaiveTime, NaiveTime)>,
+    // where `summary --file` writes the dated summaries
+    pub summary_dir: PathBuf,
+    pub api_token: Option<String>,
+    // commit todo.txt to a git repo in the data directory on every save
+    pub git: bool,
+    // keep the activity log in `activity.log` too, not just while running
+    pub activity_log: bool,
+    // keep todo.txt on a WebDAV server instead of only on disk
+    pub webdav: Option<WebDavConfig>,
+    pub todoist_token: Option<String>,
+    // the app registration `todo-tui mstodo login` signs in with
+    pub mstodo_client_id: Option<String>,
+    // projects kept in a Microsoft To Do list of the same name
+    pub mstodo_projects: Vec<String>,
+    // other machines running `serve` to sync with in the background
+    pub sync_peers: Vec<String>,
+    // seconds between background syncs
+    pub sync_interval: i64,
+    pub hooks: Hooks,
+    pub urgency: Coefficients,
+    // `sort = ["pinned desc", "due asc"]`, the order lists are shown in while
+    // they keep their manual order
+    pub sort: Vec<sorting::Key>,
+    // `[rules]`: `name = "if tag=errand then project=Personal"`, applied in
+    // order to added and imported todo
+    pub rules: Vec<Rule>,
+    pub layout: LayoutConfig,
+    // `[keys]` and `[keys.<pane>]`: action names and the keys they get
+    pub keys: Vec<KeyEntry>,
+    // seconds the keys that can follow `d` or another prefix stay listed, 0
+    // to not list them
+    pub which_key: u64,
+    // the keys `<leader>` in a binding stands for
+    pub leader: String,
+    // milliseconds a key that is a binding of its own and the start of a
+    // longer one waits for the rest before it does its own
+    pub key_timeout: u64,
+    // turn on the kitty keyboard protocol, for terminals that have it: keys
+    // like Shift+Enter and Ctrl+i come through as themselves
+    pub enhanced_keys: bool,
+    // `[project.<name>]` sections
+    pub projects: BTreeMap<String, ProjectDefaults>,
+}
+
+// what a project's todo start with, and how its view looks
+#[derive(Default)]
+pub struct ProjectDefaults {
+    // todo.txt words added to each new todo, e.g. `@office est:30m`
+    pub tags: Option<String>,
+    pub priority: Option<char>,
+    // a sort mode name, or a sort expression as in `Config::sort`, used while
+    // the list keeps its manual order
+    pub sort: Option<String>,
+    pub sort_by: Vec<sorting::Key>,
+    pub show_done: Option<bool>,
+}
+
+// `[escalate]`: open todo left untouched for `after` days go up one priority,
+// to `ceiling` at most; with `start` set, todo without a priority get that one
+pub struct Escalation {
+    // 0 turns it off
+    pub after: i64,
+    pub ceiling: char,
+    pub start: Option<char>,
+}
+
+// shell commands and a webhook run on task events; `{title}` and friends in
+// them are filled in from the task
+#[derive(Default)]
+pub struct Hooks {
+    pub added: Option<String>,
+    pub completed: Option<String>,
+    pub overdue: Option<String>,
+    // POSTed on every event
+    pub webhook: Option<String>,
+    // defaults to the task as JSON plus its "event"
+    pub webhook_body: Option<String>,
+    // a JSON line is appended here for every completion
+    pub completion_log: Option<PathBuf>,
+}
+
+// `[layout]`: pane widths in percent of the screen, the list gets the rest
+pub struct LayoutConfig {
+    pub sidebar: u16,
+    pub detail: u16,
+    // the key help line; the filter, prompts and messages still show
+    pub help: bool,
+}
+
+impl LayoutConfig {
+    // each pane within its bounds and the two together leaving the list at
+    // least a fifth of the screen; the detail pane gives way first
+    pub fn fit(&mut self) {
+        self.sidebar = self.sidebar.clamp(10, 50);
+        self.detail = self.detail.clamp(10, 60.min(80 - self.sidebar));
+    }
+}
+
+#[derive(Clone)]
+pub struct WebDavConfig {
+    // full URL of the file, e.g. https://cloud.example.com/remote.php/dav/files/me/todo.txt
+    pub url: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            stale_days: 30,
+            hide_done: false,
+            archive_after: 0,
+            escalate: Escalation { after: 0, ceiling: 'A', start: None },
+            backups: 10,
+            code_theme: "InspiredGitHub".to_string(),
+            max_length: 500,
+            capacity: None,
+            quiet_hours: None,
+            summary_dir: paths::data_dir().join("summaries"),
+            api_token: None,
+            git: false,
+            activity_log: false,
+            webdav: None,
+            todoist_token: None,
+            mstodo_client_id: None,
+            mstodo_projects: vec![],
+            sync_peers: vec![],
+            sync_interval: 300,
+            hooks: Hooks::default(),
+            urgency: Coefficients::default(),
+            sort: vec![],
+            rules: vec![],
+            layout: LayoutConfig { sidebar: 20, detail: 30, help: true },
+            keys: vec![],
+            which_key: 3,
+            leader: "\\".to_string(),
+            key_timeout: 1000,
+            enhanced_keys: false,
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+// one `action = "keys"` line, of `[keys.<pane>]` when it has a pane
+pub struct KeyEntry {
+    pub pane: Option<String>,
+    pub action: String,
+    pub keys: String,
+    // of the config, counted from 1
+    pub line: usize,
+}
+
+// one `key = value` line, with the `[section]` it sits under
+pub struct Entry {
+    // counted from 1
+    pub line: usize,
+    pub section: String,
+    pub key: String,
+    pub value: Value,
+}
+
+impl Config {
+    pub fn load() -> io::Result<Config> {
+        let text = match fs::read_to_string(paths::config_file()) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err),
+        };
+        let mut config = Config::default();
+        let mut webdav = WebDavConfig { url: String::new(), user: None, password: None };
+        for entry in parse(&text) {
+            if let Some(name) = entry.section.strip_prefix("project.") {
+                let defaults = config.projects.entry(unquote(name).to_string()).or_default();
+                match (entry.key.as_str(), entry.value) {
+                    ("tags", Value::Str(tags)) => defaults.tags = Some(tags),
+                    ("priority", Value::Str(p)) => defaults.priority = p.chars().next().filter(char::is_ascii_uppercase),
+                    ("sort", Value::Str(sort)) => {
+                        defaults.sort_by = sorting::parse(&sort);
+                        defaults.sort = Some(sort);
+                    }
+                    ("sort", Value::List(terms)) => {
+                        defaults.sort_by = sorting::parse(&terms.join(","));
+                        defaults.sort = Some(terms.join(", "));
+                    }
+                    ("show_done", Value::Bool(show)) => defaults.show_done = Some(show),
+                    _ => {}
+                }
+                continue;
+            }
+            if entry.section == "rules" {
+                if let Some(rule) = match entry.value {
+                    Value::Str(rule) => Rule::parse(&entry.key, &rule),
+                    _ => None,
+                } {
+                    config.rules.push(rule);
+                }
+                continue;
+            }
+            if entry.section == "keys" || entry.section.starts_with("keys.") {
+                if let Value::Str(keys) = entry.value {
+                    let pane = entry.section.strip_prefix("keys.").map(str::to_string);
+                    config.keys.push(KeyEntry { pane, action: entry.key, keys, line: entry.line });
+                }
+                continue;
+            }
+            if entry.section == "urgency" {
+                match entry.value {
+                    Value::Int(n) => config.urgency.set(&entry.key, n as f64),
+                    Value::Float(n) => config.urgency.set(&entry.key, n),
+                    _ => {}
+                }
+                continue;
+            }
+            match (entry.section.as_str(), entry.key.as_str(), entry.value) {
+                ("", "stale_days", Value::Int(days)) => config.stale_days = days,
+                ("", "hide_done", Value::Bool(hide)) => config.hide_done = hide,
+                ("", "archive_after", Value::Int(days)) => config.archive_after = days,
+                ("escalate", "after", Value::Int(days)) => config.escalate.after = days,
+                ("escalate", "ceiling", Value::Str(p)) => {
+                    config.escalate.ceiling = p.chars().next().filter(char::is_ascii_uppercase).unwrap_or('A')
+                }
+                ("escalate", "start", Value::Str(p)) => config.escalate.start = p.chars().next().filter(char::is_ascii_uppercase),
+                ("", "backups", Value::Int(count)) => config.backups = count,
+                ("", "max_length", Value::Int(n)) => config.max_length = n.max(0) as usize,
+                ("", "capacity", Value::Str(capacity)) => config.capacity = task::parse_minutes(&capacity),
+                ("", "sort", Value::Str(sort)) => config.sort = sorting::parse(&sort),
+                ("", "sort", Value::List(terms)) => config.sort = sorting::parse(&terms.join(",")),
+                ("", "quiet_hours", Value::Str(hours)) => config.quiet_hours = parse_hours(&hours),
+                ("", "code_theme", Value::Str(theme)) => config.code_theme = theme,
+                ("", "summary_dir", Value::Str(dir)) => config.summary_dir = paths::expand(&dir),
+                ("", "api_token", Value::Str(token)) => config.api_token = Some(token),
+                ("", "git", Value::Bool(git)) => config.git = git,
+                ("", "activity_log", Value::Bool(keep)) => config.activity_log = keep,
+                ("", "which_key", Value::Int(secs)) => config.which_key = secs.max(0) as u64,
+                ("", "leader", Value::Str(keys)) => config.leader = keys,
+                ("", "key_timeout", Value::Int(ms)) => config.key_timeout = ms.max(0) as u64,
+                ("", "enhanced_keys", Value::Bool(on)) => config.enhanced_keys = on,
+                ("webdav", "url", Value::Str(url)) => webdav.url = url,
+                ("webdav", "user", Value::Str(user)) => webdav.user = Some(user),
+                ("webdav", "password", Value::Str(password)) => webdav.password = Some(password),
+                ("todoist", "token", Value::Str(token)) => config.todoist_token = Some(token),
+                ("mstodo", "client_id", Value::Str(id)) => config.mstodo_client_id = Some(id),
+                ("mstodo", "projects", Value::Str(projects)) => {
+                    config.mstodo_projects = projects.split_whitespace().map(str::to_string).collect()
+                }
+                ("sync", "peers", Value::Str(peers)) => config.sync_peers = peers.split_whitespace().map(str::to_string).collect(),
+                ("sync", "interval", Value::Int(secs)) => config.sync_interval = secs,
+                ("hooks", "added", Value::Str(command)) => config.hooks.added = Some(command),
+                ("hooks", "completed", Value::Str(command)) => config.hooks.completed = Some(command),
+                ("hooks", "overdue", Value::Str(command)) => config.hooks.overdue = Some(command),
+                ("hooks", "webhook", Value::Str(url)) => config.hooks.webhook = Some(url),
+                ("hooks", "webhook_body", Value::Str(body)) => config.hooks.webhook_body = Some(body),
+                ("hooks", "completion_log", Value::Str(path)) => config.hooks.completion_log = Some(paths::expand(&path)),
+                ("layout", "sidebar", Value::Int(width)) => config.layout.sidebar = width.clamp(0, 100) as u16,
+                ("layout", "detail", Value::Int(width)) => config.layout.detail = width.clamp(0, 100) as u16,
+                ("layout", "help", Value::Bool(help)) => config.layout.help = help,
+                _ => {}
+            }
+        }
+        if !webdav.url.is_empty() {
+            config.webdav = Some(webdav);
+        }
+        config.layout.fit();
+        Ok(config)
+    }
+    // the window may run past midnight
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        match self.quiet_hours {
+            Some((start, end)) if start <= end => start <= time && time < end,
+            Some((start, end)) => time >= start || time < end,
+            None => false,
+        }
+    }
+    // a todo typed as `input`, in `project` unless the text names one, with
+    // that project's defaults under what was typed
+    pub fn new_task(&self, input: &str, project: Option<&str>, today: NaiveDate) -> Task {
+        let typed = Task::parse(input, today);
+        let project = typed.project.clone().or(project.map(str::to_string));
+        let defaults = match project.as_ref().and_then(|p| self.projects.get(p)) {
+            Some(defaults) => defaults,
+            None => return self.with_rules(Task { project, ..typed }),
+        };
+        let mut text = String::new();
+        if let (None, Some(p)) = (typed.priority, defaults.priority) {
+            text.push_str(&format!("({}) ", p));
+        }
+        text.push_str(defaults.tags.as_deref().unwrap_or_default());
+        // the typed words come last, so they win over the defaults
+        let task = Task::parse(&format!("{} {}", text, input), today);
+        self.with_rules(Task { project, id: typed.id, ..task })
+    }
+    pub fn with_rules(&self, mut task: Task) -> Task {
+        rules::apply(&self.rules, &mut task);
+        task
+    }
+}
+
+// the small subset of TOML the config needs: sections and `key = value` lines
+// holding strings ("basic" or 'literal'), numbers, booleans or one-line
+// arrays of strings
+pub fn parse(text: &str) -> Vec<Entry> {
+    let mut section = String::new();
+    let mut entries = vec![];
+    for (n, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if let Some(value) = parse_value(value.trim()) {
+                entries.push(Entry {
+                    line: n + 1,
+                    section: section.to_string(),
+                    key: unquote(key.trim()).to_string(),
+                    value,
+                });
+            }
+        }
+    }
+    entries
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    if text.len() >= 2 && (text.starts_with('"') && text.ends_with('"') || text.starts_with('\'') && text.ends_with('\'')) {
+        return Some(Value::Str(text[1..text.len() - 1].to_string()));
+    }
+    if let Some(items) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let items = items.split(',').map(str::trim).filter(|item| !item.is_empty());
+        return items
+            .map(|item| match parse_value(item) {
+                Some(Value::Str(item)) => Some(item),
+                _ => None,
+            })
+            .collect::<Option<_>>()
+            .map(Value::List);
+    }
+    match text {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => text.parse().ok().map(Value::Int).or_else(|| text.parse().ok().map(Value::Float)),
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text)
+}
+
+// `#` starts a comment unless it is inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+// `22:00-07:00`
+fn parse_hours(text: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = text.split_once('-')?;
+    let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    Some((time(start)?, time(end)?))
+}
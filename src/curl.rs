@@ -0,0 +1,71 @@
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+// HTTPS through the system curl; the options (`user`, `header`, `data-raw`, ...)
+// go in as a curl config on stdin so tokens and passwords never show up in `ps`
+pub fn run(url: &str, options: &[(&str, String)]) -> io::Result<Reply> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "-K", "-", "-D", "-", "--url", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for (name, value) in options {
+            writeln!(stdin, "{} = \"{}\"", name, escape(value))?;
+        }
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    parse_reply(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// `-D -` writes the header block before the body, after any `100 Continue`
+fn parse_reply(text: &str) -> io::Result<Reply> {
+    let (head, body) = text.split_once("\r\n\r\n").unwrap_or((text, ""));
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP reply"))?;
+    if (100..200).contains(&status) {
+        return parse_reply(body);
+    }
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    Ok(Reply { status, headers, body: body.to_string() })
+}
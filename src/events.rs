@@ -0,0 +1,100 @@
+use std::io;
+
+use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+        EnableMouseCapture, KeyCode, KeyEvent,
+        KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+// the terminal set up for the app: raw, on the alternate screen, with the
+// mouse, pastes and focus changes, and with `enhanced` the kitty keyboard protocol. A
+// terminal without the protocol ignores the request, so it is safe to ask
+pub fn enter(enhanced: bool) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste, EnableFocusChange)?;
+    if enhanced {
+        execute!(io::stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))?;
+    }
+    Ok(())
+}
+
+// and back as it was, to quit or to run an editor
+pub fn leave(enhanced: bool) -> io::Result<()> {
+    if enhanced {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste, DisableFocusChange)
+}
+
+// the key a key event stands for, the same on every terminal; None for the
+// ones to ignore. Only presses and repeats count: Windows reports releases
+// as well, as does the kitty protocol when asked, and they would do
+// everything twice, and AltGr as Ctrl+Alt, which would make `@` on a
+// German keyboard Ctrl+Alt+@
+pub fn key(event: KeyEvent) -> Option<(KeyCode, KeyModifiers)> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+    let (code, modifiers) = (event.code, event.modifiers);
+    let altgr = KeyModifiers::CONTROL | KeyModifiers::ALT;
+    Some(match code {
+        KeyCode::Char(c) if modifiers.contains(altgr) && !c.is_ascii_alphanumeric() => (code, modifiers - altgr),
+        // a bare carriage return or line feed from the console
+        KeyCode::Char('\r' | '\n') if !modifiers.contains(KeyModifiers::CONTROL) => (KeyCode::Enter, modifiers),
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => (KeyCode::BackTab, modifiers),
+        _ => (code, modifiers),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventState;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent { code, modifiers, kind, state: KeyEventState::NONE }
+    }
+
+    #[test]
+    fn a_press_is_one_key() {
+        let press = event(KeyCode::Char('p'), KeyModifiers::NONE, KeyEventKind::Press);
+        assert_eq!(key(press), Some((KeyCode::Char('p'), KeyModifiers::NONE)));
+        let enter = event(KeyCode::Enter, KeyModifiers::NONE, KeyEventKind::Press);
+        assert_eq!(key(enter), Some((KeyCode::Enter, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn releases_are_ignored() {
+        for code in [KeyCode::Char('p'), KeyCode::Enter, KeyCode::Esc, KeyCode::Down] {
+            assert_eq!(key(event(code, KeyModifiers::NONE, KeyEventKind::Release)), None);
+        }
+    }
+
+    #[test]
+    fn held_keys_repeat() {
+        let held = event(KeyCode::Down, KeyModifiers::NONE, KeyEventKind::Repeat);
+        assert_eq!(key(held), Some((KeyCode::Down, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn altgr_types_its_character() {
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        assert_eq!(key(event(KeyCode::Char('@'), ctrl_alt, KeyEventKind::Press)), Some((KeyCode::Char('@'), KeyModifiers::NONE)));
+        assert_eq!(key(event(KeyCode::Char('{'), ctrl_alt, KeyEventKind::Press)), Some((KeyCode::Char('{'), KeyModifiers::NONE)));
+        // a letter with both is a real Ctrl+Alt binding
+        assert_eq!(key(event(KeyCode::Char('x'), ctrl_alt, KeyEventKind::Press)), Some((KeyCode::Char('x'), ctrl_alt)));
+    }
+
+    #[test]
+    fn console_keys_match_the_unix_ones() {
+        let cr = event(KeyCode::Char('\r'), KeyModifiers::NONE, KeyEventKind::Press);
+        assert_eq!(key(cr), Some((KeyCode::Enter, KeyModifiers::NONE)));
+        let shift_tab = event(KeyCode::Tab, KeyModifiers::SHIFT, KeyEventKind::Press);
+        assert_eq!(key(shift_tab), Some((KeyCode::BackTab, KeyModifiers::SHIFT)));
+    }
+}
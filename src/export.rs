@@ -0,0 +1,122 @@
+use chrono::NaiveDateTime;
+
+use crate::{format, ical, org, task::Task};
+
+// the formats `export` writes
+#[derive(Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Org,
+    Ical,
+    TodoTxt,
+    Html,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "md" | "markdown" => Some(Format::Markdown),
+            "org" => Some(Format::Org),
+            "ics" | "ical" => Some(Format::Ical),
+            "txt" | "todo.txt" => Some(Format::TodoTxt),
+            "html" => Some(Format::Html),
+            _ => None,
+        }
+    }
+    // what a file of it ends in
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Org => "org",
+            Format::Ical => "ics",
+            Format::TodoTxt => "txt",
+            Format::Html => "html",
+        }
+    }
+}
+
+pub fn render(tasks: &[Task], format: Format, now_utc: NaiveDateTime) -> String {
+    match format {
+        Format::Markdown => markdown(tasks),
+        Format::Org => org::export(tasks),
+        Format::Ical => ical::export(tasks, false, now_utc),
+        Format::Html => html(tasks, now_utc),
+        Format::TodoTxt => {
+            let mut text = format::header();
+            for task in tasks {
+                text.push_str(&task.to_line());
+                text.push('\n');
+            }
+            text
+        }
+    }
+}
+
+// a checkbox list, inbox todo first and then a heading per project
+fn markdown(tasks: &[Task]) -> String {
+    let mut projects: Vec<Option<&String>> = tasks.iter().map(|t| t.project.as_ref()).collect();
+    projects.sort();
+    projects.dedup();
+    let mut text = String::new();
+    for project in projects {
+        if let Some(project) = project {
+            text.push_str(&format!("{}## {}\n\n", if text.is_empty() { "" } else { "\n" }, project));
+        }
+        for task in tasks.iter().filter(|t| t.project.as_ref() == project) {
+            text.push_str(&format!("- [{}] {}\n", if task.is_done() { "x" } else { " " }, task.text()));
+        }
+    }
+    text
+}
+
+// a standalone page to share: inbox todo first and then a section per
+// project, completed ones struck through
+fn html(tasks: &[Task], now_utc: NaiveDateTime) -> String {
+    let mut text = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Todo</title>\n<style>\n",
+        "body { font-family: sans-serif; max-width: 48em; margin: 2em auto; color: #222; }\n",
+        "h2 { border-bottom: 1px solid #ccc; padding-bottom: 0.2em; }\n",
+        "ul { list-style: none; padding: 0; }\n",
+        "li { padding: 0.2em 0; }\n",
+        ".done { text-decoration: line-through; color: #999; }\n",
+        ".priority { font-weight: bold; }\n",
+        ".context { color: #08a; }\n",
+        ".due { color: #666; font-size: 0.9em; }\n",
+        ".overdue { color: #c00; }\n",
+        "footer { color: #999; font-size: 0.8em; margin-top: 2em; }\n",
+        "</style>\n</head>\n<body>\n<h1>Todo</h1>\n",
+    ));
+    let today = now_utc.date();
+    let mut projects: Vec<Option<&String>> = tasks.iter().map(|t| t.project.as_ref()).collect();
+    projects.sort();
+    projects.dedup();
+    for project in projects {
+        let todo: Vec<&Task> = tasks.iter().filter(|t| t.project.as_ref() == project).collect();
+        let done = todo.iter().filter(|t| t.is_done()).count();
+        let name = project.map_or("Inbox", |p| p.as_str());
+        text.push_str(&format!("<h2>{} <small>({} of {} done)</small></h2>\n<ul>\n", escape(name), done, todo.len()));
+        for task in todo {
+            let class = if task.is_done() { " class=\"done\"" } else { "" };
+            text.push_str(&format!("<li{}>{} ", class, if task.is_done() { "&#9745;" } else { "&#9744;" }));
+            if let Some(p) = task.priority {
+                text.push_str(&format!("<span class=\"priority\">({})</span> ", p));
+            }
+            text.push_str(&escape(&task.title));
+            for context in &task.contexts {
+                text.push_str(&format!(" <span class=\"context\">@{}</span>", escape(context)));
+            }
+            if let Some(due) = task.due {
+                let overdue = if task.is_overdue(today) { " overdue" } else { "" };
+                text.push_str(&format!(" <span class=\"due{}\">due {}</span>", overdue, due));
+            }
+            text.push_str("</li>\n");
+        }
+        text.push_str("</ul>\n");
+    }
+    text.push_str(&format!("<footer>Exported from todo-tui on {} UTC</footer>\n</body>\n</html>\n", now_utc.format("%Y-%m-%d %H:%M")));
+    text
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
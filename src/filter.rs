@@ -0,0 +1,84 @@
+use chrono::NaiveDate;
+
+use crate::task::{self, Label, Task};
+
+enum Term {
+    Stale,
+    Overdue,
+    Done,
+    Pending,
+    Label(Label),
+    Context(String),
+    Project(String),
+    Location(String),
+    // `key:value`, or `key:` for any value
+    Field(String, String),
+    Text(String),
+}
+
+// space separated terms that must all match, e.g. `is:stale report`
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Filter {
+        let terms = query
+            .split_whitespace()
+            .map(|word| match word {
+                "is:stale" => Term::Stale,
+                "is:overdue" => Term::Overdue,
+                "is:done" => Term::Done,
+                "is:pending" => Term::Pending,
+                _ if word.len() > 1 && word.starts_with('@') => Term::Context(word[1..].to_string()),
+                _ if word.len() > 1 && word.starts_with('+') => Term::Project(word[1..].to_string()),
+                _ if word.len() > 4 && word.starts_with("loc:") => Term::Location(word[4..].to_lowercase()),
+                _ => match (word.strip_prefix("label:").and_then(Label::parse), word.split_once(':')) {
+                    (Some(label), _) => Term::Label(label),
+                    (None, Some((key, value))) if task::is_field_key(key) && !value.starts_with('/') => Term::Field(key.to_string(), value.to_lowercase()),
+                    _ => Term::Text(word.to_lowercase()),
+                },
+            })
+            .collect();
+        Filter { terms }
+    }
+    // `--filter <terms>...` among command arguments, up to the next `--`
+    // flag; the other arguments, and the filter (empty without the flag)
+    pub fn take_arg<'a>(args: &[&'a str]) -> (Vec<&'a str>, Filter) {
+        let mut rest = vec![];
+        let mut terms = vec![];
+        let mut in_filter = false;
+        for &arg in args {
+            match arg {
+                "--filter" => in_filter = true,
+                _ if arg.starts_with("--") => {
+                    in_filter = false;
+                    rest.push(arg);
+                }
+                _ if in_filter => terms.push(arg),
+                _ => rest.push(arg),
+            }
+        }
+        (rest, Filter::parse(&terms.join(" ")))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+    pub fn only_stale(&self) -> bool {
+        matches!(self.terms.as_slice(), [Term::Stale])
+    }
+    pub fn matches(&self, task: &Task, today: NaiveDate, stale_days: i64) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Stale => task.is_stale(today, stale_days),
+            Term::Overdue => task.is_overdue(today),
+            Term::Done => task.is_done(),
+            Term::Pending => !task.is_done(),
+            Term::Label(label) => task.label == Some(*label),
+            Term::Context(name) => task.contexts.contains(name),
+            Term::Project(name) => task.project.as_ref() == Some(name),
+            Term::Location(place) => task.location.as_ref().is_some_and(|l| l.to_lowercase() == *place),
+            Term::Field(key, value) => task.field(key).is_some_and(|v| value.is_empty() || v.to_lowercase() == *value),
+            Term::Text(text) => task.title.to_lowercase().contains(text),
+        })
+    }
+}
@@ -0,0 +1,69 @@
+// todo.txt and done.txt start with a `# todo-tui format N` line naming the
+// format their lines are in; files from before it are format 0
+const HEADER: &str = "# todo-tui format ";
+
+// MIGRATIONS[n] rewrites a line of format n into format n + 1, so a change to
+// the line format (a renamed key, a new field's default) is one more entry
+const MIGRATIONS: [fn(&str) -> String; 1] = [
+    // format 0 had the same lines, only without the header
+    |line| line.to_string(),
+];
+
+pub const VERSION: u32 = MIGRATIONS.len() as u32;
+
+pub fn header() -> String {
+    format!("{}{}\n", HEADER, VERSION)
+}
+
+pub fn is_header(line: &str) -> bool {
+    line.starts_with(HEADER)
+}
+
+pub fn version(text: &str) -> u32 {
+    text.lines().next().and_then(|line| line.strip_prefix(HEADER)).and_then(|v| v.trim().parse().ok()).unwrap_or(0)
+}
+
+// the lines of `text` in the current format, without the header; lines
+// newer than this build are kept as they are
+pub fn upgrade(text: &str) -> String {
+    let from = version(text) as usize;
+    let mut body = String::new();
+    for line in text.lines().filter(|line| !is_header(line) && !line.trim().is_empty()) {
+        let line = MIGRATIONS.iter().skip(from).fold(line.to_string(), |line, migrate| migrate(&line));
+        body.push_str(&line);
+        body.push('\n');
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_version() {
+        assert_eq!(version("# todo-tui format 1\ncall mum\n"), 1);
+        assert_eq!(version("# todo-tui format 7 \n"), 7);
+        assert_eq!(version("call mum\n"), 0);
+        assert_eq!(version(""), 0);
+        assert_eq!(version("# todo-tui format soon\n"), 0);
+        // only the first line is the header
+        assert_eq!(version("call mum\n# todo-tui format 1\n"), 0);
+    }
+
+    #[test]
+    fn upgrades_to_the_current_format() {
+        assert_eq!(upgrade("call mum\n\n  \nx 2026-06-01 pay rent\n"), "call mum\nx 2026-06-01 pay rent\n");
+        assert_eq!(upgrade("# todo-tui format 1\ncall mum"), "call mum\n");
+        assert_eq!(upgrade(""), "");
+    }
+
+    #[test]
+    fn the_header_round_trips() {
+        let text = header() + "call mum\n";
+        assert!(is_header(&header()));
+        assert_eq!(version(&text), VERSION);
+        assert_eq!(upgrade(&text), "call mum\n");
+        assert_eq!(header() + upgrade(&text).as_str(), text);
+    }
+}
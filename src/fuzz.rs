@@ -0,0 +1,112 @@
+// malformed input thrown at the parsers, to show none of them panics on what
+// a hand-edited todo.txt, a mistyped filter or the add popup can hold. A
+// failing case is shrunk to a small one and saved under proptest-regressions/,
+// so it runs first from then on
+use chrono::NaiveDate;
+use proptest::{prelude::*, sample::select};
+
+use crate::{filter::Filter, org, quick, sorting, sync, task::{self, Task}};
+
+const CASES: u32 = 3000;
+
+// bits of the syntax the parsers look for, with the edges they tend to trip on
+const PIECES: &[&str] = &[
+    "x ", "x", "(A) ", "(Z)", "(", ")", "+", "+Work", "@", "@home", "due:", "due:2026-02-30", "due:2026-13-01",
+    "due:9999-12-31", "due:0000-01-01", "id:", "est:", "est:90m", "est:1h30m", "rec:", "remind:",
+    "remind:2026-01-01T25:61", "touched:", "done:", "pri:", "label:", "label:red", "loc:", "note:", "check:", "[ ]",
+    "[x]", "key:value", ":", "::", "-", "--", "2026-", "2026-06-01", "12:99", "3pm", "13pm", "at", "9:30am", "today",
+    "tomorrow", "next", "week", "friday", "on", "in", "-3", "0", "days", "#", "#tag", "!",
+    "!high", "is:", "is:stale", "is:overdue", "is:done", "\\", "\\n", "\\\\", "\"", "'", ",", ", ", "desc", "asc",
+    " ", "  ", "\t", "\n", "\r\n", "\r", "é", "かが\u{3099}", "\u{200b}", "\u{301}", "🙂", "👩‍👩‍👧", "\u{0}", "\u{feff}",
+];
+
+// small, near the edges of u32 and past them, where sums and products wrap
+fn number() -> impl Strategy<Value = u64> {
+    prop_oneof![0u64..100, 0u64..100_000_000, (u32::MAX as u64 / 60 - 2)..(u32::MAX as u64 + 2), any::<u64>()]
+}
+
+// a word with a number in it, in each of the places one is read
+fn numbered() -> impl Strategy<Value = String> {
+    let forms = select(&["est:{}", "est:{}m", "est:{}h", "est:{}h{}m", "in {} days", "in {} weeks", "due:{}-{}-{}", "{}pm", "{}:{}", ":Effort: {}:{}"][..]);
+    (forms, number(), number(), number()).prop_map(|(form, a, b, c)| {
+        let mut numbers = [a, b, c].into_iter();
+        form.split("{}").enumerate().map(|(i, part)| if i == 0 { part.to_string() } else { format!("{}{}", numbers.next().unwrap_or(0), part) }).collect()
+    })
+}
+
+// pieces, numbers and odd characters run together, now and then with no
+// spaces
+fn text() -> impl Strategy<Value = String> {
+    let word = prop_oneof![4 => select(PIECES).prop_map(str::to_string), 2 => numbered(), 1 => any::<char>().prop_map(String::from)];
+    prop::collection::vec((word, any::<bool>()), 0..12)
+        .prop_map(|words| words.into_iter().map(|(word, space)| if space { word + " " } else { word }).collect())
+}
+
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+}
+
+// a few well-formed lines cut short or spliced with junk
+fn lines() -> impl Strategy<Value = String> {
+    const GOOD: [&str; 3] = [
+        "(A) 2026-01-02 pay rent +Home @desk due:2026-06-03 est:15m id:abc",
+        "x 2026-05-01 2026-04-01 ship the release +Work pri:B done:2026-05-01T10:00 id:def",
+        "2026-01-01 call mum remind:2026-06-01T09:00 label:red loc:home check:[x]milk id:ghi",
+    ];
+    let line = (select(&GOOD[..]), 0..100usize, text()).prop_map(|(line, at, junk)| {
+        let cut = line.len() * at / 100;
+        format!("{}{}{}", &line[..cut], junk, &line[cut..])
+    });
+    (prop::collection::vec(line, 0..6), any::<bool>()).prop_map(|(lines, crlf)| lines.join(if crlf { "\r\n" } else { "\n" }))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn todo_lines(text in prop_oneof![text(), lines()]) {
+        let tasks = sync::from_text(&text, today());
+        sync::from_text(&sync::to_text(&tasks), today());
+        for task in &tasks {
+            task.progress();
+            task.is_overdue(today());
+            task.is_stale(today(), 30);
+            task.age(today());
+        }
+        Task::parse(&text, today());
+        org::import(&text, today());
+    }
+
+    #[test]
+    fn filter_queries(query in text(), tasks in lines(), stale in -10i64..50) {
+        let tasks = sync::from_text(&tasks, today());
+        let filter = Filter::parse(&query);
+        for task in &tasks {
+            filter.matches(task, today(), stale);
+        }
+        let args: Vec<&str> = query.split_whitespace().collect();
+        Filter::take_arg(&args);
+        sorting::parse(&query);
+    }
+
+    #[test]
+    fn natural_dates(input in text()) {
+        for natural in [true, false] {
+            let tokens = quick::tokens(&input, today(), natural);
+            for t in &tokens {
+                prop_assert!(t.start <= t.end && input.is_char_boundary(t.start) && input.is_char_boundary(t.end));
+            }
+            quick::summary(&input, &tokens);
+        }
+        quick::expand(&input, today());
+        task::parse_minutes(&input);
+        task::unescape(&input);
+    }
+
+    // what the popup reads from a number is what it would have counted
+    #[test]
+    fn estimates_never_wrap(hours in number(), minutes in number()) {
+        let expected = hours.checked_mul(60).and_then(|h| h.checked_add(minutes)).filter(|&m| m <= u32::MAX as u64 && hours <= u32::MAX as u64 && minutes <= u32::MAX as u64);
+        prop_assert_eq!(task::parse_minutes(&format!("{}h{}m", hours, minutes)).map(u64::from), expected);
+    }
+}
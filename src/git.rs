@@ -0,0 +1,71 @@
+use std::{
+    io,
+    path::Path,
+    process::{Command, Output},
+};
+
+use crate::{config::Config, paths};
+
+pub struct Commit {
+    pub hash: String,
+    pub date: String,
+    pub subject: String,
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<Output> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+fn stdout(output: Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// commit todo.txt in the data directory when `git = true`, creating the repo
+// on first use
+pub fn commit_if_enabled(config: &Config, message: &str) -> io::Result<()> {
+    if !config.git {
+        return Ok(());
+    }
+    let dir = paths::data_dir();
+    if !dir.join(".git").exists() {
+        git(&dir, &["init", "--quiet"])?;
+    }
+    git(&dir, &["add", "todo.txt"])?;
+    // nothing staged means nothing changed since the last commit
+    if git(&dir, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(());
+    }
+    git(
+        &dir,
+        &["-c", "user.name=todo-tui", "-c", "user.email=todo-tui@localhost", "commit", "--quiet", "-m", message],
+    )?;
+    Ok(())
+}
+
+pub fn log() -> io::Result<Vec<Commit>> {
+    let output = git(&paths::data_dir(), &["log", "--format=%h%x09%ad%x09%s", "--date=format:%Y-%m-%d %H:%M", "--", "todo.txt"])?;
+    Ok(stdout(output)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            Some(Commit {
+                hash: parts.next()?.to_string(),
+                date: parts.next()?.to_string(),
+                subject: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+pub fn diff(hash: &str) -> io::Result<String> {
+    git(&paths::data_dir(), &["show", "--format=", hash, "--", "todo.txt"]).map(stdout)
+}
+
+pub fn file_at(hash: &str) -> io::Result<String> {
+    git(&paths::data_dir(), &["show", &format!("{}:todo.txt", hash)]).map(stdout)
+}
@@ -0,0 +1,441 @@
+// the app run headless for end-to-end tests: keys go in through the same
+// event handling as in the terminal, and a test looks at the frame drawn
+// after each one and at what was saved to todo.txt
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::TestBackend, Terminal};
+
+use crate::{format, handle_event, keymap, load_app, paths, state::State, sync, tick, ui, App};
+
+// the data directory is an environment variable, so only one harness runs
+// at a time
+static RUNNING: Mutex<()> = Mutex::new(());
+static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Harness {
+    pub terminal: Terminal<TestBackend>,
+    pub app: App,
+    dir: PathBuf,
+    // false once a key quit the app
+    pub running: bool,
+    _running: MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    // started on a data directory of its own holding `todo` and `config`,
+    // past the what's new screen
+    pub fn start(todo: &str, config: &str) -> Harness {
+        let running = RUNNING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let n = DIRS.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("todo-tui-test-{}-{}", std::process::id(), n));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        env::set_var("TODO_TUI_DIR", &dir);
+        fs::write(paths::todo_file(), todo).unwrap();
+        fs::write(dir.join("config.toml"), config).unwrap();
+        State { version: Some(env!("CARGO_PKG_VERSION").to_string()), ..State::default() }.save().unwrap();
+        let app = load_app().unwrap();
+        let mut harness = Harness { terminal: Terminal::new(TestBackend::new(100, 30)).unwrap(), app, dir, running: true, _running: running };
+        harness.draw();
+        harness
+    }
+    // keys as the config writes them, a space between each: `p`, `Enter`,
+    // `Ctrl+z`, `Space`
+    pub fn press(&mut self, keys: &str) {
+        for spec in keys.split_whitespace() {
+            let (code, modifiers) = keymap::parse_key(spec).unwrap_or_else(|| panic!("no such key: {}", spec));
+            self.key(code, modifiers);
+        }
+    }
+    // each character a key of its own, the way a person types
+    pub fn typed(&mut self, text: &str) {
+        for c in text.chars() {
+            let modifiers = if c.is_uppercase() { KeyModifiers::SHIFT } else { KeyModifiers::NONE };
+            self.key(KeyCode::Char(c), modifiers);
+        }
+    }
+    pub fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+    pub fn event(&mut self, event: Event) {
+        assert!(self.running, "the app has quit");
+        self.running = handle_event(&mut self.terminal, &mut self.app, event).unwrap()
+            && tick(&mut self.terminal, &mut self.app).unwrap();
+        if self.running {
+            self.draw();
+        }
+    }
+    // the left button pressed or let go over the row showing `text`
+    pub fn mouse(&mut self, kind: MouseEventKind, text: &str) {
+        let screen = self.screen();
+        let row = screen.lines().position(|line| line.contains(text)).unwrap_or_else(|| panic!("{:?} is not on screen:\n{}", text, screen));
+        let column = screen.lines().nth(row).and_then(|line| line.find(text)).unwrap_or(0);
+        self.event(Event::Mouse(MouseEvent { kind, column: column as u16, row: row as u16, modifiers: KeyModifiers::NONE }));
+    }
+    fn draw(&mut self) {
+        let app = &mut self.app;
+        self.terminal.draw(|f| ui(f, app)).unwrap();
+    }
+    // the last frame, a line of text per row
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    pub fn assert_shows(&self, text: &str) {
+        let screen = self.screen();
+        assert!(screen.contains(text), "{:?} is not on screen:\n{}", text, screen);
+    }
+    pub fn assert_hides(&self, text: &str) {
+        let screen = self.screen();
+        assert!(!screen.contains(text), "{:?} is on screen:\n{}", text, screen);
+    }
+    // the frame is `expected`, leaving out the blank lines above and below
+    pub fn assert_screen(&self, expected: &str) {
+        let screen = self.screen();
+        let lines: Vec<&str> = screen.lines().collect();
+        let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+        let last = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+        let shown = lines[first..last.max(first)].join("\n");
+        assert!(shown == expected.trim_matches('\n'), "the screen changed:\n{}\n\nexpected:\n{}", shown, expected);
+    }
+    // todo.txt as saved, a line per todo
+    pub fn stored(&self) -> Vec<String> {
+        let text = fs::read_to_string(paths::todo_file()).unwrap_or_default();
+        text.lines().filter(|line| !format::is_header(line)).map(str::to_string).collect()
+    }
+    pub fn stored_line(&self, title: &str) -> String {
+        let lines = self.stored();
+        lines.iter().find(|line| line.contains(title)).cloned().unwrap_or_else(|| panic!("{:?} is not saved in {:?}", title, lines))
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+#[test]
+fn add_edit_complete_undo() {
+    let mut h = Harness::start("", "");
+    h.press("p");
+    h.typed("buy milk +Home");
+    h.press("Enter");
+    h.assert_shows("buy milk");
+    assert!(h.stored_line("buy milk").contains("+Home"));
+
+    h.press("Down t");
+    h.press("Ctrl+u");
+    h.typed("buy oat milk +Home");
+    h.press("Enter");
+    h.assert_shows("oat milk");
+    assert_eq!(h.stored().len(), 1);
+
+    h.press("Space");
+    assert!(h.stored_line("oat milk").starts_with("x "));
+
+    h.press("u");
+    h.assert_shows("Undid: completed 'buy oat milk'");
+    assert!(!h.stored_line("oat milk").starts_with("x "));
+}
+
+#[test]
+fn edits_the_label() {
+    let mut h = Harness::start("2026-01-01 call mum label:red id:a\n", "");
+    h.press("Down t");
+    h.assert_shows("call mum label:red");
+    h.press("Ctrl+u");
+    h.typed("call mum label:blue");
+    h.press("Enter");
+    assert!(h.stored_line("call mum").contains("label:blue"));
+
+    h.press("t Ctrl+u");
+    h.typed("call mum");
+    h.press("Enter");
+    assert!(!h.stored_line("call mum").contains("label:"));
+}
+
+#[test]
+fn filter_then_cut_and_paste() {
+    let mut h = Harness::start("2026-01-01 call mum id:a\n2026-01-01 pay rent +Home id:b\n2026-01-01 water plants +Home id:c\n", "");
+    h.press("/");
+    h.typed("+Home");
+    h.press("Enter");
+    h.assert_hides("call mum");
+    h.assert_shows("pay rent");
+
+    h.press("Down d d");
+    h.assert_hides("pay rent");
+    assert_eq!(h.stored().len(), 2);
+    // the selection moved on to the next todo
+    h.assert_shows(">> water plants");
+
+    h.press("P");
+    h.assert_shows("pay rent");
+    assert_eq!(h.stored().len(), 3);
+}
+
+#[test]
+fn quits_on_esc() {
+    let mut h = Harness::start("2026-01-01 call mum id:a\n", "");
+    h.assert_shows("call mum");
+    h.press("Esc");
+    assert!(!h.running);
+}
+
+#[test]
+fn sorting_keeps_the_manual_order() {
+    let mut h = Harness::start("2026-01-01 pay rent due:2026-03-01 id:a\n2026-01-01 call mum due:2026-02-01 id:b\n", "");
+    h.press("s s");
+    h.assert_shows("sort: due");
+    assert!(h.screen().find("call mum") < h.screen().find("pay rent"));
+    assert!(h.stored()[0].contains("pay rent"));
+    h.press("s s s s");
+    h.assert_shows("sort: manual");
+    assert!(h.screen().find("pay rent") < h.screen().find("call mum"));
+}
+
+// the Windows console sends a release after each press
+#[test]
+fn adds_once_with_key_releases() {
+    let mut h = Harness::start("", "");
+    let keys = [KeyCode::Char('p'), KeyCode::Char('t'), KeyCode::Char('e'), KeyCode::Char('a'), KeyCode::Enter];
+    for code in keys {
+        h.key(code, KeyModifiers::NONE);
+        h.event(Event::Key(KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Release, state: KeyEventState::NONE }));
+    }
+    assert_eq!(h.stored().len(), 1);
+    h.assert_shows("tea");
+    h.assert_hides("ttee");
+}
+
+// as a terminal with the kitty keyboard protocol sends them
+#[test]
+fn shift_enter_adds_and_keeps_typing() {
+    let mut h = Harness::start("", "enhanced_keys = true\n");
+    assert!(h.app.config.enhanced_keys);
+    h.press("p");
+    h.typed("buy milk");
+    h.press("Shift+Enter");
+    h.typed("buy bread");
+    h.press("Shift+Enter");
+    assert_eq!(h.stored().len(), 2);
+    h.assert_shows("Add TODO");
+    h.press("Esc");
+    h.assert_hides("Add TODO");
+    h.assert_shows("buy bread");
+}
+
+#[test]
+fn pastes_into_the_filter() {
+    let mut h = Harness::start("2026-01-01 call mum id:a\n2026-01-01 pay rent +Home id:b\n", "");
+    // nothing to type into: not read as keys either
+    h.event(Event::Paste("dd".to_string()));
+    assert_eq!(h.stored().len(), 2);
+    h.press("/");
+    h.event(Event::Paste("pay\nrent".to_string()));
+    h.assert_shows("Filter: pay rent");
+    h.assert_hides("call mum");
+}
+
+#[test]
+fn tracks_focus() {
+    let mut h = Harness::start("2026-01-01 call mum id:a\n", "");
+    h.event(Event::FocusLost);
+    assert!(!h.app.focused);
+    h.event(Event::FocusGained);
+    assert!(h.app.focused);
+    h.assert_shows("call mum");
+}
+
+// whole frames, to show a change to the drawing code draws the same; dates
+// and times are left out as they move on
+const SNAPSHOT_TODO: &str = "(A) 2026-01-01 pay rent +Home @desk id:a\n2026-01-01 water plants +Home id:b\nx 2026-01-02 2026-01-01 call mum id:c\n";
+
+#[test]
+fn snapshot_list() {
+    let mut h = Harness::start(SNAPSHOT_TODO, "");
+    h.terminal.backend_mut().resize(80, 14);
+    h.press("Down");
+    h.assert_screen(
+        r"
+  Press Esc key to exit, p to input popup, O to postpone overdue, u to undo.
+
+
+    ┌Projects────┐┌All (sort: manual)────────────────┐┌Detail──────────────┐
+    │> All       ││>> (A) pay rent  +Home  @desk     ││pay rent            │
+    │  Inbox     ││   water plants  +Home            ││                    │
+    │  Due       ││   call mum                       ││project: Home       │
+    └────────────┘└──────────────────────────────────┘└────────────────────┘
+",
+    );
+}
+
+#[test]
+fn snapshot_popup() {
+    let mut h = Harness::start(SNAPSHOT_TODO, "");
+    h.terminal.backend_mut().resize(80, 14);
+    h.press("Down p");
+    h.typed("buy milk +Home");
+    h.assert_screen(
+        r"
+  Press Esc to stop edit, Enter to add todo list.
+
+
+    ┌Projects────┐┌A┌Add TODO 14/500───────────────────────┐l──────────────┐
+    │> All       ││>│buy milk +Home                        │ent            │
+    │  Inbox     ││ │+Home                                 │               │
+    │  Due       ││ └──────────────────────────────────────┘ct: Home       │
+    └────────────┘└──────────────────────────────────┘└────────────────────┘
+",
+    );
+}
+
+#[test]
+fn snapshot_split() {
+    let mut h = Harness::start(SNAPSHOT_TODO, "");
+    h.terminal.backend_mut().resize(80, 14);
+    h.press("Down |");
+    h.assert_screen(
+        r"
+  Press Esc key to exit, p to input popup, O to postpone overdue, u to undo.
+
+
+    ┌Projects────┐┌All (sort: manua┐┌Home (sort: manu┐┌Detail──────────────┐
+    │> All       ││>> (A) pay rent ││(A) pay rent  @d││pay rent            │
+    │  Inbox     ││   water plants ││water plants    ││                    │
+    │  Due       ││   call mum     ││                ││project: Home       │
+    └────────────┘└────────────────┘└────────────────┘└────────────────────┘
+",
+    );
+}
+
+#[test]
+fn drags_the_task_picked_up() {
+    let mut h = Harness::start("first\nsecond\nthird\n", "");
+    h.mouse(MouseEventKind::Down(MouseButton::Left), "second");
+    // the list changes under the mouse: the one above goes
+    h.press("Up d d");
+    assert_eq!(h.stored().len(), 2);
+    h.mouse(MouseEventKind::Up(MouseButton::Left), "third");
+    let stored = h.stored();
+    assert!(stored[0].contains("third") && stored[1].contains("second"), "{:?}", stored);
+}
+
+#[test]
+fn wide_panes_leave_room_for_the_list() {
+    let mut h = Harness::start("call mum\n", "[layout]\nsidebar = 50\ndetail = 60\n");
+    let layout = &h.app.config.layout;
+    assert_eq!((layout.sidebar, layout.detail), (50, 30));
+    h.assert_shows("call mum");
+    h.press("Ctrl+Left Ctrl+Left");
+    let layout = &h.app.config.layout;
+    assert!(layout.sidebar + layout.detail <= 80);
+}
+
+#[test]
+fn repeats_a_cut() {
+    let mut h = Harness::start("one\ntwo\nthree\nfour\nfive\nsix\n", "");
+    h.press("Down 2 d d");
+    assert_eq!(h.stored().len(), 4);
+    h.press(".");
+    assert_eq!(h.stored().len(), 2);
+    h.press("u");
+    assert_eq!(h.stored().len(), 4);
+    // a count of its own wins over the one cut with
+    h.press("1 .");
+    assert_eq!(h.stored().len(), 3);
+}
+
+#[test]
+fn overlays_close_newest_first() {
+    let mut h = Harness::start("call mum\n", "");
+    h.press("p");
+    h.event(Event::Paste("buy milk\nbuy eggs".to_string()));
+    h.assert_shows("Add 2 todo, one per pasted line?");
+    // a conflict from a sync waits under the popup being typed in
+    let local = h.app.tasks[0].clone();
+    let remote = crate::Task { title: "call dad".to_string(), ..local.clone() };
+    h.app.add_conflicts("webdav", vec![sync::Conflict { local, remote }]);
+    h.press("Esc");
+    h.assert_hides("one per pasted line");
+    // the popup has the keys, l doesn't settle the conflict
+    h.typed(" l");
+    h.assert_shows("buy milk buy eggs l");
+    h.press("Esc");
+    h.assert_shows("On webdav");
+    h.press("Esc");
+    h.assert_hides("On webdav");
+    assert!(h.app.overlays.is_empty());
+    assert_eq!(h.stored().len(), 1);
+}
+
+#[test]
+fn whats_new_scrolls_to_the_last_note() {
+    let mut h = Harness::start("call mum\n", "");
+    h.app.overlays.push(crate::Overlay::WhatsNew(crate::changelog::since(Some("0.1.0")), 0));
+    h.draw();
+    h.assert_shows("Type due:YYYY-MM-DD in the popup");
+    h.assert_hides("Pastes in the list are ignored");
+    for _ in 0..30 {
+        h.press("PageDown");
+    }
+    h.assert_shows("Pastes in the list are ignored");
+    h.assert_hides("Type due:YYYY-MM-DD in the popup");
+    // the scroll stopped at the last line, one up is off it
+    h.press("Up");
+    h.assert_hides("Press any key to close.");
+    h.press("x");
+    assert!(h.app.overlays.is_empty());
+    assert_eq!(h.app.state.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn pastes_one_line_outside_the_add_popup() {
+    let mut h = Harness::start("call mum id:a\n", "");
+    h.press("Down Tab a");
+    h.assert_shows("Add checklist item");
+    h.event(Event::Paste("milk\neggs".to_string()));
+    h.assert_hides("one per pasted line");
+    h.press("Enter");
+    assert_eq!(h.stored().len(), 1);
+    assert!(h.stored_line("call mum").contains("milk%20eggs"));
+}
+
+#[test]
+fn split_pastes_add_only_what_enter_would() {
+    let mut h = Harness::start("call mum\n", "max_length = 10\n");
+    h.press("p");
+    h.event(Event::Paste("buy milk\n+Home\nwater the plants\nbuy eggs".to_string()));
+    h.press("y");
+    // the popup stays to say why a line was left out
+    h.assert_shows("line 2 not added: the todo needs a title as well");
+    assert_eq!(h.stored().len(), 3);
+    assert!(!h.stored().iter().any(|line| line.contains("plants")));
+}
+
+#[test]
+fn which_key_fits_a_tiny_terminal() {
+    let mut h = Harness::start("call mum\n", "");
+    for (width, height) in [(15, 6), (2, 2), (40, 3)] {
+        h.terminal.backend_mut().resize(width, height);
+        h.terminal.resize(ratatui::layout::Rect::new(0, 0, width, height)).unwrap();
+        h.press("d");
+        h.press("Esc");
+        assert!(h.running);
+    }
+}
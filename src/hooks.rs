@@ -0,0 +1,221 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{api, config::Hooks, curl, json::Json, task::Task};
+
+#[derive(Clone, Copy)]
+pub enum Event {
+    Added,
+    Completed,
+    Overdue,
+}
+
+impl Event {
+    fn name(self) -> &'static str {
+        match self {
+            Event::Added => "added",
+            Event::Completed => "completed",
+            Event::Overdue => "overdue",
+        }
+    }
+}
+
+fn fields(event: Event, task: &Task) -> Vec<(&'static str, String)> {
+    vec![
+        ("event", event.name().to_string()),
+        ("id", task.id.to_string()),
+        ("title", task.title.to_string()),
+        ("project", task.project.clone().unwrap_or_default()),
+        ("due", task.due.map(|d| d.to_string()).unwrap_or_default()),
+        ("priority", task.priority.map(String::from).unwrap_or_default()),
+        ("label", task.label.map(|l| l.name().to_string()).unwrap_or_default()),
+    ]
+}
+
+// replace `{name}` with each field, passed through `escape`, in one pass
+// over the template: a value holding `{project}` stays as it is rather than
+// being filled in again
+fn fill(template: &str, fields: &[(&str, String)], escape: fn(&str) -> String) -> String {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let field = rest[1..].find('}').and_then(|end| fields.iter().find(|(name, _)| *name == &rest[1..end + 1]));
+        match field {
+            Some((name, value)) => {
+                text.push_str(&escape(value));
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+// the environment variable a field is passed in, `TODO_TITLE` for `{title}`
+fn variable(name: &str) -> String {
+    format!("TODO_{}", name.to_uppercase())
+}
+
+// a placeholder in a command becomes the variable holding its value, which the
+// shell expands after reading the line: `"$TODO_TITLE"` for sh, and for cmd
+// `"!TODO_TITLE!"` with delayed expansion, since cmd parses `%TODO_TITLE%`
+// again. So `&`, `|`, `;` or `%PATH%` in a title are never run
+fn reference(name: &str) -> String {
+    match cfg!(windows) {
+        true => format!("\"!{}!\"", variable(name)),
+        false => format!("\"${}\"", variable(name)),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut shell = Command::new("cmd");
+    // as written: cmd has its own quoting, not the one std uses for arguments
+    shell.args(["/V:ON", "/C"]).raw_arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+// the hook command with the fields in its environment
+fn hook_command(template: &str, fields: &[(&str, String)]) -> Command {
+    let references: Vec<(&str, String)> = fields.iter().map(|(name, _)| (*name, reference(name))).collect();
+    let mut command = shell(&fill(template, &references, str::to_string));
+    command.envs(fields.iter().map(|(name, value)| (variable(name), value)));
+    command
+}
+
+// the inside of a JSON string; the template supplies the quotes
+fn json_escape(value: &str) -> String {
+    let quoted = Json::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+// the task as JSON plus its "event"
+fn event_json(event: Event, task: &Task) -> String {
+    match api::task_json(task) {
+        Json::Obj(mut obj) => {
+            obj.insert(0, ("event".to_string(), event.name().into()));
+            Json::Obj(obj).to_string()
+        }
+        json => json.to_string(),
+    }
+}
+
+// one object per line, as the webhook gets it, for jq and friends
+fn log_completion(path: &Path, task: &Task) -> io::Result<()> {
+    let line = event_json(Event::Completed, task);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+// run the hooks for `event` in the background; the app never waits for them,
+// a one-off command joins the handles before it exits
+pub fn fire(hooks: &Hooks, event: Event, task: &Task) -> Vec<thread::JoinHandle<()>> {
+    let mut running = vec![];
+    let fields = fields(event, task);
+    if let (Event::Completed, Some(path)) = (event, &hooks.completion_log) {
+        // a failed write must not get in the way of completing the todo
+        let _ = log_completion(path, task);
+    }
+    let command = match event {
+        Event::Added => &hooks.added,
+        Event::Completed => &hooks.completed,
+        Event::Overdue => &hooks.overdue,
+    };
+    if let Some(command) = command {
+        let mut shell = hook_command(command, &fields);
+        shell.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        if let Ok(mut child) = shell.spawn() {
+            running.push(thread::spawn(move || {
+                let _ = child.wait();
+            }));
+        }
+    }
+    if let Some(url) = &hooks.webhook {
+        let body = match &hooks.webhook_body {
+            Some(template) => fill(template, &fields, json_escape),
+            None => event_json(event, task),
+        };
+        let url = url.to_string();
+        running.push(thread::spawn(move || {
+            let _ = curl::run(
+                &url,
+                &[("request", "POST".to_string()), ("header", "Content-Type: application/json".to_string()), ("data-raw", body)],
+            );
+        }));
+    }
+    running
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn task(line: &str) -> Task {
+        Task::from_line(line, NaiveDate::from_ymd_opt(2026, 6, 1).unwrap())
+    }
+
+    #[test]
+    fn fills_each_placeholder() {
+        let task = task("2026-06-01 call mum +Home due:2026-06-02 id:a");
+        let text = fill("notify {title} [{project}] {due} {unknown} {", &fields(Event::Added, &task), |v| format!("<{}>", v));
+        assert_eq!(text, "notify <call mum> [<Home>] <2026-06-02> {unknown} {");
+    }
+
+    // a placeholder in a value is text, not filled in again
+    #[test]
+    fn values_are_not_filled_again() {
+        let task = task("2026-06-01 hi {project} {title} +Home id:a");
+        let text = fill("echo {title} {project}", &fields(Event::Added, &task), |v| format!("<{}>", v));
+        assert_eq!(text, "echo <hi {project} {title}> <Home>");
+    }
+
+    // the values stay out of the command line, in the environment
+    #[test]
+    fn commands_only_name_the_variables() {
+        let task = task("2026-06-01 x & calc | more %PATH% +Home id:a");
+        let command = hook_command("notify {title} {project}", &fields(Event::Added, &task));
+        let line: Vec<String> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert!(line.join(" ").contains(&format!("notify {} {}", reference("title"), reference("project"))));
+        assert!(!line.join(" ").contains("calc"));
+        let title = command.get_envs().find(|(name, _)| *name == "TODO_TITLE").and_then(|(_, value)| value);
+        assert_eq!(title.and_then(|v| v.to_str()), Some("x & calc | more %PATH%"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_shell_runs_nothing_in_a_title() {
+        let task = task("2026-06-01 a & echo run | echo run; $(echo run) `echo run` %HOME% 'q\" +Home id:a");
+        let output = hook_command("printf '%s|%s' {title} {project}", &fields(Event::Added, &task)).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a & echo run | echo run; $(echo run) `echo run` %HOME% 'q\"|Home");
+    }
+
+    #[test]
+    fn quotes_for_json_too() {
+        let task = task("2026-06-01 say \"hi\" {project} +Home id:a");
+        let text = fill(r#"{"text": "{title}"}"#, &fields(Event::Added, &task), json_escape);
+        assert_eq!(text, r#"{"text": "say \"hi\" {project}"}"#);
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    // the version of what the body holds, for a later `If-Match`
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(content_type: &'static str, body: String) -> Response {
+        Response { status: 200, content_type, etag: None, body }
+    }
+    pub fn error(status: u16, message: &str) -> Response {
+        Response { status, content_type: "text/plain", etag: None, body: format!("{}\n", message) }
+    }
+    pub fn not_found() -> Response {
+        Response::error(404, "not found")
+    }
+}
+
+// bodies past this get a 413 unread, and lines of the head past this a 400
+const MAX_BODY: usize = 1 << 20;
+const MAX_LINE: u64 = 8 << 10;
+// a client that stops sending or reading for this long is dropped, so it
+// can't hold up the ones after it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// a tiny blocking HTTP/1.1 server, one connection at a time; enough for localhost tools
+pub fn serve(addr: &str, handler: impl Fn(&Request) -> io::Result<Response>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        // one bad connection is that client's problem, the server goes on
+        let mut stream = match stream.and_then(with_timeouts) {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("connection failed: {}", err);
+                continue;
+            }
+        };
+        let response = match read_request(&mut stream) {
+            Ok(request) => handler(&request).unwrap_or_else(|err| Response::error(500, &err.to_string())),
+            Err(response) => response,
+        };
+        // a client hanging up early is not our problem
+        let _ = write_response(&mut stream, &response);
+    }
+    Ok(())
+}
+
+fn with_timeouts(stream: TcpStream) -> io::Result<TcpStream> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+// what another `todo-tui serve` answered
+pub struct Reply {
+    pub status: u16,
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+// the client side, for talking to another `todo-tui serve`; `if_match` sends
+// the ETag a change is based on
+pub fn request(addr: &str, method: &str, path: &str, token: &str, if_match: Option<&str>, body: &str) -> io::Result<Reply> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let if_match = if_match.map(|etag| format!("If-Match: {}\r\n", etag)).unwrap_or_default();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        token,
+        if_match,
+        body.len(),
+        body
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.split_whitespace().nth(1).and_then(|s| s.parse().ok());
+    match (status, response.split_once("\r\n\r\n")) {
+        (Some(status), Some((head, body))) => {
+            let etag = head.lines().filter_map(|line| line.split_once(':')).find(|(name, _)| name.trim().eq_ignore_ascii_case("etag"));
+            Ok(Reply { status, etag: etag.map(|(_, value)| value.trim().to_string()), body: body.to_string() })
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response")),
+    }
+}
+
+// the request, or the response that turns it away
+fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let bad = |_| Response::error(400, "bad request");
+    let mut reader = BufReader::new(stream);
+    let line = read_line(&mut reader).map_err(bad)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "bad request")),
+    };
+    let mut headers = vec![];
+    loop {
+        let header = read_line(&mut reader).map_err(bad)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let mut request = Request { method, path, headers, body: String::new() };
+    let length = match request.header("content-length").map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(length)) if length <= MAX_BODY => length,
+        Some(Ok(_)) => return Err(Response::error(413, "body too large")),
+        Some(Err(_)) => return Err(Response::error(400, "bad content length")),
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(bad)?;
+    request.body = String::from_utf8(body).map_err(|_| Response::error(400, "body is not UTF-8"))?;
+    Ok(request)
+}
+
+// a line of the head, empty at the end of the stream
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE).read_line(&mut line)?;
+    if line.len() as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+    }
+    Ok(line)
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        428 => "Precondition Required",
+        _ => "Internal Server Error",
+    };
+    let etag = response.etag.as_ref().map(|etag| format!("ETag: {}\r\n", etag)).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        etag,
+        response.body.len(),
+        response.body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // what the server answers `raw` with before any handler sees it
+    fn answer(raw: &str) -> Result<Request, Response> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(raw.as_bytes()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        read_request(&mut stream)
+    }
+
+    #[test]
+    fn reads_a_request() {
+        let request = answer("PUT /sync HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello").ok().unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str(), request.body.as_str()), ("PUT", "/sync", "hello"));
+    }
+
+    #[test]
+    fn turns_away_huge_bodies_unread() {
+        let response = answer("PUT /sync HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n").err().unwrap();
+        assert_eq!(response.status, 413);
+        let response = answer("PUT /sync HTTP/1.1\r\nContent-Length: 99999999999999999999999\r\n\r\n").err().unwrap();
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn turns_away_endless_lines() {
+        let response = answer(&format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(20_000))).err().unwrap();
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn gives_up_on_idle_clients() {
+        let response = answer("GET / HTTP/1.1\r\n").err().unwrap();
+        assert_eq!(response.status, 400);
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+
+use crate::task::Task;
+
+// tasks with a due date as VTODO entries, or all-day VEVENTs for calendars that
+// ignore todos
+pub fn export(tasks: &[Task], events: bool, now_utc: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo-tui//EN".to_string(),
+    ];
+    let stamp = now_utc.format("%Y%m%dT%H%M%SZ").to_string();
+    for task in tasks {
+        let due = match task.due {
+            Some(due) => due,
+            None => continue,
+        };
+        let kind = if events { "VEVENT" } else { "VTODO" };
+        lines.push(format!("BEGIN:{}", kind));
+        lines.push(format!("UID:{}@todo-tui", task.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape(&task.title)));
+        if events {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")));
+        } else {
+            lines.push(format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")));
+            lines.push(format!("STATUS:{}", if task.is_done() { "COMPLETED" } else { "NEEDS-ACTION" }));
+        }
+        if let Some(project) = &task.project {
+            lines.push(format!("CATEGORIES:{}", escape(project)));
+        }
+        if let Some(p) = task.priority {
+            // A..I map onto iCal's 1 (highest) .. 9
+            lines.push(format!("PRIORITY:{}", (p as u8 - b'A' + 1).min(9)));
+        }
+        lines.push(format!("END:{}", kind));
+    }
+    lines.push("END:VCALENDAR".to_string());
+    let mut text = lines.join("\r\n");
+    text.push_str("\r\n");
+    text
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{org, sync, task::Task};
+
+// what an import did, by title, for the summary
+#[derive(Default)]
+pub struct Report {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl Report {
+    pub fn summary(&self) -> String {
+        format!("{} added, {} updated, {} skipped", self.added.len(), self.updated.len(), self.skipped.len())
+    }
+}
+
+// the todo in a file to import: an org file by its extension, todo.txt lines
+// otherwise
+pub fn read(path: &Path, today: NaiveDate) -> std::io::Result<Vec<Task>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(match path.extension().and_then(|e| e.to_str()) {
+        Some("org") => org::import(&text, today),
+        _ => sync::from_text(&text, today),
+    })
+}
+
+// an imported todo is one we have when its id matches, or failing that its
+// title matches give or take case, spacing and punctuation in the same
+// project; those are merged in, or left alone with `skip_existing`
+pub fn merge(tasks: &mut Vec<Task>, imported: Vec<Task>, skip_existing: bool, now: NaiveDateTime) -> Report {
+    let mut report = Report::default();
+    for mut task in imported {
+        let same = tasks
+            .iter()
+            .position(|t| t.id == task.id)
+            .or_else(|| tasks.iter().position(|t| t.project == task.project && fuzzy(&t.title) == fuzzy(&task.title)));
+        match same.map(|i| &mut tasks[i]) {
+            Some(_) if skip_existing => report.skipped.push(task.title),
+            Some(existing) => {
+                // not every format has creation or edit times, keep the ones we know
+                task.id = existing.id.to_string();
+                task.created = existing.created;
+                task.touched = existing.touched;
+                if existing.to_line() == task.to_line() {
+                    report.skipped.push(task.title);
+                } else {
+                    task.touched = now;
+                    report.updated.push(task.title.to_string());
+                    *existing = task;
+                }
+            }
+            None => {
+                task.touched = now;
+                report.added.push(task.title.to_string());
+                tasks.push(task);
+            }
+        }
+    }
+    report
+}
+
+fn fuzzy(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
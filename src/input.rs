@@ -0,0 +1,340 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// entries Up and Down go back over
+const HISTORY_SIZE: usize = 50;
+
+// a line of text being typed, with a cursor, a selection, readline's editing
+// keys and a history of what was entered; the popup (adding, editing, project
+// names, checklist items), the filter and the command line each have one
+#[derive(Default)]
+pub struct TextInput {
+    text: String,
+    // byte offsets, always between two graphemes; the selection runs from the
+    // anchor to the cursor
+    cursor: usize,
+    anchor: Option<usize>,
+    // what the last Ctrl+K, Ctrl+U or Ctrl+W took, for Ctrl+Y
+    killed: String,
+    // oldest first, with the entry Up went back to and the text typed before
+    history: Vec<String>,
+    recalled: Option<(usize, String)>,
+}
+
+impl TextInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    // the selected byte range, when there is one
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor.filter(|&a| a != self.cursor)?;
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+    // new text, with the cursor at its end
+    pub fn set(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+        self.anchor = None;
+        self.recalled = None;
+    }
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+    // the text, entered: it goes on the history and the input is left empty
+    pub fn take(&mut self) -> String {
+        self.remember();
+        let text = std::mem::take(&mut self.text);
+        self.clear();
+        text
+    }
+    // the text on the history, for inputs that keep it after Enter
+    pub fn remember(&mut self) {
+        let text = self.text.trim();
+        if !text.is_empty() {
+            self.history.retain(|h| h != text);
+            self.history.push(text.to_string());
+            let over = self.history.len().saturating_sub(HISTORY_SIZE);
+            self.history.drain(..over);
+        }
+        self.recalled = None;
+    }
+    // at the cursor, over the selection
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+    // the editing and moving keys; false for any other key, typed characters
+    // included, which are the caller's to insert
+    pub fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let alt = modifiers.contains(KeyModifiers::ALT);
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let motion = match code {
+            KeyCode::Left if ctrl || alt => Some(self.word_start()),
+            KeyCode::Right if ctrl || alt => Some(self.word_end()),
+            KeyCode::Left => Some(self.previous()),
+            KeyCode::Right => Some(self.next()),
+            KeyCode::Home => Some(0),
+            KeyCode::End => Some(self.text.len()),
+            KeyCode::Char('a') if ctrl => Some(0),
+            KeyCode::Char('e') if ctrl => Some(self.text.len()),
+            KeyCode::Char('b') if ctrl => Some(self.previous()),
+            KeyCode::Char('f') if ctrl => Some(self.next()),
+            KeyCode::Char('b') if alt => Some(self.word_start()),
+            KeyCode::Char('f') if alt => Some(self.word_end()),
+            _ => None,
+        };
+        if let Some(to) = motion {
+            // Shift keeps the selection going, any other move drops it
+            self.anchor = match shift {
+                true => self.anchor.or(Some(self.cursor)),
+                false => None,
+            };
+            self.cursor = to;
+            return true;
+        }
+        match code {
+            KeyCode::Up => self.recall(true),
+            KeyCode::Down => self.recall(false),
+            KeyCode::Backspace | KeyCode::Delete if self.selection().is_some() => self.delete_selection(),
+            KeyCode::Backspace if ctrl || alt => self.kill(self.word_start(), self.cursor),
+            KeyCode::Backspace => self.remove(self.previous(), self.cursor),
+            KeyCode::Delete => self.remove(self.cursor, self.next()),
+            KeyCode::Char('k') if ctrl => self.kill(self.cursor, self.text.len()),
+            KeyCode::Char('u') if ctrl => self.kill(0, self.cursor),
+            KeyCode::Char('w') if ctrl => self.kill(self.space_start(), self.cursor),
+            KeyCode::Char('d') if alt => self.kill(self.cursor, self.word_end()),
+            KeyCode::Char('y') if ctrl => {
+                let killed = self.killed.to_string();
+                self.insert(&killed);
+            }
+            _ => return false,
+        }
+        true
+    }
+    // the visible stretch of the text in `width` cells, scrolled to keep the
+    // cursor in view with a `…` where it is cut off, each part in the style
+    // of the range it falls in and the selection reversed; and the cursor's
+    // column in it
+    pub fn spans(&self, width: usize, styles: &[(usize, usize, Style)]) -> (Line<'_>, u16) {
+        let (from, to) = self.window(width);
+        let clipped = Style::default().fg(Color::DarkGray);
+        let selection = self.selection();
+        let mut cuts = vec![from, to];
+        cuts.extend(styles.iter().flat_map(|&(start, end, _)| [start, end]));
+        cuts.extend(selection.iter().flat_map(|&(start, end)| [start, end]));
+        let mut cuts: Vec<usize> = cuts.into_iter().map(|at| at.clamp(from, to)).collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut spans = vec![];
+        if from > 0 {
+            spans.push(Span::styled("…", clipped));
+        }
+        for pair in cuts.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let mut style = styles.iter().find(|&&(s, e, _)| s <= start && end <= e).map_or(Style::default(), |&(_, _, style)| style);
+            if selection.is_some_and(|(s, e)| s <= start && end <= e) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            spans.push(Span::styled(&self.text[start..end], style));
+        }
+        if to < self.text.len() {
+            spans.push(Span::styled("…", clipped));
+        }
+        let column = usize::from(from > 0) + self.text[from..self.cursor].width();
+        (Line::from(spans), column as u16)
+    }
+    // the byte range shown in `width` cells with the cursor in view; a cell
+    // is kept for the cursor and for a `…` on each side that is cut off
+    fn window(&self, width: usize) -> (usize, usize) {
+        let (text, cursor) = (&self.text, self.cursor);
+        if text.width() < width {
+            return (0, text.len());
+        }
+        let mut used = 1 + usize::from(cursor < text.len());
+        let mut start = cursor;
+        for (i, c) in text[..cursor].char_indices().rev() {
+            let w = c.width().unwrap_or(0);
+            // the left `…` too, unless this reaches the start
+            if used + w + usize::from(i > 0) > width {
+                break;
+            }
+            used += w;
+            start = i;
+        }
+        used += usize::from(start > 0);
+        let mut end = cursor;
+        for (i, c) in text[cursor..].char_indices() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width {
+                break;
+            }
+            used += w;
+            end = cursor + i + c.len_utf8();
+        }
+        (start, end)
+    }
+    // Up goes back an entry, keeping what was typed for Down to return to
+    fn recall(&mut self, back: bool) {
+        let at = self.recalled.as_ref().map_or(self.history.len(), |(at, _)| *at);
+        let to = match back {
+            true if at > 0 => at - 1,
+            false if at < self.history.len() => at + 1,
+            _ => return,
+        };
+        let typed = match self.recalled.take() {
+            Some((_, typed)) => typed,
+            None => self.text.to_string(),
+        };
+        let text = self.history.get(to).cloned().unwrap_or_else(|| typed.to_string());
+        self.set(text);
+        if to < self.history.len() {
+            self.recalled = Some((to, typed));
+        }
+    }
+    fn delete_selection(&mut self) {
+        if let Some((start, end)) = self.selection() {
+            self.remove(start, end);
+        }
+        self.anchor = None;
+    }
+    fn remove(&mut self, start: usize, end: usize) {
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+    fn kill(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.killed = self.text[start..end].to_string();
+            self.remove(start, end);
+        }
+    }
+    fn previous(&self) -> usize {
+        self.text[..self.cursor].grapheme_indices(true).next_back().map_or(0, |(i, _)| i)
+    }
+    fn next(&self) -> usize {
+        self.text[self.cursor..].graphemes(true).next().map_or(self.cursor, |g| self.cursor + g.len())
+    }
+    // readline's words are letters and digits
+    fn word_start(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end_matches(|c: char| !c.is_alphanumeric());
+        before.trim_end_matches(char::is_alphanumeric).len()
+    }
+    fn word_end(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let skipped = after.len() - after.trim_start_matches(|c: char| !c.is_alphanumeric()).len();
+        let word = &after[skipped..];
+        self.cursor + skipped + (word.len() - word.trim_start_matches(char::is_alphanumeric).len())
+    }
+    // Ctrl+W goes back over a whitespace-separated word
+    fn space_start(&self) -> usize {
+        self.text[..self.cursor].trim_end().trim_end_matches(|c: char| !c.is_whitespace()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        input.set(text.to_string());
+        input
+    }
+
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) {
+        assert!(input.handle(code, modifiers));
+    }
+
+    #[test]
+    fn moves_over_whole_graphemes() {
+        let mut input = typed("かが\u{3099}");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(input.cursor, "か".len());
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(input.text(), "か");
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(input.text(), "");
+    }
+
+    #[test]
+    fn kills_and_yanks() {
+        let mut input = typed("buy milk tomorrow");
+        press(&mut input, KeyCode::Char('b'), KeyModifiers::ALT);
+        press(&mut input, KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "buy milk ");
+        press(&mut input, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Char('y'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "tomorrowbuy milk ");
+        press(&mut input, KeyCode::Char('e'), KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "tomorrowbuy ");
+        press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(input.text(), "");
+    }
+
+    #[test]
+    fn moves_by_word() {
+        let mut input = typed("call +Home, then @phone");
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('f'), KeyModifiers::ALT);
+        assert_eq!(input.cursor, "call".len());
+        press(&mut input, KeyCode::Char('f'), KeyModifiers::ALT);
+        assert_eq!(input.cursor, "call +Home".len());
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(input.cursor, "call +".len());
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut input = typed("buy milk");
+        press(&mut input, KeyCode::Char('b'), KeyModifiers::ALT);
+        press(&mut input, KeyCode::End, KeyModifiers::SHIFT);
+        assert_eq!(input.selection(), Some(("buy ".len(), "buy milk".len())));
+        input.insert("bread");
+        assert_eq!(input.text(), "buy bread");
+        assert_eq!(input.selection(), None);
+        press(&mut input, KeyCode::Left, KeyModifiers::SHIFT);
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(input.selection(), None);
+    }
+
+    #[test]
+    fn walks_the_history() {
+        let mut input = TextInput::default();
+        for entry in ["first", "second"] {
+            input.insert(entry);
+            input.take();
+        }
+        input.insert("draft");
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(input.text(), "second");
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(input.text(), "first");
+        press(&mut input, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(input.text(), "draft");
+    }
+
+    #[test]
+    fn scrolls_to_the_cursor() {
+        let mut input = typed("報告書を提出する");
+        let (spans, column) = input.spans(8, &[]);
+        assert_eq!(spans.spans[0].content, "…");
+        assert_eq!(spans.width(), 7);
+        assert_eq!(column, 7);
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        let (spans, column) = input.spans(8, &[]);
+        assert_eq!(spans.spans.last().map(|s| s.content.as_ref()), Some("…"));
+        assert_eq!(column, 0);
+        let input = typed("short");
+        assert_eq!(input.spans(8, &[]).0.width(), 5);
+    }
+}
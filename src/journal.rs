@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use chrono::NaiveDate;
+
+use crate::{paths, task::Task};
+
+// what a save changes, written to the journal before todo.txt and cleared
+// after it; a journal left behind means the app died in between
+pub enum Op {
+    Set(Box<Task>),
+    Remove(String),
+}
+
+// the changes from the list as last saved to `tasks`
+pub fn diff(saved: &[Task], tasks: &[Task]) -> Vec<Op> {
+    let before: HashMap<&str, String> = saved.iter().map(|t| (t.id.as_str(), t.to_line())).collect();
+    let mut ops: Vec<Op> = tasks
+        .iter()
+        .filter(|t| before.get(t.id.as_str()) != Some(&t.to_line()))
+        .map(|t| Op::Set(Box::new(t.clone())))
+        .collect();
+    let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    ops.extend(saved.iter().filter(|t| !ids.contains(&t.id.as_str())).map(|t| Op::Remove(t.id.to_string())));
+    ops
+}
+
+// one op per line: `set <todo.txt line>` or `remove <id>`
+pub fn record(ops: &[Op]) -> io::Result<()> {
+    if ops.is_empty() {
+        return Ok(());
+    }
+    let mut text = String::new();
+    for op in ops {
+        match op {
+            Op::Set(task) => text.push_str(&format!("set {}\n", task.to_line())),
+            Op::Remove(id) => text.push_str(&format!("remove {}\n", id)),
+        }
+    }
+    fs::create_dir_all(paths::data_dir())?;
+    let mut file = OpenOptions::new().append(true).create(true).open(paths::journal_file())?;
+    file.write_all(text.as_bytes())?;
+    file.sync_data()
+}
+
+pub fn clear() -> io::Result<()> {
+    match fs::remove_file(paths::journal_file()) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+// ops an earlier run journaled but may not have saved
+pub fn pending(today: NaiveDate) -> io::Result<Vec<Op>> {
+    let text = match fs::read_to_string(paths::journal_file()) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    // a last line without its newline was cut short by the crash
+    let complete = text.rfind('\n').map_or("", |end| &text[..end]);
+    Ok(complete
+        .lines()
+        .filter_map(|line| match line.split_once(' ') {
+            Some(("set", line)) => Some(Op::Set(Box::new(Task::from_line(line, today)))),
+            Some(("remove", id)) => Some(Op::Remove(id.to_string())),
+            _ => None,
+        })
+        .collect())
+}
+
+pub fn replay(ops: Vec<Op>, tasks: &mut Vec<Task>) {
+    for op in ops {
+        match op {
+            Op::Set(task) => match tasks.iter_mut().find(|t| t.id == task.id) {
+                Some(existing) => *existing = *task,
+                None => tasks.push(*task),
+            },
+            Op::Remove(id) => tasks.retain(|t| t.id != id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    fn tasks(lines: &[&str]) -> Vec<Task> {
+        lines.iter().map(|line| Task::from_line(line, today())).collect()
+    }
+
+    fn describe(ops: &[Op]) -> Vec<String> {
+        ops.iter()
+            .map(|op| match op {
+                Op::Set(task) => format!("set {}", task.title),
+                Op::Remove(id) => format!("remove {}", id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diffs_changed_added_and_removed() {
+        let saved = tasks(&["call mum id:a", "pay rent id:b", "water plants id:c"]);
+        let now = tasks(&["call mum id:a", "pay the rent id:b", "buy milk id:d"]);
+        assert_eq!(describe(&diff(&saved, &now)), ["set pay the rent", "set buy milk", "remove c"]);
+        assert!(diff(&saved, &saved).is_empty());
+    }
+
+    #[test]
+    fn replays_over_existing_and_new_ids() {
+        let mut list = tasks(&["call mum id:a", "pay rent id:b"]);
+        let ops = diff(&list, &tasks(&["call dad id:a", "buy milk id:d"]));
+        replay(ops, &mut list);
+        let titles: Vec<&str> = list.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["call dad", "buy milk"]);
+        // replaying the same again changes nothing
+        let ops = diff(&tasks(&["call mum id:a", "pay rent id:b"]), &list);
+        replay(ops, &mut list);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn pending_drops_a_line_cut_short() {
+        let _h = crate::harness::Harness::start("", "");
+        assert!(pending(today()).unwrap().is_empty());
+        fs::write(paths::journal_file(), "set call mum id:a\nremove b\nset pay re").unwrap();
+        assert_eq!(describe(&pending(today()).unwrap()), ["set call mum", "remove b"]);
+        // with nothing whole written yet there is nothing to replay
+        fs::write(paths::journal_file(), "set call mu").unwrap();
+        assert!(pending(today()).unwrap().is_empty());
+        clear().unwrap();
+        assert!(pending(today()).unwrap().is_empty());
+        // what record writes is read back whole
+        record(&diff(&tasks(&["pay rent id:b"]), &tasks(&["buy milk id:d"]))).unwrap();
+        assert_eq!(describe(&pending(today()).unwrap()), ["set buy milk", "remove b"]);
+    }
+}
@@ -0,0 +1,596 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::{config::KeyEntry, Focus};
+
+// what a key does in the list screen; overlays, prompts and the popup keep
+// their own keys
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Add,
+    AddBatch,
+    Quit,
+    PostponeOverdue,
+    Undo,
+    CycleSort,
+    CycleGroup,
+    Fold,
+    // to a due bucket, counted from 0
+    Jump(usize),
+    JumpDeadline,
+    TypeAhead,
+    SetMark,
+    JumpBack,
+    JumpForward,
+    JumpMark,
+    AddCheck,
+    ToggleCheck,
+    RemoveCheck,
+    ToggleAge,
+    NewProject,
+    RenameProject,
+    ArchiveProject,
+    DeleteProject,
+    MoveProjectDown,
+    MoveProjectUp,
+    Filter,
+    Command,
+    Triage,
+    Label,
+    Context,
+    Mark,
+    MoveToProject,
+    CopyToProject,
+    ToggleSplit,
+    Zoom,
+    HideDone,
+    Narrow,
+    Widen,
+    SwitchPane,
+    SendAcross,
+    EditNote,
+    CopySummary,
+    Standup,
+    Stats,
+    FocusMode,
+    History,
+    Activity,
+    Backups,
+    ToggleDone,
+    CycleFocus,
+    Unselect,
+    Down,
+    Up,
+    First,
+    Last,
+    PageDown,
+    PageUp,
+    RecordMacro,
+    PlayMacro,
+    Repeat,
+    Cut,
+    Yank,
+    Paste,
+}
+
+// every action with its config name, default keys and what it does, in the
+// order the cheat sheet lists them
+const ACTIONS: &[(Action, &str, &str, &str)] = &[
+    (Action::Add, "add", "p", "add a todo in the popup"),
+    (Action::AddBatch, "add_batch", "b", "add several todo, one per Enter"),
+    (Action::Triage, "triage", "t", "edit the selected todo in the popup"),
+    (Action::ToggleDone, "toggle_done", "Space", "complete or reopen the selected todo"),
+    (Action::Undo, "undo", "u", "undo the last change"),
+    (Action::Repeat, "repeat", ".", "do the last change again, on the selected todo"),
+    (Action::PostponeOverdue, "postpone_overdue", "O", "move overdue todo to today"),
+    (Action::Label, "label", "c", "pick a color label"),
+    (Action::Context, "context", "#", "add a context to the selected todo or take one off"),
+    (Action::Mark, "mark", "v", "mark a todo for m and M"),
+    (Action::Cut, "cut", "d d", "cut the marked or selected todo"),
+    (Action::Yank, "yank", "y y", "copy the marked or selected todo"),
+    (Action::Paste, "paste", "P", "paste the last cut or yank under the selected todo"),
+    (Action::MoveToProject, "move_to_project", "m", "move the marked or selected todo to a project"),
+    (Action::CopyToProject, "copy_to_project", "M", "copy them to a project"),
+    (Action::EditNote, "edit_note", "e", "edit the note in $EDITOR"),
+    (Action::AddCheck, "add_check", "a", "add a checklist item"),
+    (Action::ToggleCheck, "toggle_check", "Space", "tick the checklist item"),
+    (Action::RemoveCheck, "remove_check", "Delete, Backspace", "remove the checklist item"),
+    (Action::Down, "down", "Down", "next row"),
+    (Action::Up, "up", "Up", "previous row"),
+    (Action::First, "first", "Home", "first row"),
+    (Action::Last, "last", "End", "last row"),
+    (Action::PageDown, "page_down", "PageDown", "a screenful down"),
+    (Action::PageUp, "page_up", "PageUp", "a screenful up"),
+    (Action::Unselect, "unselect", "Left", "clear the selection"),
+    (Action::CycleFocus, "cycle_focus", "Tab", "move between sidebar, list and detail"),
+    (Action::NewProject, "new_project", "n", "add a project"),
+    (Action::RenameProject, "rename_project", "r", "rename the project"),
+    (Action::ArchiveProject, "archive_project", "A", "archive or unarchive the project"),
+    (Action::DeleteProject, "delete_project", "D", "delete the project"),
+    (Action::MoveProjectDown, "move_project_down", "Shift+Down", "move the project down the sidebar"),
+    (Action::MoveProjectUp, "move_project_up", "Shift+Up", "move the project up the sidebar"),
+    (Action::Filter, "filter", "/", "filter the list"),
+    (Action::Command, "command", ":", "run a command"),
+    (Action::CycleSort, "cycle_sort", "s", "change the sort"),
+    (Action::CycleGroup, "cycle_group", "g", "group by project, context or due date"),
+    (Action::Fold, "fold", "-", "fold or unfold the selected todo's group"),
+    (Action::Jump(0), "jump_today", "1", "in the due view, go to today's todo"),
+    (Action::Jump(1), "jump_tomorrow", "2", "in the due view, go to tomorrow's"),
+    (Action::Jump(2), "jump_this_week", "3", "in the due view, go to the rest of the week's"),
+    (Action::Jump(3), "jump_later", "4", "in the due view, go to the later ones"),
+    (Action::Jump(4), "jump_no_date", "5", "in the due view, go to those with no due date"),
+    (Action::JumpDeadline, "jump_deadline", "N", "go to the todo due next"),
+    (Action::TypeAhead, "type_ahead", "f", "go to a todo by typing its title"),
+    (Action::SetMark, "set_mark", "`", "put the mark of the next letter on the todo"),
+    (Action::JumpMark, "jump_mark", "'", "go to the todo with the next letter's mark"),
+    (Action::JumpBack, "jump_back", "Ctrl+o", "go back to the todo jumped from or edited before"),
+    (Action::JumpForward, "jump_forward", "Ctrl+i, Ctrl+p", "go forward again in the jump list"),
+    (Action::ToggleAge, "toggle_age", "a", "show how old each todo is"),
+    (Action::HideDone, "hide_done", "h", "hide completed todo"),
+    (Action::ToggleSplit, "toggle_split", "|", "split the screen"),
+    (Action::SwitchPane, "switch_pane", "w", "work in the other pane"),
+    (Action::SendAcross, "send_across", ">", "send todo to the other pane's view"),
+    (Action::Zoom, "zoom", "z", "fill the screen with the focused pane"),
+    (Action::Narrow, "narrow", "Ctrl+Left", "narrow the focused pane"),
+    (Action::Widen, "widen", "Ctrl+Right", "widen the focused pane"),
+    (Action::CopySummary, "copy_summary", "Y", "copy today's summary"),
+    (Action::Standup, "standup", "S", "show the standup"),
+    (Action::Stats, "stats", "B", "chart the project's burndown"),
+    (Action::FocusMode, "focus_mode", "F", "show only the selected todo, with a timer"),
+    (Action::History, "history", "H", "browse the git history"),
+    (Action::Activity, "activity", "L", "show what was done to the list"),
+    (Action::Backups, "backups", "R", "restore a backup"),
+    (Action::RecordMacro, "record_macro", "q", "start or stop recording a macro"),
+    (Action::PlayMacro, "play_macro", "@", "play the macro back"),
+    (Action::Quit, "quit", "Esc", "quit"),
+];
+
+impl Action {
+    fn parse(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|(_, n, ..)| *n == name).map(|(a, ..)| *a)
+    }
+    fn name(self) -> &'static str {
+        ACTIONS.iter().find(|(a, ..)| *a == self).map_or("", |(_, n, ..)| n)
+    }
+    fn description(self) -> &'static str {
+        ACTIONS.iter().find(|(a, ..)| *a == self).map_or("", |(.., d)| d)
+    }
+    // the pane the action's keys belong to; the others work everywhere, and
+    // lose to a pane's own binding of the same key
+    pub fn focus(self) -> Option<Focus> {
+        match self {
+            Action::AddCheck | Action::ToggleCheck | Action::RemoveCheck => Some(Focus::Detail),
+            Action::NewProject
+            | Action::RenameProject
+            | Action::ArchiveProject
+            | Action::DeleteProject
+            | Action::MoveProjectDown
+            | Action::MoveProjectUp => Some(Focus::Sidebar),
+            Action::ToggleDone => Some(Focus::List),
+            _ => None,
+        }
+    }
+}
+
+// a key as the config writes it: `p`, `O`, `Space`, `Ctrl+Left`, `Shift+Up`
+pub fn parse_key(spec: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = spec.trim();
+    loop {
+        let (modifier, after) = match rest.split_once('+') {
+            Some((m, after)) if !m.is_empty() && !after.is_empty() => (m, after),
+            _ => break,
+        };
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "shift" => KeyModifiers::SHIFT,
+            "alt" => KeyModifiers::ALT,
+            _ => return None,
+        };
+        rest = after;
+    }
+    let code = match rest.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "delete" => KeyCode::Delete,
+        "backspace" => KeyCode::Backspace,
+        // the comma separates keys in the config
+        "comma" => KeyCode::Char(','),
+        lower => match (rest.chars().next(), rest.chars().count()) {
+            (Some(c), 1) if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), 1) => KeyCode::Char(c),
+            _ => KeyCode::F(lower.strip_prefix('f')?.parse().ok()?),
+        },
+    };
+    Some(normalize(code, modifiers))
+}
+
+pub fn format_key(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let mut text = String::new();
+    for (modifier, name) in [(KeyModifiers::CONTROL, "Ctrl+"), (KeyModifiers::ALT, "Alt+"), (KeyModifiers::SHIFT, "Shift+")] {
+        if modifiers.contains(modifier) {
+            text.push_str(name);
+        }
+    }
+    text.push_str(&match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(',') => "Comma".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        _ => "?".to_string(),
+    });
+    text
+}
+
+// the case of a letter already says whether Shift was down
+pub fn normalize(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+    match code {
+        KeyCode::Char(_) => (code, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+pub type Key = (KeyCode, KeyModifiers);
+
+// where a key leaves a sequence of them
+pub enum Lookup {
+    Action(Action),
+    // the start of a longer binding, wait for the next key
+    Pending,
+    // a whole binding that also starts longer ones: it happens when the
+    // timeout passes without a next key, or the next key goes on with none
+    Waiting(Action),
+    None,
+}
+
+// a key or sequence and what it does, in one pane or, without one, all of
+// them
+struct Binding {
+    keys: Vec<Key>,
+    action: Action,
+    pane: Option<Focus>,
+}
+
+pub struct Keymap {
+    // most bindings are one key, `d d` style ones a sequence
+    bindings: Vec<Binding>,
+    // what `<leader>` in a binding stands for
+    leader: Vec<Key>,
+}
+
+// `[keys.list]` and the like
+fn pane(name: &str) -> Option<Focus> {
+    match name {
+        "sidebar" => Some(Focus::Sidebar),
+        "list" => Some(Focus::List),
+        "detail" => Some(Focus::Detail),
+        _ => None,
+    }
+}
+
+fn pane_name(pane: Option<Focus>) -> &'static str {
+    match pane {
+        Some(Focus::Sidebar) => "sidebar",
+        Some(Focus::List) => "list",
+        Some(Focus::Detail) => "detail",
+        None => "",
+    }
+}
+
+impl Keymap {
+    // the defaults with `[keys]` from the config on top: `action = "key"`,
+    // several keys as `"x, Ctrl+d"`, a sequence as `"d d"` or `"<leader> p a"`,
+    // or `""` to unbind it. `[keys.list]`, `[keys.sidebar]` and
+    // `[keys.detail]` add keys that only work in that pane, and win there
+    pub fn new(overrides: &[KeyEntry], leader: &str) -> Keymap {
+        let leader = leader.split_whitespace().map(parse_key).collect::<Option<Vec<Key>>>().unwrap_or_default();
+        let mut keymap = Keymap { bindings: vec![], leader };
+        for (action, _, keys, _) in ACTIONS {
+            keymap.bind(*action, keys, action.focus());
+        }
+        for entry in overrides {
+            let Some(action) = Action::parse(&entry.action) else { continue };
+            match &entry.pane {
+                None => {
+                    keymap.bindings.retain(|b| b.action != action || b.pane != action.focus());
+                    keymap.bind(action, &entry.keys, action.focus());
+                }
+                Some(name) => {
+                    if let Some(pane) = pane(name) {
+                        keymap.bindings.retain(|b| b.action != action || b.pane != Some(pane));
+                        keymap.bind(action, &entry.keys, Some(pane));
+                    }
+                }
+            }
+        }
+        keymap
+    }
+    // a key bound here takes it from whatever had it in the same pane
+    fn bind(&mut self, action: Action, keys: &str, pane: Option<Focus>) {
+        for spec in keys.split(',').filter(|k| !k.trim().is_empty()) {
+            if let Some(keys) = self.sequence(spec) {
+                self.bindings.retain(|b| b.keys != keys || b.pane != pane);
+                self.bindings.push(Binding { keys, action, pane });
+            }
+        }
+    }
+    // `<leader>` (or `Leader`) for the leader's keys
+    fn sequence(&self, spec: &str) -> Option<Vec<Key>> {
+        let mut keys = vec![];
+        for word in spec.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "<leader>" | "leader" if !self.leader.is_empty() => keys.extend(&self.leader),
+                _ => keys.push(parse_key(word)?),
+            }
+        }
+        Some(keys).filter(|keys| !keys.is_empty())
+    }
+    // what in `[keys]` didn't do what it says, by config line: unknown
+    // panes, actions and keys, an action set twice, and a key given to two
+    // actions in the same pane (the later one gets it)
+    pub fn diagnose(&self, overrides: &[KeyEntry]) -> Vec<(usize, String)> {
+        let mut problems = vec![];
+        let mut seen: Vec<(Vec<Key>, Action, Option<Focus>, usize)> = vec![];
+        let mut set: Vec<(Action, Option<Focus>, usize)> = vec![];
+        let show = |keys: &[Key]| keys.iter().map(|&(c, m)| format_key(c, m)).collect::<Vec<_>>().join(" ");
+        for entry in overrides {
+            let (name, line) = (&entry.action, entry.line);
+            let Some(action) = Action::parse(name) else {
+                problems.push((line, format!("`{}` is not an action (`todo-tui keys` lists them)", name)));
+                continue;
+            };
+            let pane = match &entry.pane {
+                None => action.focus(),
+                Some(name) => match pane(name) {
+                    Some(pane) => Some(pane),
+                    None => {
+                        problems.push((line, format!("`[keys.{}]` is not a pane: sidebar, list or detail", name)));
+                        continue;
+                    }
+                },
+            };
+            if let Some((.., first)) = set.iter().find(|(a, p, _)| *a == action && *p == pane) {
+                problems.push((line, format!("`{}` is set again, replacing line {}", name, first)));
+                seen.retain(|(_, a, p, _)| *a != action || *p != pane);
+            }
+            set.push((action, pane, line));
+            for spec in entry.keys.split(',').filter(|k| !k.trim().is_empty()) {
+                let Some(sequence) = self.sequence(spec) else {
+                    problems.push((line, format!("`{}` is not a key", spec.trim())));
+                    continue;
+                };
+                match seen.iter().find(|(keys, _, p, _)| *keys == sequence && *p == pane) {
+                    Some((_, other, ..)) if *other == action => problems.push((line, format!("{} is listed twice", show(&sequence)))),
+                    Some((_, other, _, at)) => {
+                        let message = format!("{} is bound to both `{}` (line {}) and `{}`; `{}` gets it", show(&sequence), other.name(), at, name, name);
+                        problems.push((line, message));
+                    }
+                    None => {}
+                }
+                seen.push((sequence, action, pane, line));
+            }
+        }
+        problems.sort_by_key(|(line, _)| *line);
+        problems
+    }
+    // `key` after the `pending` ones
+    pub fn lookup(&self, focus: Focus, pending: &[Key], code: KeyCode, modifiers: KeyModifiers) -> Lookup {
+        let find = |key: Key| {
+            let mut sequence = pending.to_vec();
+            sequence.push(key);
+            let in_pane: Vec<&Binding> = self.bindings.iter().filter(|b| b.pane.is_none_or(|p| p == focus)).collect();
+            let longer = in_pane.iter().any(|b| b.keys.len() > sequence.len() && b.keys.starts_with(&sequence));
+            // the pane's own binding wins over one that works everywhere
+            let matching = in_pane.iter().filter(|b| b.keys == sequence);
+            let action = matching.max_by_key(|b| b.pane.is_some()).map(|b| b.action);
+            match (action, longer) {
+                (Some(action), true) => Lookup::Waiting(action),
+                (Some(action), false) => Lookup::Action(action),
+                (None, true) => Lookup::Pending,
+                (None, false) => Lookup::None,
+            }
+        };
+        match find(normalize(code, modifiers)) {
+            // Ctrl+Down and the like do what Down does unless bound themselves
+            Lookup::None if pending.is_empty() && !matches!(code, KeyCode::Char(_)) => find((code, KeyModifiers::NONE)),
+            found => found,
+        }
+    }
+    // the keys that can follow `pending` in the pane, each with what it does,
+    // or how many bindings go on from it
+    pub fn next_keys(&self, focus: Focus, pending: &[Key]) -> Vec<(String, String)> {
+        let mut next: Vec<(Key, Vec<Action>, bool)> = vec![];
+        for b in &self.bindings {
+            if b.keys.len() <= pending.len() || !b.keys.starts_with(pending) || b.pane.is_some_and(|p| p != focus) {
+                continue;
+            }
+            let key = b.keys[pending.len()];
+            let ends = b.keys.len() == pending.len() + 1;
+            match next.iter_mut().find(|(k, ..)| *k == key) {
+                Some((_, actions, done)) => {
+                    actions.push(b.action);
+                    *done |= ends;
+                }
+                None => next.push((key, vec![b.action], ends)),
+            }
+        }
+        next.into_iter()
+            .map(|((code, modifiers), actions, ends)| {
+                let does = match (&actions[..], ends) {
+                    ([action], true) => action.description().to_string(),
+                    (actions, _) => format!("+{} more", actions.len()),
+                };
+                (format_key(code, modifiers), does)
+            })
+            .collect()
+    }
+    // bound in another pane than the action's own, with the pane's name
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|b| b.action == action)
+            .map(|b| {
+                let keys = b.keys.iter().map(|&(c, m)| format_key(c, m)).collect::<Vec<_>>().join(" ");
+                if b.pane == action.focus() { keys } else { format!("{} ({})", keys, pane_name(b.pane)) }
+            })
+            .collect()
+    }
+    // the first key for the help line
+    pub fn key(&self, action: Action) -> String {
+        self.keys(action).into_iter().next().unwrap_or_else(|| "(unbound)".to_string())
+    }
+    // `todo-tui keys`: every action with its keys, as a Markdown table or
+    // lined up for the terminal
+    pub fn cheat_sheet(&self, markdown: bool) -> String {
+        let rows: Vec<[String; 4]> = ACTIONS
+            .iter()
+            .map(|&(action, name, _, description)| {
+                let keys = self.keys(action);
+                let pane = pane_name(action.focus());
+                [if keys.is_empty() { "-".to_string() } else { keys.join(", ") }, name.to_string(), pane.to_string(), description.to_string()]
+            })
+            .collect();
+        let header = ["Key", "Action", "Pane", "Does"].map(String::from);
+        let mut text = String::new();
+        if markdown {
+            text.push_str("| Key | Action | Pane | Does |\n|---|---|---|---|\n");
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+                text.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            return text;
+        }
+        let widths: Vec<usize> = (0..4).map(|c| rows.iter().chain([&header]).map(|r| r[c].chars().count()).max().unwrap_or(0)).collect();
+        for row in [&header].into_iter().chain(&rows) {
+            let cells: Vec<String> = row.iter().zip(&widths).map(|(c, &w)| format!("{:w$}", c, w = w)).collect();
+            text.push_str(cells.join("  ").trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `(pane, action, keys)` as `[keys]` lines, counted from 1
+    fn entries(lines: &[(Option<&str>, &str, &str)]) -> Vec<KeyEntry> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, (pane, action, keys))| KeyEntry {
+                pane: pane.map(str::to_string),
+                action: action.to_string(),
+                keys: keys.to_string(),
+                line: i + 1,
+            })
+            .collect()
+    }
+
+    fn diagnose(lines: &[(Option<&str>, &str, &str)]) -> Vec<(usize, String)> {
+        let overrides = entries(lines);
+        Keymap::new(&overrides, "\\").diagnose(&overrides)
+    }
+
+    fn found(lookup: Lookup) -> Option<&'static str> {
+        match lookup {
+            Lookup::Action(action) => Some(action.name()),
+            Lookup::Waiting(action) => Some(action.name()),
+            Lookup::Pending => Some("…"),
+            Lookup::None => None,
+        }
+    }
+
+    fn char(c: char) -> Key {
+        (KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn a_clean_config_has_no_problems() {
+        assert!(diagnose(&[(None, "add", "a"), (Some("list"), "undo", "Ctrl+z"), (None, "cut", "x, d d")]).is_empty());
+    }
+
+    #[test]
+    fn finds_unknown_actions_panes_and_keys() {
+        let problems = diagnose(&[(None, "add", "a"), (None, "fly", "f"), (Some("footer"), "add", "F"), (None, "undo", "Ctrl+Nope")]);
+        assert_eq!(
+            problems,
+            [
+                (2, "`fly` is not an action (`todo-tui keys` lists them)".to_string()),
+                (3, "`[keys.footer]` is not a pane: sidebar, list or detail".to_string()),
+                (4, "`Ctrl+Nope` is not a key".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_duplicate_chords() {
+        let problems = diagnose(&[(None, "add", "x"), (None, "undo", "z, z"), (None, "triage", "x"), (None, "add", "a")]);
+        assert_eq!(
+            problems,
+            [
+                (2, "z is listed twice".to_string()),
+                (3, "x is bound to both `add` (line 1) and `triage`; `triage` gets it".to_string()),
+                (4, "`add` is set again, replacing line 1".to_string()),
+            ]
+        );
+        // the same key in two panes is no clash
+        assert!(diagnose(&[(Some("list"), "add", "x"), (Some("sidebar"), "triage", "x")]).is_empty());
+    }
+
+    #[test]
+    fn expands_the_leader() {
+        let overrides = entries(&[(None, "add", "<leader> a"), (None, "triage", "Leader t")]);
+        let keymap = Keymap::new(&overrides, "Space");
+        // Space alone still completes, once no next key comes
+        assert!(matches!(keymap.lookup(Focus::List, &[], KeyCode::Char(' '), KeyModifiers::NONE), Lookup::Waiting(Action::ToggleDone)));
+        assert_eq!(found(keymap.lookup(Focus::List, &[char(' ')], KeyCode::Char('a'), KeyModifiers::NONE)), Some("add"));
+        assert_eq!(found(keymap.lookup(Focus::List, &[char(' ')], KeyCode::Char('t'), KeyModifiers::NONE)), Some("triage"));
+        assert!(keymap.diagnose(&overrides).is_empty());
+        // without a leader `<leader>` is no key at all
+        let keymap = Keymap::new(&overrides, "");
+        assert_eq!(keymap.diagnose(&overrides).len(), 2);
+    }
+
+    #[test]
+    fn sequences_wait_for_the_next_key() {
+        let keymap = Keymap::new(&[], "\\");
+        assert!(matches!(keymap.lookup(Focus::List, &[], KeyCode::Char('d'), KeyModifiers::NONE), Lookup::Pending));
+        assert_eq!(found(keymap.lookup(Focus::List, &[char('d')], KeyCode::Char('d'), KeyModifiers::NONE)), Some("cut"));
+        assert_eq!(found(keymap.lookup(Focus::List, &[char('d')], KeyCode::Char('q'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn the_pane_binding_wins_over_the_global_one() {
+        let overrides = entries(&[(None, "triage", "z"), (Some("list"), "add", "z")]);
+        let keymap = Keymap::new(&overrides, "\\");
+        assert_eq!(found(keymap.lookup(Focus::List, &[], KeyCode::Char('z'), KeyModifiers::NONE)), Some("add"));
+        assert_eq!(found(keymap.lookup(Focus::Sidebar, &[], KeyCode::Char('z'), KeyModifiers::NONE)), Some("triage"));
+        assert_eq!(found(keymap.lookup(Focus::Detail, &[], KeyCode::Char('z'), KeyModifiers::NONE)), Some("triage"));
+        // the global key still adds there too
+        assert_eq!(found(keymap.lookup(Focus::List, &[], KeyCode::Char('p'), KeyModifiers::NONE)), Some("add"));
+    }
+}
@@ -0,0 +1,3934 @@
+mod api;
+mod audit;
+mod backup;
+#[doc(hidden)]
+pub mod bench;
+mod browse;
+mod changelog;
+mod cli;
+mod clipboard;
+mod config;
+mod curl;
+mod events;
+mod export;
+mod filter;
+mod format;
+#[cfg(test)]
+mod fuzz;
+mod git;
+#[cfg(test)]
+mod harness;
+mod hooks;
+mod http;
+mod journal;
+mod ical;
+mod import;
+mod input;
+mod json;
+mod keymap;
+mod list;
+mod markdown;
+mod mstodo;
+mod org;
+mod paths;
+mod picker;
+mod plugin;
+mod print;
+mod purge;
+mod quick;
+mod report;
+mod rows;
+mod rules;
+mod sorting;
+mod state;
+mod storage;
+mod sync;
+mod task;
+mod todoist;
+mod urgency;
+mod webdav;
+
+use std::{ cmp::{Ordering, Reverse}, env, fs, io, process::Command, time::{Duration, Instant} };
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, Timelike};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, ListState, Wrap},
+    Frame, Terminal,
+};
+use browse::{Browsed, Browser};
+use changelog::Release;
+use config::Config;
+use filter::Filter;
+use input::TextInput;
+use keymap::{Action, Key, Keymap, Lookup};
+use list::StateList;
+use picker::{Picked, Picker};
+use plugin::Plugins;
+use purge::Purge;
+use sorting::SortMode;
+use state::State;
+use task::{Label, Task, LABELS};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// cuts and yanks kept for pasting
+const RING_SIZE: usize = 9;
+
+// todo ids the jump list keeps
+const JUMPS_SIZE: usize = 100;
+
+// columns the add popup grows between with its text
+const POPUP_MIN_WIDTH: u16 = 40;
+const POPUP_MAX_WIDTH: u16 = 100;
+
+// a pause this long after f and a few letters ends the type-ahead
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+// what the help line is typing into; the popup, pickers and questions are
+// overlays
+enum InputMode {
+    Normal,
+    Filter,
+    // a `:` command typed in the help line
+    Command,
+}
+
+// a change . can make again on another todo
+#[derive(Clone)]
+enum Repeat {
+    ToggleDone,
+    Label(Option<Label>),
+    // to this project, copying when true
+    Project(Option<String>, bool),
+    // this many rows, unless . gets a count of its own
+    Cut(usize),
+}
+
+enum Confirm {
+    PostponeOverdue,
+    // pasted text with one todo per line
+    SplitPaste(Vec<String>),
+    ArchiveProject(String),
+    DeleteProject(String),
+    // archived todo `:purge` would delete
+    Purge(Vec<Task>),
+    // changes the last run journaled but may not have saved
+    Replay(Vec<journal::Op>),
+}
+
+// what the popup names when it edits a project instead of a todo
+enum ProjectEdit {
+    New,
+    Rename(String),
+}
+
+// the list not being worked on when the screen is split; swapped with the
+// app's own to switch between them
+struct Pane {
+    view: View,
+    items: StateList<usize>,
+    rows: Vec<rows::Row>,
+}
+
+// a dialog over the lists; they stack, the newest on top taking every key
+// and drawn over the rest, and closing it shows the one under it
+enum Overlay {
+    // the releases and how far down they are scrolled
+    WhatsNew(Vec<&'static Release>, u16),
+    GitLog(GitLog),
+    Backups(BackupPicker),
+    ImportReport(import::Report),
+    // what aging todo went up to, for the summary
+    Escalated(Vec<String>),
+    Standup,
+    Stats(Stats),
+    // the todo whose reminder went off
+    Reminder(String),
+    Browse(Browser, Browse),
+    // what is wrong in the config's `[keys]`, by line, shown at startup
+    KeyProblems(Vec<(usize, String)>),
+    // the activity log, scrolled back this many lines from the newest
+    Activity(u16),
+    // what each undo step takes back, newest first; Enter undoes back to
+    // before the selected one
+    UndoList(Vec<String>, ListState),
+    // the add and edit popup; what it is for is in `triage`, `checking`,
+    // `project_edit` and `merging`
+    Popup,
+    Confirm(Confirm),
+    Picker(Picker, Pick),
+    // tasks edited here and on a provider, with the provider's name, waiting
+    // for the user to pick a version; oldest first
+    Conflicts(Vec<(String, sync::Conflict)>),
+}
+
+// what a picker overlay picks
+#[derive(Clone, Copy)]
+enum Pick {
+    Label,
+    Project,
+    Context,
+}
+
+// what the file picked in the browser is for, with the command's other
+// arguments
+enum Browse {
+    Import(Vec<String>),
+    Export(Vec<String>),
+    Restore,
+}
+
+// the burndown overlay, of one project or every todo
+#[derive(Clone)]
+struct Stats {
+    window: report::Window,
+    project: Option<String>,
+}
+
+// the one todo focus mode shows, and its timer: running since `started`
+// after `elapsed` from before it was paused
+struct FocusMode {
+    id: String,
+    started: Option<Instant>,
+    elapsed: Duration,
+}
+
+// the git history overlay
+struct GitLog {
+    commits: Vec<git::Commit>,
+    state: ListState,
+    diff: String,
+}
+
+// the backup restore overlay
+struct BackupPicker {
+    // with how many todo each holds
+    backups: Vec<(backup::Backup, usize)>,
+    state: ListState,
+}
+
+// one entry of the sidebar
+#[derive(Clone, PartialEq)]
+enum View {
+    All,
+    Inbox,
+    Project(String),
+    // todo with this context, whatever their project
+    Context(String),
+    // open todo under the due buckets, see `due_bucket`
+    Due,
+    DoneLog,
+}
+
+impl View {
+    // as `todo-tui print` names them: all, inbox, done, @context, or a project
+    // with or without its +
+    fn parse(name: &str) -> View {
+        match name {
+            "all" => View::All,
+            "inbox" => View::Inbox,
+            "done" => View::DoneLog,
+            "due" => View::Due,
+            _ => match name.strip_prefix('@') {
+                Some(context) => View::Context(context.to_string()),
+                None => View::Project(name.trim_start_matches('+').to_string()),
+            },
+        }
+    }
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            View::All => true,
+            View::Inbox => task.project.is_none() && !task.is_done(),
+            View::Project(name) => task.project.as_deref() == Some(name.as_str()),
+            View::Context(name) => !task.is_done() && task.contexts.contains(name),
+            View::Due => !task.is_done(),
+            View::DoneLog => task.is_done(),
+        }
+    }
+    fn name(&self) -> String {
+        match self {
+            View::All => "All".to_string(),
+            View::Inbox => "Inbox".to_string(),
+            View::Project(name) => name.to_string(),
+            View::Context(name) => format!("@{}", name),
+            View::Due => "Due".to_string(),
+            View::DoneLog => "Done log".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    Sidebar,
+    List,
+    // the selected task's checklist
+    Detail,
+}
+
+// the sections g splits the list into, each under a header
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    Project,
+    Context,
+    Due,
+}
+
+impl Group {
+    fn next(group: Option<Group>) -> Option<Group> {
+        match group {
+            None => Some(Group::Project),
+            Some(Group::Project) => Some(Group::Context),
+            Some(Group::Context) => Some(Group::Due),
+            Some(Group::Due) => None,
+        }
+    }
+    fn parse(name: &str) -> Option<Group> {
+        [Group::Project, Group::Context, Group::Due].into_iter().find(|g| g.name() == name)
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Group::Project => "project",
+            Group::Context => "context",
+            Group::Due => "due",
+        }
+    }
+}
+
+// the due date's bucket and its place among them, which its jump key counts
+// from 1; overdue todo are still for today
+const DUE_BUCKETS: [&str; 5] = ["Today", "Tomorrow", "This week", "Later", "No date"];
+
+fn due_bucket(due: Option<NaiveDate>, today: NaiveDate) -> (usize, &'static str) {
+    let sunday = today + chrono::Duration::days(6 - today.weekday().num_days_from_monday() as i64);
+    let bucket = match due {
+        Some(due) if due <= today => 0,
+        Some(due) if due == today + chrono::Duration::days(1) => 1,
+        Some(due) if due <= sunday => 2,
+        Some(_) => 3,
+        None => 4,
+    };
+    (bucket, DUE_BUCKETS[bucket])
+}
+
+struct App {
+    popup_input: TextInput,
+    input_mode: InputMode,
+    // why Enter didn't take the typed text, until it changes
+    popup_error: Option<String>,
+    tasks: Vec<Task>,
+    // indices into `tasks` of the rows currently shown
+    items: StateList<usize>,
+    // those rows as drawn, and the day they were worked out for
+    rows: Vec<rows::Row>,
+    rows_on: NaiveDate,
+    // keep the popup open after each Enter
+    batch: bool,
+    history: Vec<Vec<Task>>,
+    state: State,
+    overlays: Vec<Overlay>,
+    sort: SortMode,
+    group: Option<Group>,
+    // the names of the groups shown as just their header
+    folded: Vec<String>,
+    show_age: bool,
+    dirty: bool,
+    config: Config,
+    filter: Filter,
+    filter_input: TextInput,
+    command_input: TextInput,
+    views: StateList<View>,
+    focus: Focus,
+    // task being triaged in the popup
+    triage: Option<usize>,
+    // task the popup adds a checklist item to
+    checking: Option<usize>,
+    project_edit: Option<ProjectEdit>,
+    // checklist row selected while the detail pane has the focus
+    check_row: usize,
+    // ids of the todo marked with v, which m and M act on instead of the selected one
+    marked: Vec<String>,
+    copy_to_project: bool,
+    // id of the task picked up with the mouse, dropped on the button's
+    // release; by id as the list can change under the mouse
+    dragging: Option<String>,
+    split: Option<Pane>,
+    // whether the list being worked on is the left one
+    pane_left: bool,
+    // the focused pane fills the screen
+    zoomed: bool,
+    hide_done: bool,
+    // one-off message shown in place of the help line until the next key
+    status: Option<String>,
+    focus_mode: Option<FocusMode>,
+    // todo jumped from or edited, oldest first, and where Ctrl+O and Ctrl+I
+    // have got to in it; `jumps.len()` when at the newest
+    jumps: Vec<String>,
+    jump_at: usize,
+    // set_mark or jump_mark waiting for the mark's letter
+    mark_prefix: Option<Action>,
+    // the letters typed after f, and when the last one came
+    type_ahead: Option<(String, Instant)>,
+    // the list as last written to todo.txt, to journal what a save changes
+    saved: Vec<Task>,
+    // the background sync engine, when a provider is configured
+    sync: Option<sync::Engine>,
+    // bumped on every save, so sync results for an older list are dropped
+    generation: u64,
+    // last sync outcome per provider, shown at the right of the help line
+    sync_status: Vec<(String, String)>,
+    // the provider's version while a conflict is merged in the popup
+    merging: Option<String>,
+    // the day the overdue hook was last checked
+    overdue_checked: Option<NaiveDate>,
+    // and the day old completed todo were last archived
+    archived_on: Option<NaiveDate>,
+    // and the day aging todo were last escalated
+    escalated_on: Option<NaiveDate>,
+    plugins: Plugins,
+    keymap: Keymap,
+    // keys pressed since q started a recording
+    recording: Option<Vec<(KeyCode, KeyModifiers)>>,
+    // the last recording, which @ plays
+    macro_keys: Vec<(KeyCode, KeyModifiers)>,
+    replaying: bool,
+    // the last change . repeats
+    last_change: Option<Repeat>,
+    // where the mouse rests, for the tooltip of a cut off row
+    hover: Option<(u16, u16)>,
+    // digits typed before an action, `5 Space` completes five todo
+    count: Option<usize>,
+    // the first keys of a sequence like `d d`, and when the last of them was pressed
+    pending: Vec<Key>,
+    pending_at: Instant,
+    // what those keys do on their own, once the timeout passes
+    pending_action: Option<Action>,
+    // false while the terminal is in the background
+    focused: bool,
+    // todo cut or yanked, newest first, each entry one cut
+    ring: Vec<Vec<Task>>,
+    activity: audit::Log,
+    // what undo took back since the last save, for the activity log
+    undone: Vec<String>,
+}
+
+impl App {
+    fn new() -> App {
+        App {
+            tasks: vec![],
+            items: StateList::with_items(vec![]),
+            rows: vec![],
+            rows_on: NaiveDate::MIN,
+            input_mode: InputMode::Normal,
+            popup_error: None,
+            batch: false,
+            popup_input: TextInput::default(),
+            history: vec![],
+            state: State::default(),
+            overlays: vec![],
+            sort: SortMode::Manual,
+            group: None,
+            folded: vec![],
+            show_age: false,
+            dirty: false,
+            config: Config::default(),
+            filter: Filter::parse(""),
+            filter_input: TextInput::default(),
+            command_input: TextInput::default(),
+            views: StateList::with_items(vec![View::All, View::Inbox, View::Due]),
+            focus: Focus::List,
+            triage: None,
+            checking: None,
+            project_edit: None,
+            check_row: 0,
+            marked: vec![],
+            copy_to_project: false,
+            dragging: None,
+            split: None,
+            pane_left: true,
+            zoomed: false,
+            hide_done: false,
+            status: None,
+            focus_mode: None,
+            type_ahead: None,
+            mark_prefix: None,
+            jumps: vec![],
+            jump_at: 0,
+            saved: vec![],
+            sync: None,
+            generation: 0,
+            sync_status: vec![],
+            merging: None,
+            overdue_checked: None,
+            archived_on: None,
+            escalated_on: None,
+            plugins: Plugins::empty(),
+            keymap: Keymap::new(&[], "\\"),
+            recording: None,
+            macro_keys: vec![],
+            replaying: false,
+            last_change: None,
+            hover: None,
+            count: None,
+            pending: vec![],
+            pending_at: Instant::now(),
+            pending_action: None,
+            focused: true,
+            ring: vec![],
+            activity: audit::Log::default(),
+            undone: vec![],
+        }
+    }
+    // whether the popup text is a todo, rather than a project name or a
+    // checklist item
+    fn editing_todo(&self) -> bool {
+        self.checking.is_none() && self.project_edit.is_none()
+    }
+    // whether the popup adds a new todo, rather than editing one or naming
+    // a project or checklist item
+    fn adding(&self) -> bool {
+        self.editing_todo() && self.triage.is_none() && self.merging.is_none()
+    }
+    // what keeps the typed todo from being added, if anything
+    fn check_input(&self) -> Option<String> {
+        match self.editing_todo() {
+            true => self.check_todo(self.popup_input.text()),
+            false => None,
+        }
+    }
+    fn check_todo(&self, text: &str) -> Option<String> {
+        let text = text.trim();
+        let length = text.chars().count();
+        let today = Local::now().date_naive();
+        if text.is_empty() {
+            Some("type a todo first, or Esc to close".to_string())
+        } else if self.config.max_length > 0 && length > self.config.max_length {
+            Some(format!("{} characters, {} at most", length, self.config.max_length))
+        } else if Task::parse(&quick::expand(text, today), today).title.is_empty() {
+            Some("the todo needs a title as well".to_string())
+        } else {
+            None
+        }
+    }
+    fn push(&mut self) {
+        let today = Local::now().date_naive();
+        let input = self.popup_input.take();
+        let input = input.trim();
+        if let Some(edit) = self.project_edit.take() {
+            // a todo.txt project is one word
+            let name = input.split_whitespace().collect::<Vec<_>>().join("-");
+            let name = name.trim_start_matches('+');
+            match edit {
+                _ if name.is_empty() => {}
+                ProjectEdit::New => self.new_project(name),
+                ProjectEdit::Rename(old) => self.rename_project(&old, name),
+            }
+            return;
+        }
+        if let Some(i) = self.checking.take() {
+            let item = input.to_string();
+            if !item.is_empty() {
+                self.snapshot();
+                self.tasks[i].checklist.push((false, item));
+                self.tasks[i].touched = Local::now().naive_local();
+                self.check_row = self.tasks[i].checklist.len() - 1;
+                self.refresh_rows();
+            }
+            return;
+        }
+        let task = match self.triage {
+            Some(_) => Task::parse(input, today),
+            None => self.config.new_task(&quick::expand(input, today), self.view_project(), today),
+        };
+        self.snapshot();
+        self.merging = None;
+        match self.triage.take() {
+            Some(i) => self.tasks[i].apply_edit(task, Local::now().naive_local()),
+            None => {
+                hooks::fire(&self.config.hooks, hooks::Event::Added, &task);
+                self.tasks.push(task);
+            }
+        }
+        self.resort();
+    }
+    fn selected_index(&self) -> Option<usize> {
+        let row = self.items.state.selected().filter(|&row| !self.is_folded(row))?;
+        self.items.items.get(row).copied()
+    }
+    // the group a todo is listed under, and the group's place in the list:
+    // the grouping picked with g, or the place when sorting by location
+    fn group_of(&self, task: &Task, today: NaiveDate) -> Option<(usize, String)> {
+        let named = |name: Option<&String>, none: &str| match name {
+            Some(name) => (0, name.to_string()),
+            None => (1, none.to_string()),
+        };
+        match self.group {
+            _ if matches!(self.view(), View::DoneLog) => None,
+            _ if matches!(self.view(), View::Due) => {
+                let (rank, name) = due_bucket(task.due, today);
+                Some((rank, name.to_string()))
+            }
+            // in sidebar order, which `projects` worked out already
+            Some(Group::Project) => Some(match &task.project {
+                Some(project) => (self.views.items.iter().position(|v| matches!(v, View::Project(p) if p == project)).unwrap_or(0) + 1, project.to_string()),
+                None => (0, "Inbox".to_string()),
+            }),
+            Some(Group::Context) => Some(named(task.contexts.first(), "No context")),
+            Some(Group::Due) => {
+                let (rank, name) = due_bucket(task.due, today);
+                Some((rank, name.to_string()))
+            }
+            None if self.shown_sort() == SortMode::Location => Some(named(task.location.as_ref(), "No location")),
+            None => None,
+        }
+    }
+    fn row_group(&self, row: usize, today: NaiveDate) -> Option<String> {
+        let &i = self.items.items.get(row)?;
+        self.group_of(&self.tasks[i], today).map(|(_, name)| name)
+    }
+    // the todo in the group, folded away or not
+    fn group_len(&self, name: &str, today: NaiveDate) -> usize {
+        let show_done = self.shows_done();
+        self.tasks
+            .iter()
+            .filter(|t| self.in_view(t, today) && (show_done || !t.is_done()))
+            .filter(|t| self.group_of(t, today).is_some_and(|(_, group)| group == name))
+            .count()
+    }
+    // a folded group keeps its first row, which stands for the group and no
+    // single todo
+    fn is_folded(&self, row: usize) -> bool {
+        let today = Local::now().date_naive();
+        self.row_group(row, today).is_some_and(|name| self.folded.contains(&name))
+    }
+    fn selected(&self) -> Option<&Task> {
+        self.selected_index().map(|i| &self.tasks[i])
+    }
+    fn view(&self) -> &View {
+        self.views.selected_item().unwrap_or(&View::All)
+    }
+    // the project whose view is open, which new todo go into
+    fn view_project(&self) -> Option<&str> {
+        match self.view() {
+            View::Project(name) => Some(name),
+            _ => None,
+        }
+    }
+    fn inbox_count(&self) -> usize {
+        self.tasks.iter().filter(|t| View::Inbox.matches(t)).count()
+    }
+    // open the popup on the selected task to give it a project, priority or due date
+    fn start_triage(&mut self) {
+        if let Some(i) = self.selected_index() {
+            self.popup_input.set(format!("{} ", self.tasks[i].text()));
+            self.triage = Some(i);
+            self.open_popup();
+        }
+    }
+    // rebuild the sidebar and the visible rows after the tasks, view or filter changed
+    fn refresh(&mut self) {
+        let projects = self.projects();
+        let view = self.view().clone();
+        self.views.items = vec![View::All, View::Inbox, View::Due];
+        self.views.items.extend(projects.into_iter().map(View::Project));
+        let mut contexts: Vec<String> = self.tasks.iter().filter(|t| !t.is_done() && !self.is_archived(t)).flat_map(|t| t.contexts.clone()).collect();
+        contexts.sort();
+        contexts.dedup();
+        self.views.items.extend(contexts.into_iter().map(View::Context));
+        self.views.items.push(View::DoneLog);
+        let i = self.views.items.iter().position(|v| *v == view).unwrap_or(0);
+        self.views.state.select(Some(i));
+        self.refresh_items();
+        if self.split.is_some() {
+            self.swap_pane();
+            self.refresh_items();
+            self.swap_pane();
+        }
+    }
+    // run `change` with the selection kept on the same todo, by its id,
+    // wherever the change moves it or however it rebuilds the list
+    fn keep_selection(&mut self, change: impl FnOnce(&mut App)) {
+        let tasks = &self.tasks;
+        let selection = self.items.remember(|&i| tasks[i].id.clone());
+        change(self);
+        let tasks = &self.tasks;
+        self.items.restore(selection, |&i| tasks[i].id.clone());
+    }
+    // sorted again after a change, still on the todo selected
+    fn resort(&mut self) {
+        self.keep_selection(App::refresh);
+    }
+    // the view and filter match the task, completed or not
+    fn in_view(&self, task: &Task, today: NaiveDate) -> bool {
+        let view = self.view();
+        view.matches(task)
+            && self.filter.matches(task, today, self.config.stale_days)
+            // archived projects only show in their own view and the done log
+            && (matches!(view, View::Project(_) | View::DoneLog) || !self.is_archived(task))
+    }
+    // completed todo are hidden with h, or by the project's default
+    fn shows_done(&self) -> bool {
+        let project = self.view_project().and_then(|p| self.config.projects.get(p)).and_then(|d| d.show_done);
+        matches!(self.view(), View::DoneLog) || (!self.hide_done && project.unwrap_or(true))
+    }
+    fn hidden_done(&self, today: NaiveDate) -> usize {
+        match self.shows_done() {
+            true => 0,
+            false => self.tasks.iter().filter(|t| t.is_done() && self.in_view(t, today)).count(),
+        }
+    }
+    fn refresh_items(&mut self) {
+        let today = Local::now().date_naive();
+        let view = self.view().clone();
+        let show_done = self.shows_done();
+        self.items.items = (0..self.tasks.len())
+            .filter(|&i| self.in_view(&self.tasks[i], today))
+            .filter(|&i| show_done || !self.tasks[i].is_done())
+            .collect();
+        if let View::DoneLog = view {
+            self.items.items.sort_by_key(|&i| Reverse(self.tasks[i].completed));
+        } else if self.shown_sort() != SortMode::Manual {
+            // stable, so todo that compare equal keep their manual order;
+            // the rows are sorted, todo.txt keeps that order
+            let (sort, tasks) = (self.shown_sort(), &self.tasks);
+            let keys = self.sort_keys().to_vec();
+            self.items.items.sort_by(|&a, &b| sort.order(&tasks[a], &tasks[b], today, &self.config.urgency, &keys));
+        }
+        // stable, so each group keeps the sort
+        let mut items = std::mem::take(&mut self.items.items);
+        items.sort_by_cached_key(|&i| self.group_of(&self.tasks[i], today).map(|(rank, name)| (rank, name.to_lowercase())));
+        let mut prev = None;
+        items.retain(|&i| {
+            let group = self.group_of(&self.tasks[i], today).map(|(_, name)| name);
+            let first = group != prev;
+            prev = group.clone();
+            first || !group.is_some_and(|name| self.folded.contains(&name))
+        });
+        self.items.items = items;
+        match self.items.state.selected() {
+            Some(_) if self.items.items.is_empty() => self.items.unselect(),
+            Some(i) if i >= self.items.items.len() => self.items.state.select(Some(self.items.items.len() - 1)),
+            _ => {}
+        }
+        self.rows = rows::build(self, today);
+        self.rows_on = today;
+        self.decorator_errors();
+    }
+    // drawn again from the todo as they are, for changes that only show in
+    // the rows: a mark, a ticked checklist item, the ages
+    fn refresh_rows(&mut self) {
+        let today = Local::now().date_naive();
+        self.rows = rows::build(self, today);
+        if self.split.is_some() {
+            self.swap_pane();
+            self.rows = rows::build(self, today);
+            self.swap_pane();
+        }
+        self.rows_on = today;
+        self.decorator_errors();
+    }
+    fn decorator_errors(&mut self) {
+        let failed = self.plugins.take_failed();
+        if !failed.is_empty() {
+            self.status = Some(format!("plugin error, decorator turned off: {}", failed.join("; ")));
+        }
+    }
+    fn swap_pane(&mut self) {
+        if let Some(pane) = &mut self.split {
+            let view = self.views.selected_item().cloned().unwrap_or(View::All);
+            std::mem::swap(&mut self.items, &mut pane.items);
+            std::mem::swap(&mut self.rows, &mut pane.rows);
+            let i = self.views.items.iter().position(|v| *v == pane.view).unwrap_or(0);
+            self.views.state.select(Some(i));
+            pane.view = view;
+            self.pane_left = !self.pane_left;
+        }
+    }
+    // the other pane starts on the next project, for triaging the inbox into it
+    fn toggle_split(&mut self) {
+        if self.split.take().is_some() {
+            self.pane_left = true;
+            return;
+        }
+        let i = self.views.state.selected().unwrap_or(0);
+        let view = self.views.items[i + 1..]
+            .iter()
+            .chain(&self.views.items[..i])
+            .find(|v| matches!(v, View::Project(_)))
+            .cloned()
+            .unwrap_or(View::Inbox);
+        self.split = Some(Pane { view, items: StateList::with_items(vec![]), rows: vec![] });
+        self.refresh();
+    }
+    // when the list has the focus the sidebar follows the pane switched to
+    fn switch_pane(&mut self) {
+        if self.split.is_some() {
+            self.swap_pane();
+            self.focus = Focus::List;
+            self.refresh();
+        }
+    }
+    // move the selected (or marked) todo into the other pane's project
+    fn send_across(&mut self, targets: Vec<usize>) {
+        let project = match self.split.as_ref().map(|p| &p.view) {
+            Some(View::Project(name)) => Some(name.to_string()),
+            Some(View::Inbox) => None,
+            Some(_) => {
+                self.status = Some("the other pane shows no project to send to".to_string());
+                return;
+            }
+            None => return,
+        };
+        self.copy_to_project = false;
+        self.move_to_project(targets, project);
+    }
+    // sidebar order: the saved one, then projects first seen in todo.txt, then
+    // the archived ones
+    fn projects(&self) -> Vec<String> {
+        let mut found: Vec<String> = self.tasks.iter().filter_map(|t| t.project.clone()).collect();
+        found.sort();
+        found.dedup();
+        let mut projects = self.state.projects.clone();
+        projects.extend(found.into_iter().filter(|p| !self.state.projects.contains(p)));
+        projects.sort_by_key(|p| self.state.archived.contains(p));
+        projects
+    }
+    fn is_archived(&self, task: &Task) -> bool {
+        task.project.as_ref().is_some_and(|p| self.state.archived.contains(p))
+    }
+    // the project selected in the sidebar, when it has the focus
+    fn sidebar_project(&self) -> Option<String> {
+        match (&self.focus, self.view()) {
+            (Focus::Sidebar, View::Project(name)) => Some(name.to_string()),
+            _ => None,
+        }
+    }
+    fn select_view(&mut self, view: View) {
+        self.refresh();
+        let i = self.views.items.iter().position(|v| *v == view).unwrap_or(0);
+        self.views.state.select(Some(i));
+        self.items.unselect();
+        self.refresh();
+    }
+    // the project order, archive, pane widths and marks live in the state
+    // file, which the sync engine writes to as well
+    fn save_state(&mut self) {
+        let saved = State::load().and_then(|mut state| {
+            state.projects = self.state.projects.clone();
+            state.archived = self.state.archived.clone();
+            state.marks = self.state.marks.clone();
+            state.sidebar_width = self.state.sidebar_width;
+            state.detail_width = self.state.detail_width;
+            state.save()?;
+            Ok(state)
+        });
+        match saved {
+            Ok(state) => self.state = state,
+            Err(err) => self.status = Some(format!("could not save the state file: {}", err)),
+        }
+    }
+    // what a key in the list screen does, see keymap.rs for which key
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Add => self.open_popup(),
+            Action::AddBatch => {
+                self.batch = true;
+                self.open_popup();
+            }
+            // handle_key runs these, they need the terminal or the count
+            Action::Quit | Action::EditNote | Action::PlayMacro => {}
+            Action::Cut => self.cut(1),
+            Action::Yank => self.yank(1),
+            Action::Paste => self.paste_tasks(1),
+            Action::RecordMacro => match self.recording.take() {
+                Some(mut keys) => {
+                    // the key that stopped the recording
+                    keys.pop();
+                    self.status = Some(format!("Recorded {} keys, {} plays them", keys.len(), self.keymap.key(Action::PlayMacro)));
+                    self.macro_keys = keys;
+                }
+                None if !self.replaying => self.recording = Some(vec![]),
+                None => {}
+            },
+            Action::PostponeOverdue if self.overdue_count() > 0 => self.ask(Confirm::PostponeOverdue),
+            Action::PostponeOverdue => {}
+            Action::Undo => self.undo(),
+            Action::CycleSort => self.cycle_sort(),
+            Action::CycleGroup => self.cycle_group(),
+            Action::Fold => self.toggle_fold(),
+            Action::Jump(bucket) => self.jump_to_bucket(bucket),
+            Action::JumpDeadline => self.jump_to_deadline(),
+            Action::SetMark | Action::JumpMark => {
+                self.mark_prefix = Some(action);
+                self.status = Some("press a letter for the mark".to_string());
+            }
+            Action::JumpBack => self.walk_jumps(true),
+            Action::JumpForward => self.walk_jumps(false),
+            Action::TypeAhead => {
+                self.record_jump();
+                self.type_ahead = Some((String::new(), Instant::now()));
+                self.focus = Focus::List;
+                self.status = Some("f: type the start of a title".to_string());
+            }
+            Action::AddCheck => self.start_check(),
+            Action::ToggleCheck => self.toggle_check(),
+            Action::RemoveCheck => self.remove_check(),
+            Action::ToggleAge => {
+                self.show_age = !self.show_age;
+                self.refresh_rows();
+            }
+            Action::NewProject => self.start_project_edit(ProjectEdit::New),
+            Action::RenameProject => {
+                if let Some(name) = self.sidebar_project() {
+                    self.start_project_edit(ProjectEdit::Rename(name));
+                }
+            }
+            Action::ArchiveProject => match self.sidebar_project() {
+                Some(name) if self.state.archived.contains(&name) => self.archive_project(&name),
+                Some(name) => self.ask(Confirm::ArchiveProject(name)),
+                None => {}
+            },
+            Action::DeleteProject => {
+                if let Some(name) = self.sidebar_project() {
+                    self.ask(Confirm::DeleteProject(name));
+                }
+            }
+            Action::MoveProjectDown if self.sidebar_project().is_some() => self.move_project(true),
+            Action::MoveProjectUp if self.sidebar_project().is_some() => self.move_project(false),
+            // only projects move, elsewhere in the sidebar they are plain Down and Up
+            Action::MoveProjectDown => self.perform(Action::Down),
+            Action::MoveProjectUp => self.perform(Action::Up),
+            Action::Filter => self.input_mode = InputMode::Filter,
+            Action::Command => self.input_mode = InputMode::Command,
+            Action::Triage => self.start_triage(),
+            Action::Label => self.open_label_picker(),
+            Action::Context => self.open_context_picker(),
+            Action::Mark => self.toggle_mark(),
+            Action::MoveToProject => self.open_project_picker(false),
+            Action::CopyToProject => self.open_project_picker(true),
+            Action::ToggleSplit => self.toggle_split(),
+            Action::Zoom => self.zoomed = !self.zoomed,
+            Action::HideDone => {
+                self.hide_done = !self.hide_done;
+                self.keep_selection(App::refresh);
+            }
+            Action::Narrow => self.resize(false),
+            Action::Widen => self.resize(true),
+            Action::SwitchPane => self.switch_pane(),
+            Action::SendAcross => self.send_across(self.targets()),
+            Action::CopySummary => self.copy_summary(),
+            Action::Standup => self.overlays.push(Overlay::Standup),
+            Action::FocusMode => match self.selected() {
+                Some(task) => self.focus_mode = Some(FocusMode { id: task.id.to_string(), started: None, elapsed: Duration::ZERO }),
+                None => self.status = Some("select a todo to focus on".to_string()),
+            },
+            Action::Stats => {
+                let project = self.view_project().map(str::to_string);
+                self.overlays.push(Overlay::Stats(Stats { window: report::Window::Week, project }));
+            }
+            Action::History => self.open_git_log(),
+            Action::Backups => self.open_backups(),
+            Action::Activity => self.overlays.push(Overlay::Activity(0)),
+            Action::ToggleDone => {
+                self.last_change = Some(Repeat::ToggleDone);
+                self.toggle_done();
+            }
+            Action::Repeat => match self.last_change.clone() {
+                Some(Repeat::ToggleDone) => self.toggle_done(),
+                Some(Repeat::Label(label)) => self.set_label(label),
+                Some(Repeat::Project(project, copy)) => {
+                    self.copy_to_project = copy;
+                    self.move_to_project(self.targets(), project);
+                }
+                Some(Repeat::Cut(count)) => self.cut(count),
+                None => {}
+            },
+            Action::CycleFocus => {
+                self.focus = match self.focus {
+                    Focus::Sidebar => Focus::List,
+                    Focus::List if self.selected().is_some() => {
+                        self.check_row = 0;
+                        Focus::Detail
+                    }
+                    Focus::List | Focus::Detail => Focus::Sidebar,
+                };
+            }
+            Action::Unselect => {
+                self.items.unselect();
+                if let Focus::Detail = self.focus {
+                    self.focus = Focus::List;
+                }
+            }
+            Action::Down => match self.focus {
+                Focus::Sidebar => {
+                    self.views.next();
+                    self.items.unselect();
+                    self.refresh();
+                }
+                Focus::List => self.items.next(),
+                Focus::Detail => self.move_check(true),
+            },
+            Action::Up => match self.focus {
+                Focus::Sidebar => {
+                    self.views.previous();
+                    self.items.unselect();
+                    self.refresh();
+                }
+                Focus::List => self.items.previous(),
+                Focus::Detail => self.move_check(false),
+            },
+            Action::First | Action::Last => {
+                let last = action == Action::Last;
+                match self.focus {
+                    Focus::Sidebar => {
+                        if last { self.views.select_last() } else { self.views.select_first() }
+                        self.items.unselect();
+                        self.refresh();
+                    }
+                    Focus::List if last => self.items.select_last(),
+                    Focus::List => self.items.select_first(),
+                    Focus::Detail => {
+                        let len = self.selected().map_or(0, |t| t.checklist.len());
+                        self.check_row = if last { len.saturating_sub(1) } else { 0 };
+                    }
+                }
+            }
+            Action::PageDown | Action::PageUp => {
+                let down = action == Action::PageDown;
+                match self.focus {
+                    Focus::Sidebar => {
+                        if down { self.views.page_down() } else { self.views.page_up() }
+                        self.items.unselect();
+                        self.refresh();
+                    }
+                    Focus::List if down => self.items.page_down(),
+                    Focus::List => self.items.page_up(),
+                    Focus::Detail => {}
+                }
+            }
+        }
+    }
+    // what was typed after `:`
+    fn run_command(&mut self) {
+        let input = self.command_input.take();
+        let words: Vec<&str> = input.split_whitespace().collect();
+        match words.split_first() {
+            Some((&"purge", args)) => self.preview_purge(args),
+            Some((&"import", args)) => self.import(args, None),
+            Some((&"export", args)) => self.export(args, None),
+            Some((&"group", args)) => self.set_group(args),
+            Some((&"undolist", _)) => self.open_undo_list(),
+            Some((name, _)) => self.status = Some(format!("unknown command: {}", name)),
+            None => {}
+        }
+    }
+    // the confirm dialog is the dry run, listing what would go
+    fn preview_purge(&mut self, args: &[&str]) {
+        let today = Local::now().date_naive();
+        let purge = match Purge::parse(args) {
+            Ok(purge) => purge,
+            Err(err) => {
+                self.status = Some(err);
+                return;
+            }
+        };
+        match storage::load_archive(today) {
+            Ok(archived) => {
+                let doomed: Vec<Task> = archived.into_iter().filter(|t| purge.matches(t, today, self.config.stale_days)).collect();
+                match doomed.is_empty() {
+                    true => self.status = Some("no archived todo match".to_string()),
+                    false => self.ask(Confirm::Purge(doomed)),
+                }
+            }
+            Err(err) => self.status = Some(format!("could not read done.txt: {}", err)),
+        }
+    }
+    // without a file, the browser picks one
+    fn import(&mut self, args: &[&str], picked: Option<&std::path::Path>) {
+        let all = args;
+        let (args, filter) = Filter::take_arg(args);
+        let skip_existing = args.contains(&"--skip-existing");
+        let path = match picked.or_else(|| args.iter().find(|a| !a.starts_with("--")).map(std::path::Path::new)) {
+            Some(path) => path,
+            None => {
+                let args = all.iter().map(|a| a.to_string()).collect();
+                self.browse(Browse::Import(args), None);
+                return;
+            }
+        };
+        let now = Local::now().naive_local();
+        match import::read(path, now.date()) {
+            Ok(imported) => {
+                let imported = imported
+                    .into_iter()
+                    .filter(|t| filter.matches(t, now.date(), self.config.stale_days))
+                    .map(|t| self.config.with_rules(t))
+                    .collect();
+                self.snapshot();
+                let report = import::merge(&mut self.tasks, imported, skip_existing, now);
+                self.resort();
+                self.overlays.push(Overlay::ImportReport(report));
+            }
+            Err(err) => self.status = Some(format!("could not read {}: {}", path.display(), err)),
+        }
+    }
+    // the rows shown, or with `--selected` the marked (or selected) todo,
+    // narrowed by `--filter`; to a file, or the clipboard without one, or
+    // with `--save` one picked in the browser
+    fn export(&mut self, args: &[&str], picked: Option<&std::path::Path>) {
+        let all = args;
+        let (args, filter) = Filter::take_arg(args);
+        let selected = args.contains(&"--selected");
+        let save = args.contains(&"--save");
+        let args: Vec<&str> = args.into_iter().filter(|a| !a.starts_with("--")).collect();
+        let (format, path) = match args.as_slice() {
+            [name] => (export::Format::parse(name), picked),
+            [name, path] => (export::Format::parse(name), Some(std::path::Path::new(*path))),
+            _ => (None, None),
+        };
+        let format = match format {
+            Some(format) => format,
+            None => {
+                self.status = Some("usage: export <md|org|ics|txt|html> [<file> | --save] [--selected] [--filter <terms>...]".to_string());
+                return;
+            }
+        };
+        if save && path.is_none() {
+            let args = all.iter().map(|a| a.to_string()).collect();
+            self.browse(Browse::Export(args), Some(format!("todo.{}", format.extension())));
+            return;
+        }
+        let today = Local::now().date_naive();
+        let rows = if selected { self.targets() } else { self.items.items.clone() };
+        let tasks: Vec<Task> = rows
+            .into_iter()
+            .map(|i| self.tasks[i].clone())
+            .filter(|t| filter.matches(t, today, self.config.stale_days))
+            .collect();
+        let text = export::render(&tasks, format, chrono::Utc::now().naive_utc());
+        match path {
+            Some(path) => {
+                self.status = Some(match fs::write(path, text) {
+                    Ok(()) => format!("Wrote {} todo to {}", tasks.len(), path.display()),
+                    Err(err) => format!("could not write {}: {}", path.display(), err),
+                })
+            }
+            None => self.copy(&text, &format!("{} todo", tasks.len())),
+        }
+    }
+    fn purge(&mut self, doomed: &[Task]) {
+        let today = Local::now().date_naive();
+        let result = storage::load_archive(today).and_then(|mut archived| {
+            archived.retain(|t| !doomed.iter().any(|d| d.id == t.id));
+            storage::save_archive(&archived)
+        });
+        self.status = Some(match result {
+            Ok(()) => format!("purged {} todo from done.txt", doomed.len()),
+            Err(err) => format!("could not write done.txt: {}", err),
+        });
+    }
+    // Ctrl+Left/Right move the border beside the focused pane; the list keeps
+    // at least a fifth of the screen
+    fn resize(&mut self, right: bool) {
+        let step: i16 = if right { 5 } else { -5 };
+        let layout = &mut self.config.layout;
+        match self.focus {
+            Focus::Sidebar => {
+                // the sidebar only grows into what the detail pane leaves
+                let max = 50.min(80u16.saturating_sub(layout.detail)).max(10) as i16;
+                layout.sidebar = (layout.sidebar as i16 + step).clamp(10, max) as u16;
+            }
+            Focus::List | Focus::Detail => layout.detail = (layout.detail as i16 - step).max(0) as u16,
+        }
+        layout.fit();
+        self.state.sidebar_width = Some(layout.sidebar);
+        self.state.detail_width = Some(layout.detail);
+        self.save_state();
+    }
+    fn start_project_edit(&mut self, edit: ProjectEdit) {
+        self.popup_input.set(match &edit {
+            ProjectEdit::New => String::new(),
+            ProjectEdit::Rename(name) => name.to_string(),
+        });
+        self.project_edit = Some(edit);
+        self.open_popup();
+    }
+    fn new_project(&mut self, name: &str) {
+        let mut projects = self.projects();
+        if !projects.iter().any(|p| p == name) {
+            projects.push(name.to_string());
+            self.state.projects = projects;
+            self.save_state();
+        }
+        self.select_view(View::Project(name.to_string()));
+    }
+    fn rename_project(&mut self, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        let now = Local::now().naive_local();
+        self.snapshot();
+        for task in self.tasks.iter_mut().filter(|t| t.project.as_deref() == Some(old)) {
+            task.project = Some(new.to_string());
+            task.touched = now;
+        }
+        let mut projects = self.projects();
+        projects.retain(|p| p != new);
+        self.state.projects = projects.into_iter().map(|p| if p == old { new.to_string() } else { p }).collect();
+        for archived in self.state.archived.iter_mut().filter(|p| *p == old) {
+            *archived = new.to_string();
+        }
+        self.save_state();
+        self.select_view(View::Project(new.to_string()));
+    }
+    fn move_project(&mut self, down: bool) {
+        if let Some(name) = self.sidebar_project() {
+            let mut projects = self.projects();
+            let i = projects.iter().position(|p| *p == name).unwrap_or(0);
+            let j = if down { i + 1 } else { i.wrapping_sub(1) };
+            if j < projects.len() {
+                projects.swap(i, j);
+                self.state.projects = projects;
+                self.save_state();
+                self.select_view(View::Project(name));
+            }
+        }
+    }
+    fn archive_project(&mut self, name: &str) {
+        match self.state.archived.iter().position(|p| p == name) {
+            Some(i) => {
+                self.state.archived.remove(i);
+            }
+            None => self.state.archived.push(name.to_string()),
+        }
+        self.save_state();
+        self.select_view(View::Project(name.to_string()));
+    }
+    fn delete_project(&mut self, name: &str) {
+        self.snapshot();
+        self.tasks.retain(|t| t.project.as_deref() != Some(name));
+        self.state.projects.retain(|p| p != name);
+        self.state.archived.retain(|p| p != name);
+        self.save_state();
+        self.select_view(View::All);
+    }
+    fn set_filter(&mut self) {
+        self.filter = Filter::parse(self.filter_input.text());
+        self.keep_selection(App::refresh);
+    }
+    // remember the current list so the next change can be undone
+    fn snapshot(&mut self) {
+        self.history.push(self.tasks.clone());
+        self.dirty = true;
+        self.record_jump();
+    }
+    // put the selected todo on the jump list, dropping the entries gone back
+    // over and its older entry
+    fn record_jump(&mut self) {
+        let id = match self.selected() {
+            Some(task) => task.id.to_string(),
+            None => return,
+        };
+        self.jumps.truncate((self.jump_at + 1).min(self.jumps.len()));
+        self.jumps.retain(|j| *j != id);
+        self.jumps.push(id);
+        if self.jumps.len() > JUMPS_SIZE {
+            self.jumps.remove(0);
+        }
+        self.jump_at = self.jumps.len();
+    }
+    // Ctrl+O and Ctrl+I; todo deleted since are skipped
+    fn walk_jumps(&mut self, back: bool) {
+        if back && self.jump_at == self.jumps.len() && self.selected().is_some() {
+            // to come forward to where this started
+            self.record_jump();
+            self.jump_at -= 1;
+        }
+        loop {
+            let at = match back {
+                true if self.jump_at > 0 => self.jump_at - 1,
+                false if self.jump_at + 1 < self.jumps.len() => self.jump_at + 1,
+                _ => {
+                    self.status = Some(format!("at the {} of the jump list", if back { "start" } else { "end" }));
+                    return;
+                }
+            };
+            match self.tasks.iter().position(|t| t.id == self.jumps[at]) {
+                Some(i) => {
+                    self.jump_at = at;
+                    if !self.reveal(i) {
+                        self.status = Some("that todo is filtered out".to_string());
+                    }
+                    return;
+                }
+                // the later entries move down into its place
+                None => {
+                    self.jumps.remove(at);
+                    if back {
+                        self.jump_at = at;
+                    }
+                }
+            }
+        }
+    }
+    // says what it undid, and logs it as that rather than as the change back
+    fn undo(&mut self) {
+        if let Some(tasks) = self.history.pop() {
+            let undone = audit::describe(&tasks, &self.tasks);
+            self.status = Some(match undone.is_empty() {
+                true => "Undid: nothing had changed".to_string(),
+                false => format!("Undid: {}", undone.join("; ")),
+            });
+            self.undone.extend(undone.into_iter().map(|line| format!("undid: {}", line)));
+            self.keep_selection(|app| {
+                app.tasks = tasks;
+                app.refresh();
+            });
+            self.dirty = true;
+        }
+    }
+    // each change undo can take back, newest first
+    fn undo_steps(&self) -> Vec<String> {
+        let mut steps: Vec<String> = (0..self.history.len())
+            .map(|i| {
+                let after = self.history.get(i + 1).unwrap_or(&self.tasks);
+                let changes = audit::describe(&self.history[i], after);
+                if changes.is_empty() { "nothing changed".to_string() } else { changes.join("; ") }
+            })
+            .collect();
+        steps.reverse();
+        steps
+    }
+    fn open_undo_list(&mut self) {
+        if self.history.is_empty() {
+            self.status = Some("Nothing to undo".to_string());
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.overlays.push(Overlay::UndoList(self.undo_steps(), state));
+    }
+    fn save(&mut self) -> io::Result<()> {
+        if self.dirty {
+            if let Err(err) = backup::take(self.config.backups.max(0) as usize) {
+                self.status = Some(format!("backup failed: {}", err));
+            }
+            journal::record(&journal::diff(&self.saved, &self.tasks))?;
+            let changes = match self.undone.is_empty() {
+                true => audit::describe(&self.saved, &self.tasks),
+                false => std::mem::take(&mut self.undone),
+            };
+            if let Err(err) = self.activity.record(Local::now().naive_local(), changes, self.config.activity_log) {
+                self.status = Some(format!("could not write the activity log: {}", err));
+            }
+            storage::save(&self.tasks)?;
+            journal::clear()?;
+            self.saved = self.tasks.clone();
+            self.dirty = false;
+            if let Err(err) = git::commit_if_enabled(&self.config, "update todo.txt") {
+                self.status = Some(format!("git commit failed: {}", err));
+            }
+            self.generation += 1;
+            if let Some(engine) = &self.sync {
+                engine.send(self.generation, &self.tasks);
+            }
+        }
+        Ok(())
+    }
+    // run the overdue hook once a day, for every task overdue by then
+    fn check_overdue(&mut self) -> io::Result<()> {
+        let now = Local::now().naive_local();
+        let today = now.date();
+        if self.overdue_checked == Some(today) || self.config.is_quiet(now.time()) {
+            return Ok(());
+        }
+        self.overdue_checked = Some(today);
+        if self.config.hooks.overdue.is_none() && self.config.hooks.webhook.is_none() {
+            return Ok(());
+        }
+        let mut state = State::load()?;
+        if state.overdue_hooked == Some(today) {
+            return Ok(());
+        }
+        for task in self.tasks.iter().filter(|t| t.is_overdue(today)) {
+            hooks::fire(&self.config.hooks, hooks::Event::Overdue, task);
+        }
+        state.overdue_hooked = Some(today);
+        state.save()
+    }
+    // pop up the first reminder that is due, with a bell
+    fn check_reminders(&mut self) -> io::Result<()> {
+        let now = Local::now().naive_local();
+        // held back until the quiet hours are over
+        if self.overlays.iter().any(|o| matches!(o, Overlay::Reminder(_))) || self.config.is_quiet(now.time()) {
+            return Ok(());
+        }
+        if let Some(task) = self.tasks.iter().find(|t| !t.is_done() && t.remind.is_some_and(|time| time <= now)) {
+            self.overlays.push(Overlay::Reminder(task.id.to_string()));
+            let mut stdout = io::stdout();
+            io::Write::write_all(&mut stdout, b"\x07")?;
+            io::Write::flush(&mut stdout)?;
+        }
+        Ok(())
+    }
+    // off the todo when dismissed, or for later when snoozed
+    fn close_reminder(&mut self, snooze: Option<NaiveDateTime>) {
+        if let Some(Overlay::Reminder(id)) = self.overlays.pop() {
+            if let Some(i) = self.tasks.iter().position(|t| t.id == id) {
+                self.snapshot();
+                // to the minute, as todo.txt keeps it
+                self.tasks[i].remind = snooze.and_then(|time| time.with_second(0)?.with_nanosecond(0));
+                self.tasks[i].touched = Local::now().naive_local();
+                self.refresh_rows();
+            }
+        }
+    }
+    // once a day, move todo completed over `archive_after` days ago to done.txt
+    fn archive_done(&mut self) -> io::Result<()> {
+        let today = Local::now().date_naive();
+        if self.config.archive_after <= 0 || self.archived_on == Some(today) {
+            return Ok(());
+        }
+        self.archived_on = Some(today);
+        let days = self.config.archive_after;
+        let old = |t: &Task| t.completed.is_some_and(|done| (today - done.date()).num_days() > days);
+        let archive: Vec<Task> = self.tasks.iter().filter(|t| old(t)).cloned().collect();
+        if archive.is_empty() {
+            return Ok(());
+        }
+        // written out before they leave todo.txt, so a failure loses nothing
+        storage::archive(&archive, today)?;
+        self.keep_selection(|app| {
+            app.tasks.retain(|t| !old(t));
+            app.refresh();
+        });
+        self.status = Some(format!("archived {} todo completed over {} days ago to done.txt", archive.len(), days));
+        self.dirty = true;
+        self.save()
+    }
+    // once a day, raise the priority of open todo untouched for the
+    // `[escalate]` days, all in one undo step
+    fn escalate(&mut self) -> io::Result<()> {
+        let today = Local::now().date_naive();
+        let rule = &self.config.escalate;
+        if rule.after <= 0 || self.escalated_on == Some(today) {
+            return Ok(());
+        }
+        self.escalated_on = Some(today);
+        let raised = |task: &Task| match task.priority {
+            _ if task.is_done() || (today - task.touched.date()).num_days() < rule.after => None,
+            Some(p) if p > rule.ceiling => Some((p as u8 - 1) as char),
+            Some(_) => None,
+            None => rule.start,
+        };
+        let changes: Vec<(usize, char)> = self.tasks.iter().enumerate().filter_map(|(i, t)| Some((i, raised(t)?))).collect();
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.snapshot();
+        let now = Local::now().naive_local();
+        let show = |p: Option<char>| p.map_or("none".to_string(), |p| format!("({})", p));
+        let lines = changes
+            .into_iter()
+            .map(|(i, priority)| {
+                let task = &mut self.tasks[i];
+                let line = format!("{}: {} → {}", task.title, show(task.priority), show(Some(priority)));
+                task.priority = Some(priority);
+                // the clock starts again for the next step up
+                task.touched = now;
+                line
+            })
+            .collect();
+        self.overlays.push(Overlay::Escalated(lines));
+        self.refresh();
+        self.save()
+    }
+    fn poll_sync(&mut self) -> io::Result<()> {
+        let updates = match &self.sync {
+            Some(engine) => engine.updates(),
+            None => return Ok(()),
+        };
+        for update in updates {
+            let (name, text) = match update {
+                sync::Update::Synced { name, generation, merge } => {
+                    let time = Local::now().format("%H:%M");
+                    let text = match merge.conflicts.len() {
+                        0 => format!("synced {}", time),
+                        n => format!("synced {}, {} conflicts", time, n),
+                    };
+                    // a newer list is already on its way to the engine, which
+                    // puts it on top of this one; these conflicts are left
+                    // behind with it
+                    if generation == self.generation {
+                        self.add_conflicts(&name, merge.conflicts);
+                    }
+                    if generation == self.generation && sync::to_text(&merge.tasks) != sync::to_text(&self.tasks) {
+                        self.keep_selection(|app| {
+                            app.tasks = merge.tasks;
+                            app.refresh();
+                        });
+                        storage::save(&self.tasks)?;
+                        self.saved = self.tasks.clone();
+                        if let Err(err) = git::commit_if_enabled(&self.config, &format!("sync with {}", name)) {
+                            self.status = Some(format!("git commit failed: {}", err));
+                        }
+                    }
+                    (name, text)
+                }
+                sync::Update::Failed { name, error, retry, pending } => {
+                    // the error once, then just the countdown
+                    if !self.sync_status.iter().any(|(n, text)| *n == name && text.starts_with("offline")) {
+                        self.status = Some(format!("{} sync failed: {}", name, error));
+                    }
+                    (name, offline_status(pending, retry))
+                }
+                sync::Update::Pending { name, retry, pending } => (name, offline_status(pending, retry)),
+            };
+            match self.sync_status.iter_mut().find(|(n, _)| *n == name) {
+                Some(entry) => entry.1 = text,
+                None => self.sync_status.push((name, text)),
+            }
+        }
+        Ok(())
+    }
+    // under whatever is open, so a sync finishing doesn't take the keys of a
+    // popup being typed in; they show once it closes
+    fn add_conflicts(&mut self, name: &str, conflicts: Vec<sync::Conflict>) {
+        let mut conflicts = conflicts.into_iter().map(|c| (name.to_string(), c)).collect::<Vec<_>>();
+        if conflicts.is_empty() {
+            return;
+        }
+        match self.overlays.iter_mut().find_map(|o| if let Overlay::Conflicts(waiting) = o { Some(waiting) } else { None }) {
+            Some(waiting) => waiting.append(&mut conflicts),
+            None => self.overlays.insert(0, Overlay::Conflicts(conflicts)),
+        }
+    }
+    // the first conflict of the overlay on top, closing it after the last
+    fn next_conflict(&mut self) -> Option<(String, sync::Conflict)> {
+        let Some(Overlay::Conflicts(waiting)) = self.overlays.last_mut() else { return None };
+        let first = (!waiting.is_empty()).then(|| waiting.remove(0));
+        if waiting.is_empty() {
+            self.overlays.pop();
+        }
+        first
+    }
+    // replace the task with one side of the first conflict
+    fn resolve_conflict(&mut self, keep_remote: bool) {
+        let Some((_, conflict)) = self.next_conflict() else { return };
+        let mut task = if keep_remote { conflict.remote } else { conflict.local };
+        // newest everywhere, so the next sync hands it to the other side
+        task.touched = Local::now().naive_local();
+        self.snapshot();
+        match self.tasks.iter_mut().find(|t| t.id == task.id) {
+            Some(existing) => *existing = task,
+            None => self.tasks.push(task),
+        }
+        self.refresh();
+    }
+    // edit our version in the popup with theirs in the title
+    fn merge_conflict(&mut self) {
+        let Some((name, conflict)) = self.next_conflict() else { return };
+        if let Some(i) = self.tasks.iter().position(|t| t.id == conflict.local.id) {
+            self.popup_input.set(format!("{} ", conflict.local.text()));
+            self.triage = Some(i);
+            self.merging = Some(format!("{}: {}", name, conflict.remote.text()));
+            self.open_popup();
+        }
+    }
+    fn open_git_log(&mut self) {
+        if !self.config.git {
+            self.status = Some("Set git = true in the config to keep a history".to_string());
+            return;
+        }
+        match git::log() {
+            Ok(commits) if !commits.is_empty() => {
+                let mut state = ListState::default();
+                state.select(Some(0));
+                self.overlays.push(Overlay::GitLog(GitLog { commits, state, diff: String::new() }));
+                self.load_git_diff();
+            },
+            Ok(_) => self.status = Some("No history yet".to_string()),
+            Err(err) => self.status = Some(format!("git log failed: {}", err)),
+        }
+    }
+    fn open_backups(&mut self) {
+        let today = Local::now().date_naive();
+        match backup::list() {
+            Ok(backups) if !backups.is_empty() => {
+                let backups = backups
+                    .into_iter()
+                    .map(|b| {
+                        let count = fs::read_to_string(&b.path).map_or(0, |text| sync::from_text(&text, today).len());
+                        (b, count)
+                    })
+                    .collect();
+                let mut state = ListState::default();
+                state.select(Some(0));
+                self.overlays.push(Overlay::Backups(BackupPicker { backups, state }));
+            }
+            Ok(_) => self.status = Some("No backups yet".to_string()),
+            Err(err) => self.status = Some(format!("could not list the backups: {}", err)),
+        }
+    }
+    fn move_backup(&mut self, down: bool) {
+        if let Some(Overlay::Backups(picker)) = self.overlays.last_mut() {
+            let i = picker.state.selected().unwrap_or(0);
+            let i = if down { (i + 1).min(picker.backups.len() - 1) } else { i.saturating_sub(1) };
+            picker.state.select(Some(i));
+        }
+    }
+    // u undoes it like any other change
+    fn restore_backup(&mut self) {
+        let (path, time) = match self.overlays.last() {
+            Some(Overlay::Backups(picker)) => {
+                let (backup, _) = &picker.backups[picker.state.selected().unwrap_or(0)];
+                (backup.path.clone(), backup.time)
+            }
+            _ => return,
+        };
+        self.restore_file(&path, &format!("the backup of {}", time.format("%Y-%m-%d %H:%M")));
+    }
+    // the list replaced by a todo.txt file, closing the dialog it was picked in
+    fn restore_file(&mut self, path: &std::path::Path, what: &str) {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                self.snapshot();
+                self.keep_selection(|app| {
+                    app.tasks = sync::from_text(&text, Local::now().date_naive());
+                    app.refresh();
+                });
+                self.overlays.pop();
+                self.status = Some(format!("Restored the list from {}", what));
+            }
+            Err(err) => self.status = Some(format!("could not read {}: {}", path.display(), err)),
+        }
+    }
+    // from the working directory, or the backups for a restore
+    fn browse(&mut self, purpose: Browse, save_as: Option<String>) {
+        let dir = match purpose {
+            Browse::Restore => backup::dir(),
+            Browse::Import(_) | Browse::Export(_) => env::current_dir().ok().or_else(paths::home).unwrap_or_else(paths::data_dir),
+        };
+        self.overlays.push(Overlay::Browse(Browser::open(dir, save_as), purpose));
+    }
+    fn browsed(&mut self, path: &std::path::Path) {
+        let Some(Overlay::Browse(_, purpose)) = self.overlays.pop() else { return };
+        match purpose {
+            Browse::Import(args) => self.import(&args.iter().map(String::as_str).collect::<Vec<_>>(), Some(path)),
+            Browse::Export(args) => self.export(&args.iter().map(String::as_str).collect::<Vec<_>>(), Some(path)),
+            Browse::Restore => {
+                // restore_file closes the backup list under it
+                self.restore_file(path, &path.display().to_string());
+            }
+        }
+    }
+    fn move_git_log(&mut self, down: bool) {
+        if let Some(Overlay::GitLog(log)) = self.overlays.last_mut() {
+            let i = log.state.selected().unwrap_or(0);
+            let i = if down { (i + 1).min(log.commits.len() - 1) } else { i.saturating_sub(1) };
+            log.state.select(Some(i));
+        }
+        self.load_git_diff();
+    }
+    fn load_git_diff(&mut self) {
+        if let Some(Overlay::GitLog(log)) = self.overlays.last_mut() {
+            let commit = &log.commits[log.state.selected().unwrap_or(0)];
+            log.diff = git::diff(&commit.hash).unwrap_or_else(|err| err.to_string());
+        }
+    }
+    // put the list back the way it was in the selected commit
+    fn restore_git_version(&mut self) {
+        let hash = match self.overlays.last() {
+            Some(Overlay::GitLog(log)) => log.commits[log.state.selected().unwrap_or(0)].hash.to_string(),
+            _ => return,
+        };
+        match git::file_at(&hash) {
+            Ok(text) => {
+                let today = Local::now().date_naive();
+                self.snapshot();
+                self.keep_selection(|app| {
+                    app.tasks = sync::from_text(&text, today);
+                    app.refresh();
+                });
+                self.overlays.pop();
+                self.status = Some(format!("Restored the list from {}", hash));
+            },
+            Err(err) => self.status = Some(format!("git show failed: {}", err)),
+        }
+    }
+    // the open todo due soonest, overdue ones aside, and the time left until
+    // the end of its due day
+    fn next_deadline(&self, now: NaiveDateTime) -> Option<(usize, chrono::Duration)> {
+        let (i, due) = (0..self.tasks.len())
+            .filter(|&i| !self.tasks[i].is_done() && !self.is_archived(&self.tasks[i]))
+            .filter_map(|i| Some((i, self.tasks[i].due.filter(|&due| due >= now.date())?)))
+            .min_by_key(|&(_, due)| due)?;
+        let end = due.succ_opt()?.and_hms_opt(0, 0, 0)?;
+        Some((i, end - now))
+    }
+    fn jump_to_deadline(&mut self) {
+        let i = match self.next_deadline(Local::now().naive_local()) {
+            Some((i, _)) => i,
+            None => {
+                self.status = Some("nothing is due".to_string());
+                return;
+            }
+        };
+        self.record_jump();
+        if !self.reveal(i) {
+            self.status = Some("the todo due next is filtered out".to_string());
+        }
+    }
+    // select task `i`, in its project's view when the open one doesn't list
+    // it, or else All; false when the filter hides it even there
+    fn reveal(&mut self, i: usize) -> bool {
+        if !self.items.items.contains(&i) {
+            let task = &self.tasks[i];
+            let view = match &task.project {
+                Some(project) => View::Project(project.to_string()),
+                None if !task.is_done() => View::Inbox,
+                None => View::All,
+            };
+            self.select_view(view);
+        }
+        if !self.items.items.contains(&i) {
+            self.select_view(View::All);
+        }
+        match self.items.items.iter().position(|&row| row == i) {
+            Some(row) => {
+                self.items.state.select(Some(row));
+                self.focus = Focus::List;
+                true
+            }
+            None => false,
+        }
+    }
+    fn set_mark(&mut self, mark: char) {
+        match self.selected().map(|t| (t.id.to_string(), t.title.to_string())) {
+            Some((id, title)) => {
+                self.state.marks.insert(mark, id);
+                self.save_state();
+                self.status = Some(format!("mark {} is on {}", mark, title));
+            }
+            None => self.status = Some("select a todo to mark".to_string()),
+        }
+    }
+    fn jump_to_mark(&mut self, mark: char) {
+        let i = self.state.marks.get(&mark).and_then(|id| self.tasks.iter().position(|t| t.id == *id));
+        if i.is_some() {
+            self.record_jump();
+        }
+        match i {
+            Some(i) if !self.reveal(i) => self.status = Some(format!("the todo at mark {} is filtered out", mark)),
+            Some(_) => {}
+            None => self.status = Some(format!("no todo has mark {}", mark)),
+        }
+    }
+    // select the next row whose title starts with the letters typed, or else
+    // has them; the first letter moves on from the selected row, later ones
+    // keep it while it still matches
+    fn type_ahead_to(&mut self) {
+        let text = match &self.type_ahead {
+            Some((text, _)) => text.to_lowercase(),
+            None => return,
+        };
+        self.status = Some(format!("f: {}", text));
+        let len = self.items.items.len();
+        let from = match self.items.state.selected() {
+            Some(row) if text.chars().count() > 1 => row,
+            Some(row) => row + 1,
+            None => 0,
+        };
+        let rows: Vec<usize> = (0..len).map(|i| (from + i) % len).filter(|&row| !self.is_folded(row)).collect();
+        let title = |row: usize| self.tasks[self.items.items[row]].title.to_lowercase();
+        let found = rows.iter().find(|&&row| title(row).starts_with(&text)).or_else(|| rows.iter().find(|&&row| title(row).contains(&text)));
+        match found {
+            Some(&row) => self.items.state.select(Some(row)),
+            None => self.status = Some(format!("f: {} (no match)", text)),
+        }
+    }
+    // select the first row of a due bucket, in the due view
+    fn jump_to_bucket(&mut self, bucket: usize) {
+        let today = Local::now().date_naive();
+        let row = self.items.items.iter().position(|&i| due_bucket(self.tasks[i].due, today).0 == bucket);
+        match row {
+            Some(row) => {
+                self.record_jump();
+                self.items.state.select(Some(row));
+                self.focus = Focus::List;
+            }
+            None => self.status = Some(format!("nothing due {}", DUE_BUCKETS[bucket].to_lowercase())),
+        }
+    }
+    fn cycle_group(&mut self) {
+        self.group = Group::next(self.group);
+        self.folded.clear();
+        self.refresh();
+    }
+    fn set_group(&mut self, args: &[&str]) {
+        match args {
+            ["none"] => self.group = None,
+            [name] if Group::parse(name).is_some() => self.group = Group::parse(name),
+            _ => {
+                self.status = Some("usage: group project|context|due|none".to_string());
+                return;
+            }
+        }
+        self.folded.clear();
+        self.refresh();
+    }
+    // fold or unfold the group of the selected row, which stays selected
+    fn toggle_fold(&mut self) {
+        let today = Local::now().date_naive();
+        let name = match self.items.state.selected().and_then(|row| self.row_group(row, today)) {
+            Some(name) => name,
+            None => {
+                self.status = Some("the list is not grouped, g groups it".to_string());
+                return;
+            }
+        };
+        match self.folded.iter().position(|f| *f == name) {
+            Some(i) => {
+                self.folded.remove(i);
+            }
+            None => self.folded.push(name.to_string()),
+        }
+        self.refresh_items();
+        let row = (0..self.items.items.len()).find(|&row| self.row_group(row, today).as_ref() == Some(&name));
+        self.items.state.select(row);
+    }
+    fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        if self.sort == SortMode::Custom && self.sort_keys().is_empty() {
+            self.sort = self.sort.next();
+        }
+        self.resort();
+    }
+    // the sort the list is shown in: the one picked with s, or else the
+    // open project's default, which leaves the file's order alone
+    fn shown_sort(&self) -> SortMode {
+        let default = self.view_project().and_then(|p| self.config.projects.get(p)).and_then(|d| d.sort.as_deref());
+        match (self.sort, default) {
+            // a project's sort that names no mode is an expression
+            (SortMode::Manual, Some(sort)) => SortMode::parse(sort).unwrap_or(SortMode::Custom),
+            (SortMode::Manual, None) if !self.config.sort.is_empty() => SortMode::Custom,
+            (sort, _) => sort,
+        }
+    }
+    // the open project's sort expression, or else the config's
+    fn sort_keys(&self) -> &[sorting::Key] {
+        match self.view_project().and_then(|p| self.config.projects.get(p)) {
+            Some(defaults) if defaults.sort.as_deref().and_then(SortMode::parse).is_none() && !defaults.sort_by.is_empty() => &defaults.sort_by,
+            _ => &self.config.sort,
+        }
+    }
+    // estimated minutes of the open todo on screen, and of those due today or earlier
+    fn workload(&self, today: NaiveDate) -> (u32, u32) {
+        let open = || self.items.items.iter().map(|&i| &self.tasks[i]).filter(|t| !t.is_done());
+        let view = open().filter_map(|t| t.estimate).fold(0, u32::saturating_add);
+        let planned =
+            self.tasks.iter().filter(|t| !t.is_done() && t.due.is_some_and(|d| d <= today)).filter_map(|t| t.estimate).fold(0, u32::saturating_add);
+        (view, planned)
+    }
+    fn overdue_count(&self) -> usize {
+        let today = Local::now().date_naive();
+        self.tasks.iter().filter(|t| t.is_overdue(today)).count()
+    }
+    fn postpone_overdue(&mut self) {
+        let today = Local::now().date_naive();
+        self.snapshot();
+        for task in self.tasks.iter_mut().filter(|t| t.is_overdue(today)) {
+            task.due = Some(today);
+            task.touched = Local::now().naive_local();
+        }
+        self.refresh();
+    }
+    // placeholder shown instead of an empty list
+    fn empty_message(&self) -> &'static str {
+        if self.tasks.is_empty() {
+            "No todo yet - press p to add one"
+        } else if !self.filter.is_empty() && self.filter.only_stale() {
+            "No stale todo - the list is fresh"
+        } else if !self.filter.is_empty() {
+            "No todo match the filter - press / to change it"
+        } else if let View::Inbox = self.view() {
+            "Inbox zero - nothing left to triage"
+        } else if let View::DoneLog = self.view() {
+            "Nothing done yet - press Space on a todo to complete it"
+        } else {
+            "Nothing here - press p to add a todo"
+        }
+    }
+    fn dismiss_whats_new(&mut self) -> io::Result<()> {
+        self.overlays.pop();
+        // reloaded, the sync engine writes to it as well
+        self.state = State::load()?;
+        self.state.version = Some(env!("CARGO_PKG_VERSION").to_string());
+        self.state.save()
+    }
+    fn paste(&mut self, text: &str) {
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.len() > 1 && self.adding() {
+            self.ask(Confirm::SplitPaste(lines));
+        } else if lines.len() > 1 {
+            // a project name, checklist item or edited todo is one line
+            self.insert(&lines.join(" "));
+        } else {
+            // kept as it is, an input method commits its text this way and
+            // its spaces count
+            self.insert(&text.replace(['\r', '\n'], ""));
+        }
+    }
+    // typed or committed text at the popup input's cursor, over the
+    // selection, as much of it as `max_length` leaves room for
+    fn insert(&mut self, text: &str) {
+        let room = match self.config.max_length {
+            max if max > 0 && self.editing_todo() => max.saturating_sub(self.popup_input.text().chars().count()),
+            _ => usize::MAX,
+        };
+        let fits: String = text
+            .graphemes(true)
+            .scan(0, |used, g| {
+                *used += g.chars().count();
+                (*used <= room).then_some(g)
+            })
+            .collect();
+        self.popup_error = (fits.len() < text.len()).then(|| format!("{} characters at most", self.config.max_length));
+        self.popup_input.insert(&fits);
+    }
+    // each line that Enter would take as a todo; the first refusal is left
+    // in `popup_error`
+    fn push_lines(&mut self, lines: &[String]) {
+        let today = Local::now().date_naive();
+        self.snapshot();
+        for (n, line) in lines.iter().enumerate() {
+            if let Some(error) = self.check_todo(line) {
+                self.popup_error.get_or_insert(format!("line {} not added: {}", n + 1, error));
+                continue;
+            }
+            let task = self.config.new_task(&quick::expand(line, today), self.view_project(), today);
+            hooks::fire(&self.config.hooks, hooks::Event::Added, &task);
+            self.tasks.push(task);
+        }
+        self.resort();
+    }
+    // the checklist row in range of the selected task's checklist
+    fn check_index(&self) -> Option<usize> {
+        let len = self.selected()?.checklist.len();
+        (len > 0).then(|| self.check_row.min(len - 1))
+    }
+    fn move_check(&mut self, down: bool) {
+        if let (Some(row), Some(task)) = (self.check_index(), self.selected()) {
+            let len = task.checklist.len();
+            self.check_row = if down { (row + 1) % len } else { (row + len - 1) % len };
+        }
+    }
+    fn toggle_check(&mut self) {
+        if let (Some(row), Some(i)) = (self.check_index(), self.selected_index()) {
+            self.snapshot();
+            let item = &mut self.tasks[i].checklist[row];
+            item.0 = !item.0;
+            self.tasks[i].touched = Local::now().naive_local();
+            self.refresh_rows();
+        }
+    }
+    fn remove_check(&mut self) {
+        if let (Some(row), Some(i)) = (self.check_index(), self.selected_index()) {
+            self.snapshot();
+            self.tasks[i].checklist.remove(row);
+            self.tasks[i].touched = Local::now().naive_local();
+            self.refresh_rows();
+        }
+    }
+    fn start_check(&mut self) {
+        if let Some(i) = self.selected_index() {
+            self.popup_input.clear();
+            self.checking = Some(i);
+            self.open_popup();
+        }
+    }
+    fn set_note(&mut self, i: usize, note: &str) {
+        let note = note.trim_end();
+        let note = if note.trim().is_empty() { None } else { Some(note.to_string()) };
+        if note != self.tasks[i].note {
+            self.snapshot();
+            self.tasks[i].note = note;
+            self.tasks[i].touched = Local::now().naive_local();
+            self.refresh_rows();
+        }
+    }
+    // the marked todo, or the selected row and the `count - 1` after it
+    fn rows_from_selection(&self, count: usize) -> Vec<usize> {
+        match (self.marked.is_empty(), self.items.state.selected()) {
+            (true, Some(row)) if !self.is_folded(row) => self.items.items.iter().skip(row).take(count).copied().collect(),
+            _ => self.targets(),
+        }
+    }
+    fn yank(&mut self, count: usize) {
+        let rows = self.rows_from_selection(count);
+        if rows.is_empty() {
+            return;
+        }
+        self.ring.insert(0, rows.iter().map(|&i| self.tasks[i].clone()).collect());
+        self.ring.truncate(RING_SIZE);
+        self.status = Some(format!("Yanked {} todo", rows.len()));
+    }
+    fn cut(&mut self, count: usize) {
+        let rows = self.rows_from_selection(count);
+        if rows.is_empty() {
+            return;
+        }
+        self.last_change = Some(Repeat::Cut(count));
+        self.snapshot();
+        self.ring.insert(0, rows.iter().map(|&i| self.tasks[i].clone()).collect());
+        self.ring.truncate(RING_SIZE);
+        let ids: Vec<String> = rows.iter().map(|&i| self.tasks[i].id.to_string()).collect();
+        self.marked.clear();
+        self.status = Some(format!("Cut {} todo, {} pastes them", ids.len(), self.keymap.key(Action::Paste)));
+        self.keep_selection(|app| {
+            app.tasks.retain(|t| !ids.contains(&t.id));
+            app.refresh();
+        });
+    }
+    // entry `nth` of the ring (1 the newest) under the selected row, in the
+    // project of the view; a todo whose id is still in the list is pasted as
+    // a copy with a new one
+    fn paste_tasks(&mut self, nth: usize) {
+        let entry = match self.ring.get(nth - 1) {
+            Some(entry) => entry.clone(),
+            None => {
+                self.status = Some(format!("Nothing to paste, the ring holds {}", self.ring.len()));
+                return;
+            }
+        };
+        let project = match self.view() {
+            View::Project(name) => Some(Some(name.to_string())),
+            View::Inbox => Some(None),
+            _ => None,
+        };
+        let now = Local::now().naive_local();
+        self.snapshot();
+        let first = self.selected_index().map_or(self.tasks.len(), |i| i + 1);
+        for (at, mut task) in (first..).zip(entry) {
+            if self.tasks.iter().any(|t| t.id == task.id) {
+                task.id = task::new_id();
+            }
+            if let Some(project) = &project {
+                task.project = project.clone();
+            }
+            task.touched = now;
+            self.tasks.insert(at, task);
+        }
+        self.refresh();
+    }
+    // the selected row and the `count - 1` after it, as one change
+    fn toggle_rows(&mut self, count: usize) {
+        self.last_change = Some(Repeat::ToggleDone);
+        let first = match self.items.state.selected() {
+            Some(row) if count > 1 => row,
+            _ => return self.toggle_done(),
+        };
+        let ids: Vec<String> = self.items.items.iter().skip(first).take(count).map(|&i| self.tasks[i].id.to_string()).collect();
+        let now = Local::now().naive_local();
+        self.snapshot();
+        for task in self.tasks.iter_mut().filter(|t| ids.contains(&t.id)) {
+            task.completed = match task.completed {
+                Some(_) => None,
+                None => Some(now),
+            };
+            task.touched = now;
+            if task.is_done() {
+                hooks::fire(&self.config.hooks, hooks::Event::Completed, task);
+            }
+        }
+        self.refresh();
+    }
+    fn toggle_done(&mut self) {
+        if let Some(i) = self.selected_index() {
+            let now = Local::now().naive_local();
+            self.snapshot();
+            let task = &mut self.tasks[i];
+            task.completed = match task.completed {
+                Some(_) => None,
+                None => Some(now),
+            };
+            task.touched = now;
+            if task.is_done() {
+                hooks::fire(&self.config.hooks, hooks::Event::Completed, task);
+            }
+            self.refresh();
+        }
+    }
+    fn copy_summary(&mut self) {
+        let text = report::daily_summary(&self.tasks, Local::now().date_naive(), report::Format::Markdown);
+        self.copy(&text, "today's summary");
+    }
+    fn copy(&mut self, text: &str, what: &str) {
+        self.status = Some(match clipboard::copy(text) {
+            Ok(()) => format!("Copied {} to the clipboard", what),
+            Err(err) => format!("Copy failed: {}", err),
+        });
+    }
+    // how many open todo pass `test`, for a picker's preview line
+    fn count_open(&self, test: impl Fn(&Task) -> bool) -> String {
+        let n = self.tasks.iter().filter(|t| !t.is_done() && test(t)).count();
+        format!("{} open todo", n)
+    }
+    fn open_label_picker(&mut self) {
+        if let Some(task) = self.selected() {
+            let row = task.label.and_then(|l| LABELS.iter().position(|&x| x == l)).map_or(0, |i| i + 1);
+            let mut items = vec![picker::Item::new("none", self.count_open(|t| t.label.is_none()))];
+            items.extend(LABELS.iter().map(|&l| picker::Item {
+                label: l.name().to_string(),
+                preview: self.count_open(|t| t.label == Some(l)),
+                color: Some(label_color(l)),
+            }));
+            self.overlays.push(Overlay::Picker(Picker::new("Label", items, row), Pick::Label));
+        }
+    }
+    // the contexts in use, to add one to the selected todo or take it off
+    fn open_context_picker(&mut self) {
+        let Some(task) = self.selected() else { return };
+        let mut contexts: Vec<&String> = self.tasks.iter().flat_map(|t| &t.contexts).collect();
+        contexts.sort();
+        contexts.dedup();
+        let items = contexts
+            .into_iter()
+            .map(|c| {
+                let preview = self.count_open(|t| t.contexts.contains(c));
+                let preview = if task.contexts.contains(c) { format!("{} · Enter takes it off", preview) } else { preview };
+                picker::Item::new(format!("@{}", c), preview)
+            })
+            .collect();
+        self.overlays.push(Overlay::Picker(Picker::new("Context", items, 0), Pick::Context));
+    }
+    fn toggle_context(&mut self, context: &str) {
+        let context = context.trim_start_matches(['@', '#']);
+        if context.is_empty() || context.contains(char::is_whitespace) {
+            return;
+        }
+        if let Some(i) = self.selected_index() {
+            self.snapshot();
+            let task = &mut self.tasks[i];
+            match task.contexts.iter().position(|c| c == context) {
+                Some(at) => drop(task.contexts.remove(at)),
+                None => task.contexts.push(context.to_string()),
+            }
+            task.touched = Local::now().naive_local();
+            self.refresh();
+        }
+    }
+    fn set_label(&mut self, label: Option<Label>) {
+        if let Some(i) = self.selected_index() {
+            self.snapshot();
+            self.tasks[i].label = label;
+            self.tasks[i].touched = Local::now().naive_local();
+            match self.sort {
+                SortMode::Label => self.resort(),
+                _ => self.refresh(),
+            }
+        }
+    }
+    fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected().map(|t| t.id.to_string()) {
+            match self.marked.iter().position(|m| *m == id) {
+                Some(i) => {
+                    self.marked.remove(i);
+                }
+                None => self.marked.push(id),
+            }
+            self.refresh_rows();
+        }
+    }
+    // what m and M act on
+    fn targets(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            return self.selected_index().into_iter().collect();
+        }
+        (0..self.tasks.len()).filter(|&i| self.marked.contains(&self.tasks[i].id)).collect()
+    }
+    fn picker_projects(&self) -> Vec<Option<String>> {
+        let mut rows = vec![None];
+        rows.extend(self.projects().into_iter().map(Some));
+        rows
+    }
+    fn open_project_picker(&mut self, copy: bool) {
+        if !self.targets().is_empty() {
+            let project = self.view_project();
+            let row = self.picker_projects().iter().position(|p| p.as_deref() == project).unwrap_or(0);
+            let items = self
+                .picker_projects()
+                .into_iter()
+                .map(|p| {
+                    let preview = self.count_open(|t| t.project == p);
+                    picker::Item::new(p.map_or("Inbox".to_string(), |p| format!("+{}", p)), preview)
+                })
+                .collect();
+            let count = self.targets().len();
+            let title = format!("{} {} todo to", if copy { "Copy" } else { "Move" }, count);
+            self.overlays.push(Overlay::Picker(Picker::new(title, items, row), Pick::Project));
+            self.copy_to_project = copy;
+        }
+    }
+    fn move_to_project(&mut self, targets: Vec<usize>, project: Option<String>) {
+        if targets.is_empty() {
+            return;
+        }
+        let now = Local::now().naive_local();
+        self.snapshot();
+        for i in targets.iter().copied() {
+            if self.copy_to_project {
+                // the copy keeps everything but the id, so sync sees a new todo
+                let mut copy = self.tasks[i].clone();
+                copy.id = task::new_id();
+                copy.project = project.clone();
+                copy.touched = now;
+                self.tasks.push(copy);
+            } else {
+                self.tasks[i].project = project.clone();
+                self.tasks[i].touched = now;
+            }
+        }
+        let target = project.map_or("the Inbox".to_string(), |p| format!("+{}", p));
+        let verb = if self.copy_to_project { "copied" } else { "moved" };
+        self.status = Some(format!("{} {} todo to {}", verb, targets.len(), target));
+        self.marked.clear();
+        self.refresh();
+    }
+    // click a row to select it; drag a todo onto another row to reorder the
+    // list, or onto a project in the sidebar to move it there
+    fn mouse(&mut self, event: MouseEvent) {
+        let (column, row) = (event.column, event.row);
+        self.hover = None;
+        match event.kind {
+            MouseEventKind::Moved => self.hover = Some((column, row)),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.split.as_ref().is_some_and(|p| p.items.row_at(column, row).is_some()) {
+                    self.switch_pane();
+                }
+                if let Some(i) = self.views.row_at(column, row) {
+                    self.focus = Focus::Sidebar;
+                    self.views.state.select(Some(i));
+                    self.items.unselect();
+                    self.refresh();
+                } else if let Some(i) = self.items.row_at(column, row) {
+                    self.focus = Focus::List;
+                    self.items.state.select(Some(i));
+                    self.dragging = Some(self.tasks[self.items.items[i]].id.to_string());
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let from = match self.dragging.take().and_then(|id| self.tasks.iter().position(|t| t.id == id)) {
+                    Some(from) => from,
+                    None => return,
+                };
+                // a marked todo takes the other marked ones along
+                let targets = if self.marked.contains(&self.tasks[from].id) { self.targets() } else { vec![from] };
+                if let Some(i) = self.items.row_at(column, row) {
+                    self.reorder(from, self.items.items[i]);
+                } else if self.split.as_ref().is_some_and(|p| p.items.row_at(column, row).is_some()) {
+                    self.send_across(targets);
+                } else if let Some(i) = self.views.row_at(column, row) {
+                    let project = match &self.views.items[i] {
+                        View::Project(name) => Some(name.to_string()),
+                        View::Inbox => None,
+                        _ => return,
+                    };
+                    self.copy_to_project = false;
+                    self.move_to_project(targets, project);
+                }
+            }
+            _ => {}
+        }
+    }
+    // put task `from` where task `to` is in todo.txt, which is the manual order
+    fn reorder(&mut self, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        if self.shown_sort() != SortMode::Manual || matches!(self.view(), View::DoneLog) {
+            self.status = Some("switch to the manual sort (s) to reorder by dragging".to_string());
+            return;
+        }
+        if self.group.is_some() {
+            self.status = Some("ungroup the list (g) to reorder by dragging".to_string());
+            return;
+        }
+        self.snapshot();
+        let task = self.tasks.remove(from);
+        self.tasks.insert(to, task);
+        self.refresh();
+        let row = self.items.items.iter().position(|&i| i == to);
+        self.items.state.select(row);
+    }
+    // a key a plugin bound in the list
+    fn run_plugin_key(&mut self, key: char) {
+        let actions = match self.plugins.run_key(key, &self.tasks) {
+            Ok(actions) => actions,
+            Err(err) => {
+                self.status = Some(format!("plugin error: {}", err));
+                return;
+            }
+        };
+        let mut tasks = self.tasks.clone();
+        let applied = plugin::apply(actions, &mut tasks, &self.config);
+        if applied.changed {
+            self.snapshot();
+            self.keep_selection(|app| {
+                app.tasks = tasks;
+                app.refresh();
+            });
+        }
+        if !applied.messages.is_empty() {
+            self.status = Some(applied.messages.join("; "));
+        }
+    }
+    // whether the help line holds what the top overlay asks for
+    fn prompting(&self) -> bool {
+        matches!(self.overlays.last(), Some(Overlay::Popup | Overlay::Picker(..) | Overlay::Confirm(_)))
+    }
+    fn ask(&mut self, confirm: Confirm) {
+        self.overlays.push(Overlay::Confirm(confirm));
+    }
+    fn open_popup(&mut self) {
+        if !matches!(self.overlays.last(), Some(Overlay::Popup)) {
+            self.overlays.push(Overlay::Popup);
+        }
+    }
+    fn close_popup(&mut self) {
+        if let Some(Overlay::Popup) = self.overlays.last() {
+            self.overlays.pop();
+        }
+    }
+}
+
+// the command line and the TUI; src/main.rs is only this, so the benches
+// in benches/ can get at the rest
+pub fn run() -> Result<(), io::Error> {
+    paths::check()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("quick") => return cli::quick(&args[1..]),
+        Some("summary") => return cli::summary(&args[1..]),
+        Some("ical") => return cli::ical(&args[1..]),
+        Some("serve") => return cli::serve(&args[1..]),
+        Some("sync") => return cli::sync(&args[1..]),
+        Some("org") => return cli::org(&args[1..]),
+        Some("todoist") => return cli::todoist(&args[1..]),
+        Some("mstodo") => return cli::mstodo(&args[1..]),
+        Some("run") => return cli::run(&args[1..]),
+        Some("purge") => return cli::purge(&args[1..]),
+        Some("import") => return cli::import(&args[1..]),
+        Some("export") => return cli::export(&args[1..]),
+        Some("print") => return cli::print(&args[1..]),
+        Some("keys") => return cli::keys(&args[1..]),
+        _ => {}
+    }
+
+    let app = load_app()?;
+
+    let enhanced = app.config.enhanced_keys;
+    events::enter(enhanced)?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    let res = run_app(&mut terminal, app);
+    events::leave(enhanced)?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        println!("{:?}", err)
+    }
+    Ok(())
+}
+
+// the list, config, keys, plugins and state from the data directory, with
+// what's new and any key problems to show first
+fn load_app() -> io::Result<App> {
+    let mut app = App::new();
+    app.tasks = storage::load(Local::now().date_naive())?;
+    app.saved = app.tasks.clone();
+    let ops = journal::pending(Local::now().date_naive())?;
+    if !ops.is_empty() {
+        app.ask(Confirm::Replay(ops));
+    }
+    app.config = Config::load()?;
+    app.keymap = Keymap::new(&app.config.keys, &app.config.leader);
+    if app.config.activity_log {
+        app.activity = audit::Log::load()?;
+    }
+    app.hide_done = app.config.hide_done;
+    let providers = sync::providers(&app.config)?;
+    if !providers.is_empty() {
+        let interval = Duration::from_secs(app.config.sync_interval.max(10) as u64);
+        app.sync = Some(sync::spawn(providers, interval, app.tasks.clone()));
+    }
+    let (plugins, errors) = Plugins::load();
+    app.plugins = plugins;
+    if !errors.is_empty() {
+        app.status = Some(format!("plugin error: {}", errors.join("; ")));
+    }
+    // the sidebar's project order and the pane widths are in the state file
+    app.state = State::load()?;
+    if let Some(width) = app.state.sidebar_width {
+        app.config.layout.sidebar = width;
+    }
+    if let Some(width) = app.state.detail_width {
+        app.config.layout.detail = width;
+    }
+    app.config.layout.fit();
+    app.refresh();
+    if app.state.version.as_deref() != Some(env!("CARGO_PKG_VERSION")) {
+        let releases = changelog::since(app.state.version.as_deref());
+        if !releases.is_empty() {
+            app.overlays.push(Overlay::WhatsNew(releases, 0));
+        }
+    }
+    // over what's new, so it is seen first
+    let problems = app.keymap.diagnose(&app.config.keys);
+    if !problems.is_empty() {
+        app.overlays.push(Overlay::KeyProblems(problems));
+    }
+    Ok(app)
+}
+
+fn offline_status(pending: usize, retry: Duration) -> String {
+    match pending {
+        0 => format!("offline, retry in {}s", retry.as_secs()),
+        n => format!("offline, {} pending, retry in {}s", n, retry.as_secs()),
+    }
+}
+
+// the selected task's note in $VISUAL or $EDITOR, with the TUI suspended
+fn edit_note<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    let i = match app.selected_index() {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    let path = env::temp_dir().join(format!("todo-tui-note-{}.md", app.tasks[i].id));
+    fs::write(&path, app.tasks[i].note.as_deref().unwrap_or_default())?;
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_else(|_| "vi".to_string());
+    events::leave(app.config.enhanced_keys)?;
+    // the editor may take arguments, `code --wait`
+    let status = Command::new("sh").arg("-c").arg(format!("{} \"$1\"", editor)).arg("sh").arg(&path).status();
+    events::enter(app.config.enhanced_keys)?;
+    terminal.clear()?;
+    match status {
+        Ok(status) if status.success() => {
+            let note = fs::read_to_string(&path)?;
+            app.set_note(i, &note);
+        }
+        Ok(status) => app.status = Some(format!("{} exited with {}, note unchanged", editor, status)),
+        Err(err) => app.status = Some(format!("could not run {}: {}", editor, err)),
+    }
+    let _ = fs::remove_file(&path);
+    Ok(())
+}
+
+// one key, false when it quits the app
+fn handle_key<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> io::Result<bool> {
+    app.status = None;
+    app.hover = None;
+    // the newest dialog has the keys to itself
+    let now = Local::now().naive_local();
+    let dialog = !app.overlays.is_empty();
+    match app.overlays.last() {
+        Some(Overlay::Reminder(_)) => match code {
+            KeyCode::Enter | KeyCode::Esc => app.close_reminder(None),
+            KeyCode::Char('1') => app.close_reminder(Some(now + chrono::Duration::minutes(10))),
+            KeyCode::Char('2') => app.close_reminder(Some(now + chrono::Duration::hours(1))),
+            KeyCode::Char('3') => app.close_reminder((now.date() + chrono::Duration::days(1)).and_hms_opt(9, 0, 0)),
+            _ => {}
+        },
+        // Up and Down scroll the what's-new screen, any other key closes it;
+        // drawing it stops the scroll at the last line
+        Some(Overlay::WhatsNew(_, down)) => {
+            let down = match code {
+                KeyCode::Down => down.saturating_add(1),
+                KeyCode::Up => down.saturating_sub(1),
+                KeyCode::PageDown => down.saturating_add(10),
+                KeyCode::PageUp => down.saturating_sub(10),
+                _ => return app.dismiss_whats_new().map(|_| true),
+            };
+            if let Some(Overlay::WhatsNew(_, scrolled)) = app.overlays.last_mut() {
+                *scrolled = down;
+            }
+        }
+        Some(Overlay::GitLog(_)) => match code {
+            KeyCode::Down => app.move_git_log(true),
+            KeyCode::Up => app.move_git_log(false),
+            KeyCode::Char('r') => app.restore_git_version(),
+            KeyCode::Esc | KeyCode::Char('H') => drop(app.overlays.pop()),
+            _ => {}
+        },
+        Some(Overlay::Backups(_)) => match code {
+            KeyCode::Down => app.move_backup(true),
+            KeyCode::Up => app.move_backup(false),
+            KeyCode::Enter | KeyCode::Char('r') => app.restore_backup(),
+            KeyCode::Char('o') => app.browse(Browse::Restore, None),
+            KeyCode::Esc | KeyCode::Char('R') => drop(app.overlays.pop()),
+            _ => {}
+        },
+        Some(Overlay::Browse(..)) => {
+            let Some(Overlay::Browse(browser, _)) = app.overlays.last_mut() else { unreachable!() };
+            match browser.handle(code) {
+                Some(Browsed::Path(path)) => app.browsed(&path),
+                Some(Browsed::Cancelled) => drop(app.overlays.pop()),
+                None => {}
+            }
+        }
+        Some(Overlay::Escalated(_)) => match code {
+            KeyCode::Char('u') => {
+                app.overlays.pop();
+                app.undo();
+            }
+            KeyCode::Esc | KeyCode::Enter => drop(app.overlays.pop()),
+            _ => {}
+        },
+        Some(Overlay::ImportReport(_)) => {
+            if let KeyCode::Esc | KeyCode::Enter = code {
+                app.overlays.pop();
+            }
+        }
+        Some(Overlay::Activity(back)) => {
+            let back = *back;
+            let lines = app.activity.entries.len() as u16;
+            let back = match code {
+                KeyCode::Up => (back + 1).min(lines.saturating_sub(1)),
+                KeyCode::Down => back.saturating_sub(1),
+                KeyCode::PageUp => (back + 10).min(lines.saturating_sub(1)),
+                KeyCode::PageDown => back.saturating_sub(10),
+                KeyCode::Esc | KeyCode::Char('L') => {
+                    app.overlays.pop();
+                    return Ok(true);
+                }
+                _ => back,
+            };
+            app.overlays.pop();
+            app.overlays.push(Overlay::Activity(back));
+        }
+        Some(Overlay::UndoList(_, state)) => {
+            let row = state.selected().unwrap_or(0);
+            let last = app.history.len().saturating_sub(1);
+            let row = match code {
+                KeyCode::Down => (row + 1).min(last),
+                KeyCode::Up => row.saturating_sub(1),
+                KeyCode::Enter => {
+                    app.overlays.pop();
+                    (0..=row).for_each(|_| app.undo());
+                    // one step says what it was
+                    if row > 0 {
+                        app.status = Some(format!("Undid the last {} changes", row + 1));
+                    }
+                    return Ok(true);
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.overlays.pop();
+                    return Ok(true);
+                }
+                _ => row,
+            };
+            if let Some(Overlay::UndoList(_, state)) = app.overlays.last_mut() {
+                state.select(Some(row));
+            }
+        }
+        Some(Overlay::KeyProblems(_)) => {
+            if let KeyCode::Esc | KeyCode::Enter = code {
+                app.overlays.pop();
+            }
+        }
+        Some(Overlay::Standup) => match code {
+            KeyCode::Char('y') => app.copy(&report::standup(&app.tasks, now.date()), "the standup"),
+            KeyCode::Esc | KeyCode::Char('S') => drop(app.overlays.pop()),
+            _ => {}
+        },
+        Some(Overlay::Stats(stats)) => {
+            // Left and Right step through the projects, every todo first
+            let Stats { window, project } = stats.clone();
+            let projects = app.picker_projects();
+            let i = projects.iter().position(|p| *p == project).unwrap_or(0);
+            let stats = match code {
+                KeyCode::Char('w') => Some(Stats { window: report::Window::Week, project }),
+                KeyCode::Char('m') => Some(Stats { window: report::Window::Month, project }),
+                KeyCode::Left => Some(Stats { window, project: projects[(i + projects.len() - 1) % projects.len()].clone() }),
+                KeyCode::Right => Some(Stats { window, project: projects[(i + 1) % projects.len()].clone() }),
+                KeyCode::Esc | KeyCode::Char('B') => None,
+                _ => return Ok(true),
+            };
+            app.overlays.pop();
+            app.overlays.extend(stats.map(Overlay::Stats));
+        }
+        Some(Overlay::Popup) => popup_key(app, code, modifiers),
+        Some(Overlay::Picker(..)) => picker_key(app, code, modifiers),
+        Some(Overlay::Confirm(_)) => confirm_key(app, code)?,
+        Some(Overlay::Conflicts(_)) => match code {
+            KeyCode::Char('l') => app.resolve_conflict(false),
+            KeyCode::Char('r') => app.resolve_conflict(true),
+            KeyCode::Char('m') => app.merge_conflict(),
+            // leave the version the sync picked
+            KeyCode::Esc => drop(app.next_conflict()),
+            _ => {}
+        },
+        None => {}
+    }
+    if dialog {
+        return Ok(true);
+    }
+    if let Some(focus) = &mut app.focus_mode {
+        match code {
+            KeyCode::Char('t') => match focus.started.take() {
+                Some(started) => focus.elapsed += started.elapsed(),
+                None => focus.started = Some(Instant::now()),
+            },
+            KeyCode::Esc | KeyCode::Char('F') => app.focus_mode = None,
+            _ => {}
+        }
+        return Ok(true);
+    }
+    match app.input_mode {
+        InputMode::Normal if app.mark_prefix.is_some() => match (app.mark_prefix.take(), code) {
+            (Some(Action::SetMark), KeyCode::Char(c)) if c.is_ascii_alphabetic() => app.set_mark(c),
+            (Some(_), KeyCode::Char(c)) if c.is_ascii_alphabetic() => app.jump_to_mark(c),
+            // anything else lets it go
+            _ => {}
+        },
+        InputMode::Normal if app.type_ahead.as_ref().is_some_and(|(_, last)| last.elapsed() < TYPE_AHEAD_TIMEOUT) => match code {
+            KeyCode::Char(c) => {
+                if let Some((text, last)) = &mut app.type_ahead {
+                    text.push(c);
+                    *last = Instant::now();
+                }
+                app.type_ahead_to();
+            }
+            KeyCode::Backspace => {
+                if let Some((text, last)) = &mut app.type_ahead {
+                    text.pop();
+                    *last = Instant::now();
+                }
+                app.type_ahead_to();
+            }
+            // Enter, Esc or any other key ends it
+            _ => app.type_ahead = None,
+        },
+        InputMode::Normal => {
+            app.type_ahead = None;
+            let pending = std::mem::take(&mut app.pending);
+            let waiting = app.pending_action.take();
+            let lookup = match app.keymap.lookup(app.focus, &pending, code, modifiers) {
+                // the jump keys are digits like any other outside the due view,
+                // or once a count has started
+                Lookup::Action(Action::Jump(_)) if app.count.is_some() || !matches!(app.view(), View::Due) => Lookup::None,
+                lookup => lookup,
+            };
+            match lookup {
+                Lookup::Pending => {
+                    app.pending = pending;
+                    app.pending.push(keymap::normalize(code, modifiers));
+                    app.pending_at = Instant::now();
+                }
+                Lookup::Waiting(action) => {
+                    app.pending = pending;
+                    app.pending.push(keymap::normalize(code, modifiers));
+                    app.pending_at = Instant::now();
+                    app.pending_action = Some(action);
+                }
+                // a key that ends no sequence drops it, Esc included; one
+                // after keys that are a binding themselves does that first
+                Lookup::None if !pending.is_empty() => match waiting {
+                    Some(action) if code != KeyCode::Esc => {
+                        if !run_action(terminal, app, action)? {
+                            return Ok(false);
+                        }
+                        return handle_key(terminal, app, code, modifiers);
+                    }
+                    _ => {}
+                },
+                // a count is digits no action or plugin has taken, and doesn't start with 0
+                Lookup::None if matches!(code, KeyCode::Char('1'..='9')) || (matches!(code, KeyCode::Char('0')) && app.count.is_some()) => {
+                    if let KeyCode::Char(c) = code {
+                        if !app.plugins.bound(c) {
+                            let digit = c.to_digit(10).unwrap_or(0) as usize;
+                            app.count = Some((app.count.unwrap_or(0) * 10 + digit).min(999));
+                            return Ok(true);
+                        }
+                        app.run_plugin_key(c);
+                    }
+                }
+                Lookup::None => match code {
+                    KeyCode::Char(c) if app.plugins.bound(c) => app.run_plugin_key(c),
+                    _ => {}
+                },
+                Lookup::Action(action) => return run_action(terminal, app, action),
+            }
+        }
+        InputMode::Command => match code {
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+                app.run_command();
+            },
+            KeyCode::Esc => {
+                app.command_input.clear();
+                app.input_mode = InputMode::Normal;
+            },
+            _ if app.command_input.handle(code, modifiers) => {},
+            KeyCode::Char(c) => app.command_input.insert(c.encode_utf8(&mut [0; 4])),
+            _ => {}
+        },
+        InputMode::Filter => match code {
+            KeyCode::Enter => {
+                app.filter_input.remember();
+                app.input_mode = InputMode::Normal;
+            },
+            KeyCode::Esc => {
+                app.filter_input.clear();
+                app.set_filter();
+                app.input_mode = InputMode::Normal;
+            },
+            _ if app.filter_input.handle(code, modifiers) => app.set_filter(),
+            KeyCode::Char(c) => {
+                app.filter_input.insert(c.encode_utf8(&mut [0; 4]));
+                app.set_filter();
+            },
+            _ => {}
+        },
+    }
+    Ok(true)
+}
+
+// a key in the popup
+fn popup_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    match (code, modifiers) {
+        // only a terminal with `enhanced_keys` tells Shift+Enter from
+        // Enter: it adds the todo and keeps the popup open for the next
+        (KeyCode::Enter, KeyModifiers::SHIFT) if app.adding() && !app.popup_input.text().trim().is_empty() => {
+            app.popup_error = app.check_input();
+            if app.popup_error.is_none() {
+                app.push();
+                app.popup_input.clear();
+            }
+        },
+        (KeyCode::Enter, KeyModifiers::SHIFT) => {},
+        // an empty line ends batch add
+        (KeyCode::Enter, KeyModifiers::NONE) if !(app.batch && app.popup_input.text().trim().is_empty()) && app.check_input().is_some() => {
+            app.popup_error = app.check_input();
+        },
+        (KeyCode::Enter, KeyModifiers::NONE) if app.batch && !app.popup_input.text().trim().is_empty() => {
+            app.push();
+            app.popup_input.clear();
+        },
+        (KeyCode::Enter, KeyModifiers::NONE) if app.batch => {
+            app.close_popup();
+            app.batch = false;
+        },
+        (KeyCode::Enter, KeyModifiers::NONE) => {
+            app.close_popup();
+            app.push();
+            app.popup_input.clear();
+        },
+        _ if app.popup_input.handle(code, modifiers) => app.popup_error = None,
+        (KeyCode::Char(c), _) => app.insert(c.encode_utf8(&mut [0; 4])),
+        (KeyCode::Esc, KeyModifiers::NONE) => {
+            app.batch = false;
+            app.triage = None;
+            app.checking = None;
+            app.project_edit = None;
+            app.merging = None;
+            app.popup_input.clear();
+            app.popup_error = None;
+            app.close_popup();
+        },
+        _ => {}
+    }
+}
+
+// a key in the label, project or context picker
+fn picker_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let Some(Overlay::Picker(picker, pick)) = app.overlays.last_mut() else { return };
+    let pick = *pick;
+    // Enter with nothing left to pick adds what was typed as a new context
+    let typed = picker.input.text().to_string();
+    let none = picker.rows().next().is_none();
+    let picked = picker.handle(code, modifiers);
+    let selected = picker.selected().map(|item| item.label.to_string());
+    let picked = match picked {
+        None if code == KeyCode::Enter && none && matches!(pick, Pick::Context) => {
+            app.overlays.pop();
+            return app.toggle_context(&typed);
+        }
+        None => return,
+        Some(picked) => picked,
+    };
+    app.overlays.pop();
+    if let Picked::Item(i) = picked {
+        match pick {
+            Pick::Label => {
+                let label = i.checked_sub(1).map(|i| LABELS[i]);
+                app.set_label(label);
+                app.last_change = Some(Repeat::Label(label));
+            }
+            Pick::Project => {
+                let project = app.picker_projects().swap_remove(i);
+                app.last_change = Some(Repeat::Project(project.clone(), app.copy_to_project));
+                app.move_to_project(app.targets(), project);
+            }
+            Pick::Context => app.toggle_context(&selected.unwrap_or_default()),
+        }
+    }
+}
+
+// y or n to the question on top; the popup it was asked from, if any, is
+// under it and gets the keys again
+fn confirm_key(app: &mut App, code: KeyCode) -> io::Result<()> {
+    let yes = match code {
+        KeyCode::Char('y') | KeyCode::Enter => true,
+        KeyCode::Char('n') | KeyCode::Esc => false,
+        _ => return Ok(()),
+    };
+    let Some(Overlay::Confirm(confirm)) = app.overlays.pop() else { return Ok(()) };
+    match (confirm, yes) {
+        (Confirm::PostponeOverdue, true) => app.postpone_overdue(),
+        (Confirm::ArchiveProject(name), true) => app.archive_project(&name),
+        (Confirm::DeleteProject(name), true) => app.delete_project(&name),
+        (Confirm::Purge(doomed), true) => app.purge(&doomed),
+        (Confirm::Replay(ops), true) => {
+            app.snapshot();
+            app.keep_selection(|app| {
+                journal::replay(ops, &mut app.tasks);
+                app.refresh();
+            });
+        },
+        (Confirm::Replay(_), false) => journal::clear()?,
+        (Confirm::SplitPaste(lines), true) => {
+            app.popup_error = None;
+            app.push_lines(&lines);
+            app.popup_input.clear();
+            // open on a refused line, to say why
+            if !app.batch && app.popup_error.is_none() {
+                app.close_popup();
+            }
+        },
+        // keep a declined paste as a single line of text
+        (Confirm::SplitPaste(lines), false) => app.insert(&lines.join(" ")),
+        (_, false) => {},
+    }
+    Ok(())
+}
+
+// a key's action in the list screen, with the count typed before it; false
+// when it quits the app
+fn run_action<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, action: Action) -> io::Result<bool> {
+    match action {
+        // Esc drops a count before it quits
+        Action::Quit if app.count.is_some() => app.count = None,
+        Action::Quit => return Ok(false),
+        Action::EditNote => edit_note(terminal, app)?,
+        // a macro doesn't play itself
+        Action::PlayMacro if !app.replaying => return play_macro(terminal, app),
+        _ => {
+            let typed = app.count.take();
+            let count = typed.unwrap_or(1);
+            match action {
+                Action::ToggleDone => app.toggle_rows(count),
+                Action::Repeat if matches!(app.last_change, Some(Repeat::ToggleDone)) => app.toggle_rows(count),
+                Action::Repeat if typed.is_some() && matches!(app.last_change, Some(Repeat::Cut(_))) => app.cut(count),
+                Action::Cut => app.cut(count),
+                Action::Yank => app.yank(count),
+                // the ring entry to paste, 1 the newest
+                Action::Paste => app.paste_tasks(count),
+                // moving and undoing add up, other actions happen once
+                Action::Down | Action::Up | Action::PageDown | Action::PageUp | Action::Undo | Action::Repeat => (0..count).for_each(|_| app.perform(action)),
+                _ => app.perform(action),
+            }
+        }
+    }
+    Ok(true)
+}
+
+// the recorded keys, as if typed again; they go through the overlays and
+// prompts too, so a macro can fill in a popup
+fn play_macro<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
+    app.replaying = true;
+    let mut running = true;
+    for (code, modifiers) in app.macro_keys.clone() {
+        if !handle_key(terminal, app, code, modifiers)? {
+            running = false;
+            break;
+        }
+    }
+    app.replaying = false;
+    Ok(running)
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+    loop {
+        if !tick(terminal, &mut app)? {
+            return Ok(());
+        }
+        terminal.draw(|f| ui(f, &mut app))?;
+        // wake up now and then to pick up sync results, and in time for a
+        // waiting key; seldom in the background, where reminders can wait
+        // a few seconds
+        let every = Duration::from_millis(if app.focused { 250 } else { 5000 });
+        let wait = match app.pending_action {
+            Some(_) => Duration::from_millis(app.config.key_timeout).saturating_sub(app.pending_at.elapsed()),
+            None => every,
+        };
+        if !event::poll(wait.min(every))? {
+            continue;
+        }
+        // everything already waiting before the next frame, so text pasted
+        // as keys, where the terminal has no bracketed paste, draws once
+        loop {
+            if !handle_event(terminal, &mut app, event::read()?)? {
+                return Ok(());
+            }
+            if !event::poll(Duration::ZERO)? {
+                break;
+            }
+        }
+    }
+}
+
+// what goes on between events: sync results, the daily jobs, reminders and
+// a waiting key whose time is up. false to quit
+fn tick<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
+    app.poll_sync()?;
+    app.check_overdue()?;
+    app.archive_done()?;
+    app.escalate()?;
+    app.check_reminders()?;
+    // ages, overdue and stale todo move on at midnight
+    if app.rows_on != Local::now().date_naive() {
+        app.refresh_rows();
+    }
+    // keys that are a binding and the start of a longer one do theirs
+    // once nothing more comes
+    let timeout = Duration::from_millis(app.config.key_timeout);
+    if let Some(action) = app.pending_action.filter(|_| app.pending_at.elapsed() >= timeout) {
+        app.pending.clear();
+        app.pending_action = None;
+        if !run_action(terminal, app, action)? {
+            return Ok(false);
+        }
+        app.save()?;
+    }
+    Ok(true)
+}
+
+// one terminal event, saved once handled; false to quit
+fn handle_event<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, event: Event) -> io::Result<bool> {
+    match event {
+        Event::Key(key) => {
+            let Some((code, modifiers)) = events::key(key) else {
+                return Ok(true);
+            };
+            if let Some(keys) = &mut app.recording {
+                keys.push((code, modifiers));
+            }
+            if !handle_key(terminal, app, code, modifiers)? {
+                return Ok(false);
+            }
+        },
+        // a paste is one event, however long, and is text wherever there is
+        // text to type; on one line outside the add popup
+        Event::Paste(text) => match (app.overlays.last_mut(), &app.input_mode) {
+            (Some(Overlay::Popup), _) => app.paste(&text),
+            (Some(Overlay::Picker(picker, _)), _) => picker.paste(&text),
+            (Some(_), _) | (None, InputMode::Normal) => {},
+            (None, InputMode::Filter) => {
+                app.filter_input.insert(&text.replace(['\r', '\n'], " "));
+                app.set_filter();
+            },
+            (None, InputMode::Command) => app.command_input.insert(&text.replace(['\r', '\n'], " ")),
+        },
+        // saved below on the way out, so todo.txt is up to date for whatever
+        // else opens it
+        Event::FocusLost => app.focused = false,
+        Event::FocusGained => app.focused = true,
+        Event::Mouse(mouse) => {
+            let overlay = !app.overlays.is_empty() || app.focus_mode.is_some();
+            if !overlay && matches!(app.input_mode, InputMode::Normal) {
+                app.mouse(mouse);
+            }
+        },
+        _ => {}
+    }
+    app.save()?;
+    Ok(true)
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    // focus mode draws its todo and nothing else, until the todo goes away
+    if let Some(focus) = &app.focus_mode {
+        match app.tasks.iter().find(|t| t.id == focus.id) {
+            Some(task) => {
+                focus_ui(f, task, focus);
+                let today = Local::now().date_naive();
+                for overlay in &mut app.overlays {
+                    overlay_ui(f, overlay, &app.tasks, &app.config, &app.activity, today);
+                }
+                return;
+            }
+            None => app.focus_mode = None,
+        }
+    }
+    // window setting
+    let layout = &app.config.layout;
+    let show_help = layout.help || app.status.is_some() || !matches!(app.input_mode, InputMode::Normal) || app.prompting();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(show_help as u16),
+            Constraint::Percentage(90),
+        ].as_ref(),)
+        .split(f.area());
+    let main = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(2)
+        .constraints([
+            Constraint::Percentage(layout.sidebar),
+            Constraint::Percentage(100 - layout.sidebar - layout.detail),
+            Constraint::Percentage(layout.detail),
+        ].as_ref(),)
+        .split(chunks[1]);
+    // a zoomed pane takes the room of all three, the others get none
+    let main: Vec<Rect> = match app.zoomed {
+        false => main.to_vec(),
+        true => {
+            let full = chunks[1].inner(Margin { vertical: 2, horizontal: 2 });
+            let zoomed = match app.focus {
+                Focus::Sidebar => 0,
+                Focus::List => 1,
+                Focus::Detail => 2,
+            };
+            (0..3).map(|i| if i == zoomed { full } else { Rect::default() }).collect()
+        }
+    };
+
+    // help message
+    let sync_status: Vec<String> = app.sync_status.iter().map(|(name, text)| format!("{}: {}", name, text)).collect();
+    let sync_status = if sync_status.is_empty() { String::new() } else { format!("  {}", sync_status.join(" | ")) };
+    // counting down to the next deadline, yellow on its last day
+    let countdown = match app.next_deadline(Local::now().naive_local()) {
+        Some((i, left)) => {
+            let title: String = app.tasks[i].title.chars().take(30).collect();
+            let clock = format!("{}d {:02}h {:02}m", left.num_days(), left.num_hours() % 24, left.num_minutes() % 60);
+            let style = if left.num_days() == 0 { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::DarkGray) };
+            Span::styled(format!("  {} left: {}", clock, title), style)
+        }
+        None => Span::raw(""),
+    };
+    // the filter and the command line get what the header leaves
+    let room = (chunks[0].width as usize).saturating_sub(countdown.width() + sync_status.chars().count() + "Filter: ".len());
+    let (filter, filter_column) = app.filter_input.spans(room, &[]);
+    let (command, command_column) = app.command_input.spans(room, &[]);
+    let (msg, style) = match (app.overlays.last(), &app.input_mode) {
+        (Some(Overlay::Popup), _) => (
+            vec![
+                Span::raw("Press "),
+                Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to stop edit, "),
+                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(if app.batch { " to add and keep typing, Enter on an empty line to finish. " } else { " to add todo list. " }),
+            ],
+            Style::default(),
+        ),
+        (Some(Overlay::Picker(_, Pick::Label)), _) => (
+            vec![
+                Span::raw("Press "),
+                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to set the label, "),
+                Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to cancel."),
+            ],
+            Style::default(),
+        ),
+        (Some(Overlay::Picker(_, Pick::Project)), _) => (
+            vec![
+                Span::raw("Press "),
+                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(if app.copy_to_project { " to copy there, " } else { " to move there, " }),
+                Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to cancel."),
+            ],
+            Style::default(),
+        ),
+        (Some(Overlay::Picker(_, Pick::Context)), _) => (
+            vec![
+                Span::raw("Press "),
+                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to add the context or take it off, or a new one typed, "),
+                Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to cancel."),
+            ],
+            Style::default(),
+        ),
+        (Some(Overlay::Confirm(_)), _) => (
+            vec![
+                Span::styled("y", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to confirm, "),
+                Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to cancel."),
+            ],
+            Style::default(),
+        ),
+        (_, InputMode::Filter) => (
+            vec![
+                Span::styled("Filter: ", Style::default().add_modifier(Modifier::BOLD)),
+            ].into_iter().chain(filter.spans).collect(),
+            Style::default(),
+        ),
+        (_, InputMode::Command) => (
+            [Span::styled(":", Style::default().add_modifier(Modifier::BOLD))].into_iter().chain(command.spans).collect(),
+            Style::default(),
+        ),
+        (_, InputMode::Normal) => (
+            vec![
+                Span::raw("Press "),
+                Span::styled(format!("{} key", app.keymap.key(Action::Quit)), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to exit, "),
+                Span::styled(app.keymap.key(Action::Add), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to input popup, "),
+                Span::styled(app.keymap.key(Action::PostponeOverdue), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to postpone overdue, "),
+                Span::styled(app.keymap.key(Action::Undo), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to undo."),
+            ],
+            Style::default().add_modifier(Modifier::RAPID_BLINK),
+        ),
+    };
+    let (msg, style) = match &app.status {
+        Some(status) => (vec![Span::raw(status.to_string())], Style::default().add_modifier(Modifier::BOLD)),
+        None => (msg, style),
+    };
+    let text = Text::from(Line::from(msg)).patch_style(style);
+    let header = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(countdown.width() as u16), Constraint::Length(sync_status.chars().count() as u16)].as_ref())
+        .split(chunks[0]);
+    let help_message = Paragraph::new(text);
+    f.render_widget(help_message, header[0]);
+    f.render_widget(Paragraph::new(countdown), header[1]);
+    f.render_widget(Paragraph::new(sync_status).style(Style::default().fg(Color::DarkGray)), header[2]);
+    if let InputMode::Filter = app.input_mode {
+        f.set_cursor_position((header[0].x + "Filter: ".len() as u16 + filter_column, header[0].y));
+    }
+    if let InputMode::Command = app.input_mode {
+        f.set_cursor_position((header[0].x + 1 + command_column, header[0].y));
+    }
+
+    // sidebar ui
+    let inbox_count = app.inbox_count();
+    let views: Vec<ListItem> = app
+        .views
+        .items
+        .iter()
+        .map(|v| match v {
+            View::Inbox if inbox_count > 0 => ListItem::new(Line::from(vec![
+                Span::raw("Inbox "),
+                Span::styled(format!("[{}]", inbox_count), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            ])),
+            View::Context(_) => ListItem::new(Span::styled(v.name(), Style::default().fg(Color::Cyan))),
+            View::Project(name) if app.state.archived.contains(name) => {
+                ListItem::new(Span::styled(format!("{} (archived)", name), Style::default().fg(Color::DarkGray)))
+            },
+            _ => ListItem::new(v.name()),
+        })
+        .collect();
+    let highlight = match app.focus {
+        Focus::Sidebar => Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD),
+        Focus::List | Focus::Detail => Style::default().add_modifier(Modifier::BOLD),
+    };
+    let views = List::new(views)
+        .block(Block::default().borders(Borders::ALL).title("Projects"))
+        .highlight_style(highlight)
+        .highlight_symbol("> ");
+    app.views.layout(main[0], vec![1; app.views.items.len()]);
+    f.render_stateful_widget(views, main[0], &mut app.views.state);
+
+    // todo list ui, next to the other pane when split
+    let today = Local::now().date_naive();
+    let hovered = if app.split.is_some() {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(main[1]);
+        let (this, other) = if app.pane_left { (halves[0], halves[1]) } else { (halves[1], halves[0]) };
+        let hovered = render_list(f, app, this, true);
+        app.swap_pane();
+        let across = render_list(f, app, other, false);
+        app.swap_pane();
+        hovered.or(across)
+    } else {
+        render_list(f, app, main[1], true)
+    };
+
+    // detail ui
+    let detail = match app.selected() {
+        Some(task) => {
+            let mut lines = vec![
+                Line::from(Span::styled(task.title.to_string(), Style::default().add_modifier(Modifier::BOLD))),
+                Line::from(""),
+            ];
+            if let Some(project) = &task.project {
+                lines.push(Line::from(format!("project: {}", project)));
+            }
+            if !task.contexts.is_empty() {
+                let contexts: Vec<String> = task.contexts.iter().map(|c| format!("@{}", c)).collect();
+                lines.push(Line::from(format!("contexts: {}", contexts.join(" "))));
+            }
+            if let Some(p) = task.priority {
+                lines.push(Line::from(format!("priority: {}", p)));
+            }
+            if let Some(due) = task.due {
+                lines.push(Line::from(format!("due: {}", due)));
+            }
+            if let Some(minutes) = task.estimate {
+                lines.push(Line::from(format!("estimate: {}", task::format_minutes(minutes))));
+            }
+            if let Some(place) = &task.location {
+                lines.push(Line::from(format!("location: {}", place)));
+            }
+            if let Some(time) = task.remind {
+                lines.push(Line::from(format!("remind: {}", time.format("%Y-%m-%d %H:%M"))));
+            }
+            if !task.is_done() {
+                lines.push(Line::from(format!("urgency: {:.1}", urgency::score(task, today, &app.config.urgency))));
+            }
+            for (key, value) in &task.fields {
+                lines.push(Line::from(format!("{}: {}", key, value)));
+            }
+            lines.push(Line::from(format!("created: {} ({})", task.created, task.age(today))));
+            if let Some(done) = task.completed {
+                lines.push(Line::from(format!("done: {}", done.format("%Y-%m-%d %H:%M"))));
+            }
+            let focused = matches!(app.focus, Focus::Detail);
+            if !task.checklist.is_empty() || focused {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    format!("checklist {}", task.progress().unwrap_or_default()),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+            }
+            let row = app.check_index();
+            for (n, (done, item)) in task.checklist.iter().enumerate() {
+                let style = match (focused && row == Some(n), done) {
+                    (true, _) => Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD),
+                    (false, true) => Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT),
+                    (false, false) => Style::default(),
+                };
+                lines.push(Line::from(Span::styled(format!("[{}] {}", if *done { 'x' } else { ' ' }, item), style)));
+            }
+            if focused {
+                lines.push(Line::from(Span::styled(
+                    "a add, Space tick, Del remove, Tab leave",
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if let Some(note) = &task.note {
+                lines.push(Line::from(""));
+                lines.extend(markdown::to_spans(note, &app.config.code_theme));
+            }
+            Paragraph::new(lines)
+        }
+        None => Paragraph::new("No todo selected").style(Style::default().fg(Color::DarkGray)),
+    };
+    let detail = detail
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Detail"));
+    f.render_widget(detail, main[2]);
+
+    let size = f.area();
+
+    // hover tooltip ui, below the mouse or above it near the bottom
+    if let (Some(i), Some((column, row))) = (hovered, app.hover) {
+        let task = &app.tasks[i];
+        let mut lines = vec![Line::from(Span::styled(task.title.to_string(), Style::default().add_modifier(Modifier::BOLD)))];
+        if let Some(due) = task.due {
+            lines.push(Line::from(format!("due {}", due)));
+        }
+        let width = (task.title.chars().count() as u16 + 4).clamp(12, 60).min(size.width).max(3);
+        let height = (task.title.chars().count() as u16).div_ceil(width - 2) + lines.len() as u16 + 1;
+        let y = if row + 1 + height <= size.height { row + 1 } else { row.saturating_sub(height) };
+        let area = Rect { x: column.min(size.width.saturating_sub(width)), y, width, height: height.min(size.height) };
+        let tooltip = Paragraph::new(lines).wrap(Wrap { trim: true }).block(Block::default().borders(Borders::ALL));
+        f.render_widget(Clear, area);
+        f.render_widget(tooltip, area);
+    }
+
+    // the keys that can follow a prefix, in the bottom right corner until it
+    // has waited long enough to not be needed
+    let waiting = app.pending_at.elapsed() < Duration::from_secs(app.config.which_key);
+    if !app.pending.is_empty() && waiting && matches!(app.input_mode, InputMode::Normal) && app.overlays.is_empty() {
+        let next = app.keymap.next_keys(app.focus, &app.pending);
+        let typed: Vec<String> = app.pending.iter().map(|&(c, m)| keymap::format_key(c, m)).collect();
+        let key_width = next.iter().map(|(key, _)| key.width()).max().unwrap_or(0);
+        let lines: Vec<Line> = next
+            .iter()
+            .map(|(key, does)| {
+                Line::from(vec![
+                    Span::styled(format!("{:w$}  ", key, w = key_width), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                    Span::raw(does.as_str()),
+                ])
+            })
+            .collect();
+        let width = next.iter().map(|(_, does)| key_width + 2 + does.width()).max().unwrap_or(0) as u16 + 2;
+        let height = lines.len() as u16 + 2;
+        let (width, height) = (width.max(20).min(size.width), height.min(size.height.saturating_sub(1)));
+        // left out on a terminal too small for its border
+        if width >= 3 && height >= 3 {
+            let area = Rect { x: size.width - width, y: size.height.saturating_sub(height + 1), width, height };
+            let hint = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!("{} …", typed.join(" "))));
+            f.render_widget(Clear, area);
+            f.render_widget(hint, area);
+        }
+    }
+
+    // dialogs, the newest drawn last
+    for i in 0..app.overlays.len() {
+        match &app.overlays[i] {
+            Overlay::Popup => popup_ui(f, app, i + 1 == app.overlays.len()),
+            _ => overlay_ui(f, &mut app.overlays[i], &app.tasks, &app.config, &app.activity, today),
+        }
+    }
+}
+
+// the add and edit popup; todo text shows what will be read out of it in
+// color, and a summary under it; a new todo is read as quick entry
+fn popup_ui(f: &mut Frame, app: &App, top: bool) {
+    let size = f.area();
+    let todo = app.editing_todo();
+    let text = app.popup_input.text();
+    let tokens = if todo { quick::tokens(text, Local::now().date_naive(), app.triage.is_none()) } else { vec![] };
+    let summary = (!tokens.is_empty()).then(|| quick::summary(text, &tokens));
+    let length = text.chars().count();
+    // how much of the limit the todo text uses, red over it after a paste
+    let (counter, counter_style) = match app.config.max_length {
+        max if todo && max > 0 && length > max => (format!(" {}/{}", length, max), Style::default().fg(Color::Red)),
+        max if todo && max > 0 => (format!(" {}/{}", length, max), Style::default().fg(Color::DarkGray)),
+        _ => (String::new(), Style::default()),
+    };
+    let title = match &app.merging {
+        Some(theirs) => format!("Merge TODO - {}", theirs),
+        None if app.triage.is_some() => "Triage TODO".to_string(),
+        None if app.checking.is_some() => "Add checklist item".to_string(),
+        None => match &app.project_edit {
+            Some(ProjectEdit::New) => "New project".to_string(),
+            Some(ProjectEdit::Rename(name)) => format!("Rename project {}", name),
+            None if app.batch => "Add TODO (batch)".to_string(),
+            None => "Add TODO".to_string(),
+        },
+    };
+    // as wide as the longest line, within bounds, as tall as its lines
+    let content = [
+        text.width() + 1,
+        summary.as_deref().map_or(0, str::width),
+        app.popup_error.as_deref().map_or(0, str::width),
+        title.width() + counter.width(),
+    ];
+    let width = (content.into_iter().max().unwrap_or(0) as u16 + 2).clamp(POPUP_MIN_WIDTH, POPUP_MAX_WIDTH);
+    let rows = 1 + u16::from(summary.is_some()) + u16::from(app.popup_error.is_some());
+    let area = centered_size(width, rows + 2, size);
+    let styles: Vec<_> = tokens
+        .iter()
+        .map(|t| (t.start, t.end, Style::default().fg(token_color(t.kind)).add_modifier(Modifier::BOLD)))
+        .collect();
+    let (input, column) = app.popup_input.spans(area.width.saturating_sub(2) as usize, &styles);
+    let mut items = vec![ListItem::new(input)];
+    if let Some(summary) = summary {
+        items.push(ListItem::new(Span::styled(summary, Style::default().fg(Color::DarkGray))));
+    }
+    if let Some(error) = &app.popup_error {
+        items.push(ListItem::new(Span::styled(error.to_string(), Style::default().fg(Color::Red))));
+    }
+    let items = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::raw(title), Span::styled(counter, counter_style)])))
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+    // under a question or a picker, the cursor is theirs
+    if top {
+        f.set_cursor_position((area.x + column + 1, area.y + 1));
+    }
+    f.render_widget(Clear, area);
+    f.render_widget(items, area);
+}
+
+// one dialog of the stack
+fn overlay_ui(f: &mut Frame, overlay: &mut Overlay, tasks: &[Task], config: &Config, activity: &audit::Log, today: NaiveDate) {
+    let size = f.area();
+    match overlay {
+        Overlay::Picker(picker, _) => picker_ui(f, picker, size),
+        Overlay::Confirm(confirm) => {
+            let (msg, height) = match confirm {
+                Confirm::PostponeOverdue => (
+                    vec![Line::from(format!("Postpone {} overdue todo to today? (y/n)", tasks.iter().filter(|t| t.is_overdue(today)).count()))],
+                    10,
+                ),
+                Confirm::SplitPaste(lines) => {
+                    let mut msg = vec![Line::from(format!("Add {} todo, one per pasted line? (y/n)", lines.len())), Line::from("")];
+                    msg.extend(lines.iter().map(|line| Line::from(format!("  - {}", line))));
+                    (msg, 50)
+                },
+                Confirm::ArchiveProject(name) => (
+                    vec![
+                        Line::from(format!("Archive project {}? (y/n)", name)),
+                        Line::from("Its todo leave the other views until A unarchives it."),
+                    ],
+                    10,
+                ),
+                Confirm::Replay(ops) => (
+                    vec![
+                        Line::from(format!("The last run stopped while saving {} changes. Replay them? (y/n)", ops.len())),
+                        Line::from("n drops them and keeps todo.txt as it is."),
+                    ],
+                    10,
+                ),
+                Confirm::Purge(doomed) => {
+                    let mut msg = vec![Line::from(format!("Delete {} archived todo for good? (y/n)", doomed.len())), Line::from("")];
+                    let done = |t: &Task| t.completed.map(|d| d.date().to_string()).unwrap_or_default();
+                    msg.extend(doomed.iter().map(|t| Line::from(format!("  - {} {}", done(t), t.text()))));
+                    (msg, 50)
+                },
+                Confirm::DeleteProject(name) => {
+                    let count = tasks.iter().filter(|t| t.project.as_deref() == Some(name.as_str())).count();
+                    (vec![Line::from(format!("Delete project {} and its {} todo? u undoes it. (y/n)", name, count))], 10)
+                },
+            };
+            let confirm = Paragraph::new(msg)
+                .block(Block::default().borders(Borders::ALL).title("Confirm"));
+            let area = centered_rect(60, height, size);
+            f.render_widget(Clear, area);
+            f.render_widget(confirm, area);
+        }
+        Overlay::Conflicts(conflicts) => {
+            let Some((name, conflict)) = conflicts.first() else { return };
+            let area = centered_rect(80, 40, size);
+            f.render_widget(Clear, area);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(area);
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(rows[0]);
+            let side = |title: String, task: &Task| {
+                let lines = vec![
+                    Line::from(task.text()),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        format!("edited {}", task.touched.format("%Y-%m-%d %H:%M")),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                    Line::from(if task.is_done() { "done" } else { "open" }),
+                ];
+                Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::default().borders(Borders::ALL).title(title))
+            };
+            let left = format!("Here{}", if conflicts.len() > 1 { format!(" (1 of {})", conflicts.len()) } else { String::new() });
+            f.render_widget(side(left, &conflict.local), panes[0]);
+            f.render_widget(side(format!("On {}", name), &conflict.remote), panes[1]);
+            let help = Paragraph::new("l keep this one, r keep theirs, m merge by hand, Esc leave the newer one")
+                .style(Style::default().add_modifier(Modifier::BOLD));
+            f.render_widget(help, rows[1]);
+        }
+        // drawn by `popup_ui`
+        Overlay::Popup => {}
+        Overlay::Standup => {
+            let text = report::standup(tasks, today);
+            let mut lines: Vec<Line> = text
+                .lines()
+                .map(|line| match line.strip_prefix("## ") {
+                    Some(heading) => Line::from(Span::styled(heading.to_string(), Style::default().add_modifier(Modifier::BOLD))),
+                    None => Line::from(line.to_string()),
+                })
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from("Press y to copy, Esc to close."));
+            let standup = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Standup"));
+            let area = centered_rect(60, 60, size);
+            f.render_widget(Clear, area);
+            f.render_widget(standup, area);
+        }
+        Overlay::Stats(stats) => {
+            let tasks: Vec<&Task> = tasks.iter().filter(|t| stats.project.is_none() || t.project == stats.project).collect();
+            let days = report::burndown(&tasks, today, stats.window);
+            let series = |count: fn(&report::Day) -> usize| -> Vec<(f64, f64)> {
+                days.iter().enumerate().map(|(x, day)| (x as f64, count(day) as f64)).collect()
+            };
+            let (open, created, completed) = (series(|d| d.open), series(|d| d.created), series(|d| d.completed));
+            let line = |name, color, data| {
+                Dataset::default().name(name).marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(color)).data(data)
+            };
+            let (first, last) = (&days[0], &days[days.len() - 1]);
+            let top = days.iter().map(|d| d.open.max(d.created).max(d.completed)).max().unwrap_or(0).max(1);
+            let chart = Chart::new(vec![line("open", Color::Yellow, &open), line("created", Color::Red, &created), line("completed", Color::Green, &completed)])
+                .x_axis(
+                    Axis::default()
+                        .bounds([0.0, (days.len() - 1) as f64])
+                        .labels(vec![Span::raw(first.date.format("%b %d").to_string()), Span::raw(last.date.format("%b %d").to_string())]),
+                )
+                .y_axis(Axis::default().bounds([0.0, top as f64]).labels(vec![Span::raw("0"), Span::raw(top.to_string())]))
+                .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 2)));
+            // open at the start of the window, before its first day's changes
+            let before = (first.open + first.completed).saturating_sub(first.created);
+            let trend = match last.open.cmp(&before) {
+                Ordering::Less => "shrinking",
+                Ordering::Equal => "holding",
+                Ordering::Greater => "growing",
+            };
+            let summary = vec![
+                Line::from(format!(
+                    "{} open, {} a {} ago: {} created, {} completed, the backlog is {}.",
+                    last.open,
+                    before,
+                    stats.window.name(),
+                    last.created,
+                    last.completed,
+                    trend
+                )),
+                Line::from("Press w for a week, m for a month, Left and Right for another project, Esc to close."),
+            ];
+            let project = stats.project.as_deref().unwrap_or("all todo");
+            let block = Block::default().borders(Borders::ALL).title(format!("Burndown: {} (last {})", project, stats.window.name()));
+            let area = centered_rect(80, 70, size);
+            f.render_widget(Clear, area);
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(5), Constraint::Length(3)].as_ref())
+                .split(inner);
+            f.render_widget(chart, parts[0]);
+            f.render_widget(Paragraph::new(summary).wrap(Wrap { trim: true }), parts[1]);
+        }
+        Overlay::GitLog(log) => {
+            let area = centered_rect(80, 80, size);
+            f.render_widget(Clear, area);
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+                .split(area);
+            let commits: Vec<ListItem> = log
+                .commits
+                .iter()
+                .map(|c| ListItem::new(format!("{} {} {}", c.hash, c.date, c.subject)))
+                .collect();
+            let commits = List::new(commits)
+                .block(Block::default().borders(Borders::ALL).title("History (r to restore, Esc to close)"))
+                .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD));
+            f.render_stateful_widget(commits, panes[0], &mut log.state);
+            let diff: Vec<Line> = log
+                .diff
+                .lines()
+                .map(|line| {
+                    let style = match line.chars().next() {
+                        Some('+') if !line.starts_with("+++") => Style::default().fg(Color::Green),
+                        Some('-') if !line.starts_with("---") => Style::default().fg(Color::Red),
+                        _ => Style::default(),
+                    };
+                    Line::from(Span::styled(line.to_string(), style))
+                })
+                .collect();
+            let diff = Paragraph::new(diff).block(Block::default().borders(Borders::ALL).title("Diff"));
+            f.render_widget(diff, panes[1]);
+        }
+        Overlay::Backups(picker) => {
+            let area = centered_rect(50, 60, size);
+            f.render_widget(Clear, area);
+            let rows: Vec<ListItem> = picker
+                .backups
+                .iter()
+                .map(|(b, count)| ListItem::new(format!("{}  {} todo", b.time.format("%Y-%m-%d %H:%M"), count)))
+                .collect();
+            let rows = List::new(rows)
+                .block(Block::default().borders(Borders::ALL).title("Backups (Enter to restore, o for another file, Esc to close)"))
+                .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD));
+            f.render_stateful_widget(rows, area, &mut picker.state);
+        }
+        Overlay::Browse(browser, purpose) => {
+            let area = centered_rect(60, 70, size);
+            f.render_widget(Clear, area);
+            let save_as = browser.save_as.as_deref().unwrap_or_default();
+            let rows: Vec<ListItem> = browser
+                .rows
+                .iter()
+                .map(|row| match row {
+                    browse::Row::SaveHere => ListItem::new(Span::styled(format!("[save here as {}]", save_as), Style::default().fg(Color::Green))),
+                    browse::Row::Parent => ListItem::new(Span::styled("../", Style::default().fg(Color::Blue))),
+                    browse::Row::Dir(name) => ListItem::new(Span::styled(format!("{}/", name), Style::default().fg(Color::Blue))),
+                    browse::Row::File(name) => ListItem::new(name.as_str()),
+                })
+                .collect();
+            let verb = match purpose {
+                Browse::Import(_) => "Import",
+                Browse::Export(_) => "Export to",
+                Browse::Restore => "Restore from",
+            };
+            let hint = match &browser.error {
+                Some(error) => Span::styled(error.to_string(), Style::default().fg(Color::Red)),
+                None => Span::styled("Enter picks, Left goes up, ~ home, . dotfiles, Esc cancels", Style::default().fg(Color::DarkGray)),
+            };
+            let block = Block::default().borders(Borders::ALL).title(format!("{} {}", verb, browser.dir.display()));
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .split(inner);
+            let rows = List::new(rows)
+                .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD))
+                .highlight_symbol(">> ");
+            f.render_stateful_widget(rows, chunks[0], &mut browser.state);
+            f.render_widget(Paragraph::new(hint), chunks[1]);
+        }
+        Overlay::ImportReport(report) => {
+            let mut lines = vec![Line::from(Span::styled(report.summary(), Style::default().add_modifier(Modifier::BOLD)))];
+            for (heading, titles) in [("Added", &report.added), ("Updated", &report.updated), ("Skipped", &report.skipped)] {
+                if titles.is_empty() {
+                    continue;
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(heading, Style::default().add_modifier(Modifier::BOLD))));
+                for title in titles {
+                    lines.push(Line::from(format!("  - {}", title)));
+                }
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from("Press Enter or Esc to close."));
+            let report = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Import"));
+            let area = centered_rect(60, 60, size);
+            f.render_widget(Clear, area);
+            f.render_widget(report, area);
+        }
+        Overlay::Escalated(changes) => {
+            let title = format!("{} todo untouched for {} days went up a priority", changes.len(), config.escalate.after);
+            let mut lines = vec![Line::from(Span::styled(title, Style::default().add_modifier(Modifier::BOLD))), Line::from("")];
+            lines.extend(changes.iter().map(|line| Line::from(format!("  - {}", line))));
+            lines.push(Line::from(""));
+            lines.push(Line::from("Press u to undo them all, Enter or Esc to keep them."));
+            let summary = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Escalated"));
+            let area = centered_rect(60, 60, size);
+            f.render_widget(Clear, area);
+            f.render_widget(summary, area);
+        }
+        Overlay::Activity(back) => {
+            let area = centered_rect(70, 70, size);
+            // newest at the bottom, Up going back from it
+            let rows = area.height.saturating_sub(2) as usize;
+            let end = activity.entries.len() - (*back as usize).min(activity.entries.len());
+            let mut lines: Vec<Line> = activity.entries[end.saturating_sub(rows)..end]
+                .iter()
+                .map(|(time, what)| {
+                    let when = if time.date() == today { time.format("%H:%M").to_string() } else { time.format("%Y-%m-%d %H:%M").to_string() };
+                    Line::from(vec![Span::styled(format!("{} ", when), Style::default().fg(Color::DarkGray)), Span::raw(what.as_str())])
+                })
+                .collect();
+            if lines.is_empty() {
+                lines.push(Line::from("Nothing done yet."));
+            }
+            let log = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Activity (Up and Down scroll, Esc to close)"));
+            f.render_widget(Clear, area);
+            f.render_widget(log, area);
+        }
+        Overlay::UndoList(steps, state) => {
+            let area = centered_rect(60, 60, size);
+            f.render_widget(Clear, area);
+            let rows: Vec<ListItem> = steps.iter().enumerate().map(|(i, step)| ListItem::new(format!("{:>3}  {}", i + 1, step))).collect();
+            let rows = List::new(rows)
+                .block(Block::default().borders(Borders::ALL).title("Undo (Enter undoes it and all after it, Esc to close)"))
+                .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD));
+            f.render_stateful_widget(rows, area, state);
+        }
+        Overlay::KeyProblems(problems) => {
+            let path = paths::config_file();
+            let mut lines: Vec<Line> = problems
+                .iter()
+                .map(|(line, problem)| Line::from(vec![Span::styled(format!("line {}: ", line), Style::default().fg(Color::Red)), Span::raw(problem.as_str())]))
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("Fix them under [keys] in {}, then restart.", path.display())));
+            lines.push(Line::from("Press Enter or Esc to go on with the keys as they are."));
+            let report = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Key bindings").border_style(Style::default().fg(Color::Red)));
+            let area = centered_rect(70, 60, size);
+            f.render_widget(Clear, area);
+            f.render_widget(report, area);
+        }
+        Overlay::WhatsNew(releases, down) => {
+            let mut lines = vec![];
+            for release in releases {
+                lines.push(Line::from(Span::styled(
+                    release.version,
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                for note in release.notes {
+                    lines.push(Line::from(format!("  - {}", note)));
+                }
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from("Press any key to close."));
+            let area = centered_rect(70, 70, size);
+            let whats_new = Paragraph::new(lines).wrap(Wrap { trim: false });
+            let last = whats_new.line_count(area.width.saturating_sub(2)).saturating_sub(area.height.saturating_sub(2) as usize);
+            *down = (*down).min(last as u16);
+            let whats_new = whats_new
+                .scroll((*down, 0))
+                .block(Block::default().borders(Borders::ALL).title("What's new (Up and Down scroll)"));
+            f.render_widget(Clear, area);
+            f.render_widget(whats_new, area);
+        }
+        Overlay::Reminder(id) => {
+            if let Some(task) = tasks.iter().find(|t| t.id == *id) {
+                reminder_ui(f, task);
+            }
+        }
+    }
+}
+
+fn reminder_ui(f: &mut Frame, task: &Task) {
+    let mut lines = vec![Line::from(Span::styled(task.title.to_string(), Style::default().add_modifier(Modifier::BOLD)))];
+    if let Some(time) = task.remind {
+        lines.push(Line::from(format!("reminder for {}", time.format("%a %Y-%m-%d %H:%M"))));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Snooze with 1 for 10 minutes, 2 for an hour, 3 until 9:00 tomorrow."));
+    lines.push(Line::from("Press Enter or Esc to dismiss."));
+    let reminder = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Reminder").border_style(Style::default().fg(Color::Yellow)));
+    let area = centered_rect(50, 100, f.area());
+    let area = Rect { y: area.y + area.height.saturating_sub(8) / 2, height: area.height.min(8), ..area };
+    f.render_widget(Clear, area);
+    f.render_widget(reminder, area);
+}
+
+fn focus_ui(f: &mut Frame, task: &Task, focus: &FocusMode) {
+    let mut lines = vec![Line::from(Span::styled(task.title.to_string(), Style::default().add_modifier(Modifier::BOLD)))];
+    if let Some(note) = &task.note {
+        lines.push(Line::from(""));
+        lines.extend(note.lines().map(|line| Line::from(line.to_string())));
+    }
+    lines.push(Line::from(""));
+    let elapsed = focus.elapsed + focus.started.map_or(Duration::ZERO, |s| s.elapsed());
+    let clock = format!("{:02}:{:02}", elapsed.as_secs() / 60, elapsed.as_secs() % 60);
+    lines.push(Line::from(match (focus.started, elapsed.is_zero()) {
+        (Some(_), _) => Span::styled(clock, Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        (None, false) => Span::styled(format!("{} paused", clock), Style::default().fg(Color::DarkGray)),
+        (None, true) => Span::raw(""),
+    }));
+    lines.push(Line::from(""));
+    let timer = if focus.started.is_some() { "pause" } else { "start" };
+    lines.push(Line::from(Span::styled(format!("t to {} the timer, Esc to leave", timer), Style::default().fg(Color::DarkGray))));
+    // centered up and down too, by the lines it wraps to
+    let area = centered_rect(60, 100, f.area());
+    let height: usize = lines.iter().map(|line| line.width().max(1).div_ceil(area.width.max(1) as usize)).sum();
+    let height = (height as u16).min(area.height);
+    let area = Rect { y: area.y + (area.height - height) / 2, height, ..area };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).wrap(Wrap { trim: false }), area);
+}
+
+// the list's colors for projects and contexts, the rest apart from those
+fn token_color(kind: quick::Kind) -> Color {
+    match kind {
+        quick::Kind::Date(_) => Color::Yellow,
+        quick::Kind::Time(_) => Color::Green,
+        quick::Kind::Tag => Color::Cyan,
+        quick::Kind::Priority(_) => Color::Red,
+        quick::Kind::Project => Color::Blue,
+        quick::Kind::Field => Color::Magenta,
+    }
+}
+
+fn label_color(label: Label) -> Color {
+    match label {
+        Label::Red => Color::Red,
+        Label::Orange => Color::Rgb(255, 140, 0),
+        Label::Yellow => Color::Yellow,
+        Label::Green => Color::Green,
+        Label::Blue => Color::Blue,
+        Label::Purple => Color::Magenta,
+    }
+}
+
+// the task list of the pane `app` currently holds; returns the todo under
+// the mouse when its row is cut off at the edge
+fn render_list(f: &mut Frame, app: &mut App, area: Rect, active: bool) -> Option<usize> {
+    let today = Local::now().date_naive();
+    let items: Vec<ListItem> = app.rows.iter().map(rows::Row::item).collect();
+    let mut title = match app.group {
+        Some(group) => format!("{} (sort: {}, group: {})", app.view().name(), app.shown_sort().name(), group.name()),
+        None => format!("{} (sort: {})", app.view().name(), app.shown_sort().name()),
+    };
+    let hidden = app.hidden_done(today);
+    if hidden > 0 {
+        title.push_str(&format!(" {} done hidden", hidden));
+    }
+    if app.recording.is_some() {
+        title.push_str(" recording");
+    }
+    if let Some(count) = app.count {
+        title.push_str(&format!(" {}", count));
+    }
+    if !app.filter.is_empty() {
+        title.push_str(&format!(" [{}]", app.filter_input.text()));
+    }
+    let (view_minutes, planned) = app.workload(today);
+    if view_minutes > 0 {
+        title.push_str(&format!(" est {}", task::format_minutes(view_minutes)));
+    }
+    let mut title = vec![Span::raw(title)];
+    match app.config.capacity {
+        Some(capacity) if planned > capacity => title.push(Span::styled(
+            format!(" today {} of {}, over capacity", task::format_minutes(planned), task::format_minutes(capacity)),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Some(capacity) if planned > 0 => {
+            title.push(Span::raw(format!(" today {} of {}", task::format_minutes(planned), task::format_minutes(capacity))))
+        }
+        None if planned > 0 => title.push(Span::raw(format!(" today {}", task::format_minutes(planned)))),
+        _ => {}
+    }
+    let title = Line::from(title);
+    // the header of the group the top row is in sticks to the top once it
+    // scrolls off, taking a line from the list
+    let heights: Vec<usize> = items.iter().map(ListItem::height).collect();
+    let sticky = |app: &App| {
+        let top = app.items.state.offset();
+        let group = app.row_group(top, today);
+        (top > 0 && group == app.row_group(top - 1, today)).then_some(group).flatten()
+    };
+    let mut list_area = area;
+    app.items.layout(area, heights.clone());
+    if sticky(app).is_some() && area.height > 3 {
+        list_area = Rect { y: area.y + 1, height: area.height - 1, ..area };
+        app.items.layout(list_area, heights.clone());
+        if sticky(app).is_none() {
+            list_area = area;
+            app.items.layout(area, heights);
+        }
+    }
+    let items = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(if list_area == area { title.clone() } else { Line::default() }))
+        .highlight_style(if active { Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD) } else { Style::default().add_modifier(Modifier::BOLD) })
+        .highlight_symbol(">> ");
+
+    if app.items.items.is_empty() {
+        let empty = Paragraph::new(app.empty_message())
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray))
+            .block(Block::default().borders(Borders::ALL).title(title.clone()));
+        f.render_widget(empty, area);
+    } else {
+        f.render_stateful_widget(items, list_area, &mut app.items.state);
+    }
+    if let (true, Some(name)) = (list_area != area, sticky(app)) {
+        // over the list's top border, whose corners the block's sides cover
+        f.render_widget(Block::default().borders(Borders::ALL).title(title), area);
+        let indent = if app.items.state.selected().is_some() { "   " } else { "" };
+        let header = Paragraph::new(Span::styled(
+            format!("{}▾ {} ({})", indent, name, app.group_len(&name, today)),
+            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        ))
+        .style(Style::default().fg(Color::Black).bg(Color::White));
+        let line = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: 1 };
+        f.render_widget(Clear, line);
+        f.render_widget(header, line);
+    }
+    // the borders and the highlight symbol take their share of the row
+    let room = (area.width as usize).saturating_sub(if app.items.state.selected().is_some() { 5 } else { 2 });
+    let row = app.hover.and_then(|(column, row)| app.items.row_at(column, row))?;
+    (app.rows.get(row)?.width > room).then(|| app.items.items[row])
+}
+
+// the typed text over the rows it narrows the list to, the matched letters
+// in bold, and the selected row's preview at the bottom
+fn picker_ui(f: &mut Frame, picker: &mut Picker, size: Rect) {
+    let rows = picker.rows().count() as u16;
+    let width = picker.rows().map(|(item, _)| (item.label.width() + 8).max(item.preview.width() + 2)).max().unwrap_or(0) as u16;
+    let width = width.max(picker.title.width() as u16 + 4).clamp(30, POPUP_MAX_WIDTH);
+    let area = centered_size(width, rows.clamp(1, 15) + 5, size);
+    let block = Block::default().borders(Borders::ALL).title(picker.title.as_str());
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(inner);
+    let (input, column) = picker.input.spans(inner.width.saturating_sub(2) as usize, &[]);
+    f.render_widget(Paragraph::new(Line::from([vec![Span::raw("> ")], input.spans].concat())), chunks[0]);
+    f.set_cursor_position((chunks[0].x + 2 + column, chunks[0].y));
+    let bold = Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow);
+    let items: Vec<ListItem> = picker
+        .rows()
+        .map(|(item, hits)| {
+            let mut spans = vec![];
+            if let Some(color) = item.color {
+                spans.push(Span::styled("● ", Style::default().fg(color)));
+            }
+            for (at, c) in item.label.char_indices() {
+                let style = if hits.contains(&at) { bold } else { Style::default() };
+                spans.push(Span::styled(c.to_string(), style));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[1], &mut picker.state);
+    let preview = picker.selected().map_or("no match", |item| item.preview.as_str());
+    f.render_widget(Paragraph::new(Span::styled(preview.to_string(), Style::default().fg(Color::DarkGray))), chunks[2]);
+}
+
+// `width` by `height` in the middle of `r`, cut down to fit it
+fn centered_size(width: u16, height: u16, r: Rect) -> Rect {
+    let (width, height) = (width.min(r.width), height.min(r.height));
+    Rect { x: r.x + (r.width - width) / 2, y: r.y + (r.height - height) / 2, width, height }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(popup_layout[1])[1]
+}
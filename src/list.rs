@@ -0,0 +1,395 @@
+use ratatui::{
+    layout::{Margin, Rect},
+    widgets::ListState,
+};
+
+pub struct StateList<T> {
+    pub state: ListState,
+    pub items: Vec<T>,
+    // where the list was last drawn, to map mouse clicks back to rows
+    pub area: Rect,
+    pub heights: Vec<usize>,
+}
+
+impl<T> StateList<T> {
+    pub fn with_items(items: Vec<T>) -> StateList<T> {
+        StateList { state: ListState::default(), items, area: Rect::default(), heights: vec![] }
+    }
+    // called with the rows about to be drawn
+    pub fn layout(&mut self, area: Rect, heights: Vec<usize>) {
+        self.area = area;
+        self.heights = heights;
+        // not drawn at all
+        if area.width < 3 {
+            return;
+        }
+        self.ensure_visible(area.height.saturating_sub(2) as usize);
+    }
+    // scrolled as little as it takes for the selected row to fit in `max`
+    // lines, into the offset of `state` that ratatui's List draws from, so
+    // clicks are mapped with what is on screen before the next frame
+    pub fn ensure_visible(&mut self, max: usize) {
+        let h = &self.heights;
+        if h.is_empty() || max == 0 {
+            return;
+        }
+        let mut start = self.state.offset().min(h.len() - 1);
+        let mut end = start;
+        let mut height = 0;
+        for row in &h[start..] {
+            if height + row > max {
+                break;
+            }
+            height += row;
+            end += 1;
+        }
+        let selected = self.state.selected().unwrap_or(0).min(h.len() - 1);
+        while selected >= end {
+            height += h[end];
+            end += 1;
+            while height > max {
+                height -= h[start];
+                start += 1;
+            }
+        }
+        while selected < start {
+            start -= 1;
+            height += h[start];
+            while height > max {
+                end -= 1;
+                height -= h[end];
+            }
+        }
+        *self.state.offset_mut() = start;
+    }
+    pub fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.area.inner(Margin { vertical: 1, horizontal: 1 });
+        if column < inner.x || column >= inner.right() || row < inner.y || row >= inner.bottom() {
+            return None;
+        }
+        let mut top = inner.y as usize;
+        for (i, height) in self.heights.iter().enumerate().skip(self.state.offset()) {
+            top += height;
+            if (row as usize) < top {
+                return Some(i);
+            }
+        }
+        None
+    }
+    // down a row, from the last back to the first; nothing is selected in
+    // an empty list
+    pub fn next(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.items.len() => i + 1,
+            _ => 0,
+        };
+        self.select(i);
+    }
+    // up a row, from the first round to the last
+    pub fn previous(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) if i > 0 => (i - 1).min(self.items.len().saturating_sub(1)),
+            Some(_) => self.items.len().saturating_sub(1),
+            None => 0,
+        };
+        self.select(i);
+    }
+    // a screenful down: to the last row that fits on one page with the
+    // selected one, in the lines last drawn
+    pub fn page_down(&mut self) {
+        let Some(from) = self.state.selected() else {
+            return self.select_first();
+        };
+        let last = self.items.len().saturating_sub(1);
+        let from = from.min(last);
+        let (mut row, mut used) = (from, self.height(from));
+        while row < last && used + self.height(row + 1) <= self.page() {
+            row += 1;
+            used += self.height(row);
+        }
+        // a row taller than the page still moves on
+        self.select(if row > from { row } else { (from + 1).min(last) });
+    }
+    pub fn page_up(&mut self) {
+        let Some(from) = self.state.selected() else {
+            return self.select_first();
+        };
+        let from = from.min(self.items.len().saturating_sub(1));
+        let (mut row, mut used) = (from, self.height(from));
+        while row > 0 && used + self.height(row - 1) <= self.page() {
+            row -= 1;
+            used += self.height(row);
+        }
+        self.select(if row < from { row } else { from.saturating_sub(1) });
+    }
+    // inside the border; a line while the list hasn't been drawn
+    fn page(&self) -> usize {
+        (self.area.height.saturating_sub(2) as usize).max(1)
+    }
+    // lines the row took when last drawn
+    fn height(&self, row: usize) -> usize {
+        self.heights.get(row).copied().unwrap_or(1)
+    }
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+    pub fn select_last(&mut self) {
+        self.select(self.items.len().saturating_sub(1));
+    }
+    // the row, or None when the list is empty
+    fn select(&mut self, row: usize) {
+        self.state.select((row < self.items.len()).then_some(row));
+    }
+    pub fn selected_item(&self) -> Option<&T> {
+        self.items.get(self.state.selected()?)
+    }
+    pub fn unselect(&mut self) {
+        self.state.select(None);
+    }
+    // what is selected now, found again by `key` with `restore` once the
+    // rows are rebuilt
+    pub fn remember<K>(&self, key: impl Fn(&T) -> K) -> Selection<K> {
+        let row = self.state.selected();
+        Selection { key: row.and_then(|row| self.items.get(row)).map(key), row }
+    }
+    // on the row with the same key, or when that one is gone the row nearest
+    // where it was
+    pub fn restore<K: PartialEq>(&mut self, selection: Selection<K>, key: impl Fn(&T) -> K) {
+        let Some(row) = selection.row else {
+            return;
+        };
+        let found = selection.key.and_then(|k| self.items.iter().position(|item| key(item) == k));
+        let row = found.unwrap_or(row.min(self.items.len().saturating_sub(1)));
+        self.state.select((!self.items.is_empty()).then_some(row));
+    }
+}
+
+pub struct Selection<K> {
+    key: Option<K>,
+    row: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // rows hold indices into `tasks`, as the todo list does
+    fn list(tasks: &[&str], selected: Option<usize>) -> StateList<usize> {
+        let mut list = StateList::with_items((0..tasks.len()).collect());
+        list.state.select(selected);
+        list
+    }
+
+    fn selected<'a>(list: &StateList<usize>, tasks: &[&'a str]) -> Option<&'a str> {
+        list.state.selected().map(|row| tasks[list.items[row]])
+    }
+
+    // every length up to a few rows, with nothing, each row or a row past
+    // the end selected
+    fn every_list() -> impl Iterator<Item = StateList<usize>> {
+        (0..6).flat_map(|len| {
+            std::iter::once(None).chain((0..len + 2).map(Some)).map(move |selected| {
+                let mut list = StateList::with_items((0..len).collect());
+                list.state.select(selected);
+                list
+            })
+        })
+    }
+
+    #[test]
+    fn moves_stay_in_range() {
+        let moves: [fn(&mut StateList<usize>); 4] =
+            [StateList::next, StateList::previous, StateList::select_first, StateList::select_last];
+        for mut list in every_list() {
+            for step in moves {
+                step(&mut list);
+                match list.items.len() {
+                    0 => assert_eq!(list.state.selected(), None),
+                    len => assert!(list.state.selected().is_some_and(|row| row < len)),
+                }
+                assert_eq!(list.selected_item().copied(), list.state.selected());
+            }
+        }
+    }
+
+    #[test]
+    fn next_and_previous_wrap_round() {
+        for len in 1..6 {
+            for row in 0..len {
+                let mut list = list(&vec![""; len], Some(row));
+                list.next();
+                list.previous();
+                assert_eq!(list.state.selected(), Some(row));
+                (0..len).for_each(|_| list.previous());
+                assert_eq!(list.state.selected(), Some(row));
+            }
+            let mut list = list(&vec![""; len], Some(len - 1));
+            list.next();
+            assert_eq!(list.state.selected(), Some(0));
+            list.previous();
+            assert_eq!(list.state.selected(), Some(len - 1));
+        }
+    }
+
+    #[test]
+    fn first_and_last() {
+        let mut list = list(&["a", "b", "c"], None);
+        list.select_last();
+        assert_eq!(list.selected_item(), Some(&2));
+        list.select_first();
+        assert_eq!(list.selected_item(), Some(&0));
+        list.items.clear();
+        assert_eq!(list.selected_item(), None);
+        list.select_last();
+        assert_eq!(list.state.selected(), None);
+    }
+
+    // `rows` one-line rows drawn in a box `lines` high, borders included
+    fn drawn(rows: usize, lines: u16, selected: usize) -> StateList<usize> {
+        let mut list = StateList::with_items((0..rows).collect());
+        list.state.select(Some(selected));
+        list.layout(Rect::new(0, 0, 20, lines), vec![1; rows]);
+        list
+    }
+
+    #[test]
+    fn scrolls_only_as_far_as_it_must() {
+        let mut list = drawn(20, 7, 0);
+        assert_eq!(list.state.offset(), 0);
+        list.state.select(Some(4));
+        list.ensure_visible(5);
+        assert_eq!(list.state.offset(), 0);
+        list.state.select(Some(7));
+        list.ensure_visible(5);
+        assert_eq!(list.state.offset(), 3);
+        list.state.select(Some(5));
+        list.ensure_visible(5);
+        assert_eq!(list.state.offset(), 3);
+        list.state.select(Some(1));
+        list.ensure_visible(5);
+        assert_eq!(list.state.offset(), 1);
+    }
+
+    #[test]
+    fn fits_tall_rows() {
+        let mut list = StateList::with_items((0..6).collect::<Vec<usize>>());
+        list.state.select(Some(3));
+        list.layout(Rect::new(0, 0, 20, 7), vec![2, 2, 2, 2, 1, 1]);
+        // rows 2 and 3 are the four lines ending at row 3
+        assert_eq!(list.state.offset(), 2);
+        list.ensure_visible(0);
+        assert_eq!(list.state.offset(), 2);
+    }
+
+    #[test]
+    fn pages_a_screenful() {
+        let mut list = drawn(12, 7, 0);
+        list.page_down();
+        assert_eq!(list.state.selected(), Some(4));
+        list.page_down();
+        assert_eq!(list.state.selected(), Some(8));
+        list.page_down();
+        assert_eq!(list.state.selected(), Some(11));
+        list.page_down();
+        assert_eq!(list.state.selected(), Some(11));
+        list.page_up();
+        assert_eq!(list.state.selected(), Some(7));
+        list.page_up();
+        list.page_up();
+        assert_eq!(list.state.selected(), Some(0));
+        list.page_up();
+        assert_eq!(list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn pages_over_rows_taller_than_the_page() {
+        let mut list = StateList::with_items((0..3).collect::<Vec<usize>>());
+        list.state.select(Some(0));
+        list.layout(Rect::new(0, 0, 20, 4), vec![3, 3, 3]);
+        list.page_down();
+        assert_eq!(list.state.selected(), Some(1));
+        list.page_up();
+        assert_eq!(list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn pages_stay_in_range() {
+        for mut list in every_list() {
+            list.page_down();
+            assert_eq!(list.selected_item().copied(), list.state.selected());
+            list.page_up();
+            assert_eq!(list.selected_item().copied(), list.state.selected());
+            assert_eq!(list.state.selected().is_none(), list.items.is_empty());
+        }
+    }
+
+    #[test]
+    fn follows_a_sort() {
+        let mut tasks = vec!["c", "a", "b"];
+        let mut list = list(&tasks, Some(0));
+        let selection = list.remember(|&i| tasks[i]);
+        tasks.sort();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(list.state.selected(), Some(2));
+        assert_eq!(selected(&list, &tasks), Some("c"));
+    }
+
+    #[test]
+    fn follows_a_filter() {
+        let tasks = vec!["a", "b", "c", "d"];
+        let mut list = list(&tasks, Some(2));
+        let selection = list.remember(|&i| tasks[i]);
+        list.items.retain(|&i| i % 2 == 0);
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(selected(&list, &tasks), Some("c"));
+    }
+
+    #[test]
+    fn follows_a_reload() {
+        let mut tasks = vec!["a", "b", "c"];
+        let mut list = list(&tasks, Some(1));
+        let selection = list.remember(|&i| tasks[i]);
+        tasks = vec!["new", "c", "b", "a"];
+        list.items = (0..tasks.len()).collect();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(selected(&list, &tasks), Some("b"));
+    }
+
+    #[test]
+    fn stays_near_a_deleted_row() {
+        let mut tasks = vec!["a", "b", "c"];
+        let mut list = list(&tasks, Some(1));
+        let selection = list.remember(|&i| tasks[i]);
+        tasks.remove(1);
+        list.items = (0..tasks.len()).collect();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(selected(&list, &tasks), Some("c"));
+
+        let selection = list.remember(|&i| tasks[i]);
+        tasks.remove(1);
+        list.items = (0..tasks.len()).collect();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(selected(&list, &tasks), Some("a"));
+    }
+
+    #[test]
+    fn unselects_once_the_list_is_empty() {
+        let mut tasks = vec!["a"];
+        let mut list = list(&tasks, Some(0));
+        let selection = list.remember(|&i| tasks[i]);
+        tasks.clear();
+        list.items.clear();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(list.state.selected(), None);
+    }
+
+    #[test]
+    fn leaves_nothing_selected_alone() {
+        let mut tasks = vec!["b", "a"];
+        let mut list = list(&tasks, None);
+        let selection = list.remember(|&i| tasks[i]);
+        tasks.sort();
+        list.restore(selection, |&i| tasks[i]);
+        assert_eq!(list.state.selected(), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::OnceLock;
+
+use syntect::{easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+// the bits of Markdown notes use: `#` headings, `-`/`*`/`1.` lists, `**bold**`,
+// `*italics*` or `_italics_`, `inline code`, and fenced code blocks,
+// highlighted with the syntect `theme` of that name
+pub fn to_spans(text: &str, theme: &str) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    // the language and lines of the open code block
+    let mut block: Option<(String, Vec<&str>)> = None;
+    for line in text.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut block, fence) {
+            (Some((lang, code)), Some(_)) => {
+                lines.extend(highlight(lang, code, theme));
+                block = None;
+                continue;
+            }
+            (Some((_, code)), None) => {
+                code.push(line);
+                continue;
+            }
+            (None, Some(lang)) => {
+                block = Some((lang.trim().to_string(), vec![]));
+                continue;
+            }
+            (None, None) => {}
+        }
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim_start();
+        if let Some(heading) = heading(trimmed) {
+            lines.push(Line::from(Span::styled(heading.to_string(), Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED))));
+            continue;
+        }
+        let mut spans = vec![];
+        let rest = match bullet(trimmed) {
+            Some((marker, rest)) => {
+                spans.push(Span::raw(format!("{}{} ", " ".repeat(indent), marker)));
+                rest
+            }
+            None => line,
+        };
+        spans.extend(inline(rest));
+        lines.push(Line::from(spans));
+    }
+    // a block left open runs to the end of the note
+    if let Some((lang, code)) = block {
+        lines.extend(highlight(&lang, &code, theme));
+    }
+    lines
+}
+
+// loaded on first use, parsing the bundled definitions takes a moment
+fn syntaxes() -> &'static (SyntaxSet, ThemeSet) {
+    static SETS: OnceLock<(SyntaxSet, ThemeSet)> = OnceLock::new();
+    SETS.get_or_init(|| (SyntaxSet::load_defaults_newlines(), ThemeSet::load_defaults()))
+}
+
+// only the foreground, so the block sits on the pane's own background
+fn highlight(lang: &str, code: &[&str], theme: &str) -> Vec<Line<'static>> {
+    let (syntaxes, themes) = syntaxes();
+    let plain = || code.iter().map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(Color::Magenta)))).collect();
+    let theme = match themes.themes.get(theme) {
+        Some(theme) => theme,
+        None => return plain(),
+    };
+    let syntax = syntaxes.find_syntax_by_token(lang).unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut lines = vec![];
+    for line in code {
+        let line = format!("{}\n", line);
+        let regions = match highlighter.highlight_line(&line, syntaxes) {
+            Ok(regions) => regions,
+            Err(_) => return plain(),
+        };
+        let spans: Vec<Span> = regions
+            .into_iter()
+            .map(|(style, text)| {
+                let fg = style.foreground;
+                Span::styled(text.trim_end_matches('\n').to_string(), Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b)))
+            })
+            .collect();
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match level {
+        1..=6 => line[level..].strip_prefix(' ').map(str::trim),
+        _ => None,
+    }
+}
+
+// `- item` as `• item`; numbered items keep their number
+fn bullet(line: &str) -> Option<(String, &str)> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).or_else(|| line.strip_prefix("+ ")) {
+        return Some(("•".to_string(), rest));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match line[digits..].strip_prefix(". ") {
+        Some(rest) if digits > 0 => Some((line[..digits + 1].to_string(), rest)),
+        _ => None,
+    }
+}
+
+// emphasis and code within one line; a marker without its closing twin is
+// plain text
+fn inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    let mut plain = String::new();
+    let mut rest = text;
+    let mut prev = ' ';
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            // snake_case is not emphasis
+            '_' if prev.is_alphanumeric() => None,
+            '`' => close(rest, "`").map(|(inner, after)| (inner, Style::default().fg(Color::Magenta), after)),
+            '*' if rest.starts_with("**") => close(rest, "**").map(|(inner, after)| (inner, Style::default().add_modifier(Modifier::BOLD), after)),
+            '*' | '_' => close(rest, &c.to_string()).map(|(inner, after)| (inner, Style::default().add_modifier(Modifier::ITALIC), after)),
+            _ => None,
+        };
+        match styled {
+            Some((inner, style, after)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(inner.to_string(), style));
+                prev = c;
+                rest = after;
+            }
+            None => {
+                plain.push(c);
+                prev = c;
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+// `text` starts with `marker`: the part up to the next `marker`, and what follows
+fn close<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let body = &text[marker.len()..];
+    let end = body.find(marker)?;
+    match &body[..end] {
+        "" => None,
+        inner => Some((inner, &body[end + marker.len()..])),
+    }
+}
@@ -0,0 +1,294 @@
+use std::{collections::BTreeMap, fs, io, thread, time::Duration};
+
+use chrono::{Local, NaiveDate};
+
+use crate::{
+    curl,
+    json::{self, Json},
+    paths,
+    state::State,
+    storage,
+    sync::SyncProvider,
+    task::Task,
+};
+
+const LOGIN: &str = "https://login.microsoftonline.com/common/oauth2/v2.0";
+const GRAPH: &str = "https://graph.microsoft.com/v1.0/me/todo";
+const SCOPE: &str = "Tasks.ReadWrite offline_access";
+
+struct Remote {
+    id: String,
+    title: String,
+    done: bool,
+    due: Option<NaiveDate>,
+    high: bool,
+}
+
+fn parse_reply(reply: curl::Reply) -> io::Result<Json> {
+    let body = json::parse(&reply.body).unwrap_or(Json::Null);
+    match reply.status {
+        200 | 201 => Ok(body),
+        204 => Ok(Json::Null),
+        status => {
+            let message = body.get("error").and_then(|e| e.get("message").or(Some(e))).and_then(Json::as_str).unwrap_or_default();
+            Err(io::Error::other(format!("Microsoft answered {}: {}", status, message)))
+        }
+    }
+}
+
+fn form(url: &str, fields: &[(&str, &str)]) -> io::Result<curl::Reply> {
+    let options: Vec<(&str, String)> = fields.iter().map(|(k, v)| ("data-urlencode", format!("{}={}", k, v))).collect();
+    curl::run(url, &options)
+}
+
+fn str_field(value: &Json, key: &str) -> String {
+    value.get(key).and_then(Json::as_str).unwrap_or_default().to_string()
+}
+
+// `todo-tui mstodo login`: the OAuth device flow, so no browser redirect is needed
+pub fn login(client_id: &str) -> io::Result<()> {
+    let code = parse_reply(form(&format!("{}/devicecode", LOGIN), &[("client_id", client_id), ("scope", SCOPE)])?)?;
+    println!("{}", str_field(&code, "message"));
+    let interval = code.get("interval").and_then(Json::as_num).unwrap_or(5.0) as u64;
+    let device_code = str_field(&code, "device_code");
+    loop {
+        thread::sleep(Duration::from_secs(interval));
+        let reply = form(
+            &format!("{}/token", LOGIN),
+            &[
+                ("client_id", client_id),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", &device_code),
+            ],
+        )?;
+        let body = json::parse(&reply.body).unwrap_or(Json::Null);
+        match body.get("error").and_then(Json::as_str) {
+            Some("authorization_pending") | Some("slow_down") => continue,
+            Some(_) => return parse_reply(reply).map(|_| ()),
+            None => {
+                save_refresh_token(&str_field(&body, "refresh_token"))?;
+                println!("signed in, run `todo-tui mstodo` to sync");
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn save_refresh_token(token: &str) -> io::Result<()> {
+    storage::write_secret(&paths::mstodo_token_file(), token)
+}
+
+// trade the stored refresh token for an access token; Microsoft hands out a
+// new refresh token each time
+fn access_token(client_id: &str) -> io::Result<String> {
+    let refresh = match fs::read_to_string(paths::mstodo_token_file()) {
+        Ok(token) => token.trim().to_string(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(io::Error::other("not signed in, run `todo-tui mstodo login` first"))
+        }
+        Err(err) => return Err(err),
+    };
+    let body = parse_reply(form(
+        &format!("{}/token", LOGIN),
+        &[("client_id", client_id), ("grant_type", "refresh_token"), ("refresh_token", &refresh), ("scope", SCOPE)],
+    )?)?;
+    if let Some(refresh) = body.get("refresh_token").and_then(Json::as_str) {
+        save_refresh_token(refresh)?;
+    }
+    Ok(str_field(&body, "access_token"))
+}
+
+struct Graph {
+    token: String,
+}
+
+impl Graph {
+    fn call(&self, method: &str, url: &str, body: Option<Json>) -> io::Result<Json> {
+        let url = if url.starts_with("https://") { url.to_string() } else { format!("{}{}", GRAPH, url) };
+        let mut options = vec![("request", method.to_string()), ("header", format!("Authorization: Bearer {}", self.token))];
+        if let Some(body) = body {
+            options.push(("header", "Content-Type: application/json".to_string()));
+            options.push(("data-raw", body.to_string()));
+        }
+        parse_reply(curl::run(&url, &options)?)
+    }
+    // every page of a collection
+    fn list(&self, url: &str) -> io::Result<Vec<Json>> {
+        let mut items = vec![];
+        let mut next = Some(url.to_string());
+        while let Some(url) = next {
+            let page = self.call("GET", &url, None)?;
+            next = page.get("@odata.nextLink").and_then(Json::as_str).map(str::to_string);
+            if let Some(Json::Arr(values)) = page.get("value") {
+                items.extend(values.iter().cloned());
+            }
+        }
+        Ok(items)
+    }
+}
+
+impl Remote {
+    fn from_json(value: &Json) -> Remote {
+        Remote {
+            id: str_field(value, "id"),
+            title: str_field(value, "title"),
+            done: value.get("status").and_then(Json::as_str) == Some("completed"),
+            due: value
+                .get("dueDateTime")
+                .and_then(|due| due.get("dateTime"))
+                .and_then(Json::as_str)
+                .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok()),
+            high: value.get("importance").and_then(Json::as_str) == Some("high"),
+        }
+    }
+    fn differs(&self, task: &Task) -> bool {
+        self.title != task.title || self.due != task.due || self.done != task.is_done() || self.high != (task.priority == Some('A'))
+    }
+}
+
+// To Do only has "high" importance to offer, which (A) maps onto
+fn body(task: &Task) -> Json {
+    let due = task.due.map(|d| {
+        Json::Obj(vec![
+            ("dateTime".to_string(), format!("{}T00:00:00", d).into()),
+            ("timeZone".to_string(), "UTC".into()),
+        ])
+    });
+    Json::Obj(vec![
+        ("title".to_string(), task.title.as_str().into()),
+        ("status".to_string(), if task.is_done() { "completed" } else { "notStarted" }.into()),
+        ("importance".to_string(), if task.priority == Some('A') { "high" } else { "normal" }.into()),
+        ("dueDateTime".to_string(), due.into()),
+    ])
+}
+
+// `todo-tui mstodo`: the configured projects, each in the To Do list of the
+// same name
+pub struct MsTodo {
+    client_id: String,
+    projects: Vec<String>,
+    graph: Graph,
+    // project -> list id
+    lists: Vec<(String, String)>,
+    // list id and the task as pulled
+    remote: Vec<(String, Remote)>,
+    // local task id -> `<list id> <task id>`
+    links: BTreeMap<String, String>,
+}
+
+impl MsTodo {
+    pub fn new(client_id: &str, projects: &[String]) -> MsTodo {
+        MsTodo {
+            client_id: client_id.to_string(),
+            projects: projects.to_vec(),
+            graph: Graph { token: String::new() },
+            lists: vec![],
+            remote: vec![],
+            links: BTreeMap::new(),
+        }
+    }
+    fn push_all(&mut self, tasks: &[Task]) -> io::Result<()> {
+        for task in tasks {
+            match self.links.get(&task.id).cloned() {
+                Some(link) => match self.remote.iter().find(|(list, r)| link == format!("{} {}", list, r.id)) {
+                    Some((list, r)) if r.differs(task) => {
+                        self.graph.call("PATCH", &format!("/lists/{}/tasks/{}", list, r.id), Some(body(task)))?;
+                    }
+                    Some(_) => {}
+                    // deleted over there but edited here since: forget the
+                    // link, keep the local task
+                    None => {
+                        self.links.remove(&task.id);
+                    }
+                },
+                None if !task.is_done() => {
+                    let list = match self.lists.iter().find(|(project, _)| Some(project) == task.project.as_ref()) {
+                        Some((_, list)) => list.to_string(),
+                        None => continue,
+                    };
+                    let created = self.graph.call("POST", &format!("/lists/{}/tasks", list), Some(body(task)))?;
+                    self.links.insert(task.id.to_string(), format!("{} {}", list, str_field(&created, "id")));
+                }
+                None => {}
+            }
+        }
+        // deleted here: gone over there too, unless it's finished there,
+        // where finished tasks are left alone
+        let gone: Vec<(String, String)> =
+            self.links.iter().filter(|(local, _)| !tasks.iter().any(|t| &t.id == *local)).map(|(l, r)| (l.to_string(), r.to_string())).collect();
+        for (local, link) in gone {
+            if let Some((list, r)) = self.remote.iter().find(|(list, r)| link == format!("{} {}", list, r.id) && !r.done) {
+                self.graph.call("DELETE", &format!("/lists/{}/tasks/{}", list, r.id), None)?;
+            }
+            self.links.remove(&local);
+        }
+        Ok(())
+    }
+}
+
+impl SyncProvider for MsTodo {
+    fn name(&self) -> String {
+        "mstodo".to_string()
+    }
+    fn pull(&mut self, known: &[Task]) -> io::Result<Vec<Task>> {
+        let now = Local::now().naive_local();
+        self.graph.token = access_token(&self.client_id)?;
+        let lists = self.graph.list("/lists")?;
+        self.lists.clear();
+        self.remote.clear();
+        for project in &self.projects {
+            let list = match lists.iter().find(|l| str_field(l, "displayName") == *project) {
+                Some(list) => str_field(list, "id"),
+                None => {
+                    let body = Json::Obj(vec![("displayName".to_string(), project.as_str().into())]);
+                    str_field(&self.graph.call("POST", "/lists", Some(body))?, "id")
+                }
+            };
+            for task in self.graph.list(&format!("/lists/{}/tasks", list))? {
+                self.remote.push((list.to_string(), Remote::from_json(&task)));
+            }
+            self.lists.push((project.to_string(), list));
+        }
+        self.links = State::load()?.links.remove("mstodo").unwrap_or_default();
+        let mut tasks = vec![];
+        for (list, r) in &self.remote {
+            let link = format!("{} {}", list, r.id);
+            let id = self.links.iter().find(|(_, l)| **l == link).map(|(id, _)| id.to_string());
+            let mut task = match id.as_ref().and_then(|id| known.iter().find(|t| &t.id == id)) {
+                Some(task) => task.clone(),
+                // finished tasks from before the first sync stay over there
+                None if r.done => continue,
+                None => {
+                    let mut task = Task::parse("", now.date());
+                    task.id = id.unwrap_or_else(|| task.id.to_string());
+                    task.project = self.lists.iter().find(|(_, l)| l == list).map(|(project, _)| project.to_string());
+                    self.links.insert(task.id.to_string(), link);
+                    task
+                }
+            };
+            task.title = r.title.to_string();
+            task.due = r.due;
+            match (r.high, task.priority) {
+                (true, _) => task.priority = Some('A'),
+                (false, Some('A')) => task.priority = None,
+                _ => {}
+            }
+            task.completed = if r.done { task.completed.or(Some(now)) } else { None };
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+    fn push(&mut self, tasks: &[Task]) -> io::Result<()> {
+        let pushed = self.push_all(tasks);
+        // keep the links of whatever got created, even when a later call failed
+        let mut state = State::load()?;
+        state.links.insert("mstodo".to_string(), self.links.clone());
+        state.save()?;
+        pushed
+    }
+    // the tasks of the synced lists that were linked as of the pull; one of
+    // them missing was deleted there
+    fn holds(&self, task: &Task) -> bool {
+        self.links.contains_key(&task.id)
+    }
+}
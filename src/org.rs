@@ -0,0 +1,310 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::task::{self, Label, Task};
+
+const DATE: &str = "%Y-%m-%d %a";
+const CLOSED: &str = "%Y-%m-%d %a %H:%M";
+
+// inbox todo as top-level headings, then one heading per project holding
+// its todo; ids go in a property drawer so an import finds them again, next
+// to the custom fields; checklists become checkbox lists and the note the
+// text of the heading
+pub fn export(tasks: &[Task]) -> String {
+    let mut text = String::new();
+    for task in tasks.iter().filter(|t| t.project.is_none()) {
+        push_task(&mut text, task, 1);
+    }
+    let mut projects: Vec<&String> = tasks.iter().filter_map(|t| t.project.as_ref()).collect();
+    projects.sort();
+    projects.dedup();
+    for project in projects {
+        text.push_str(&format!("* {}\n", project));
+        for task in tasks.iter().filter(|t| t.project.as_ref() == Some(project)) {
+            push_task(&mut text, task, 2);
+        }
+    }
+    text
+}
+
+fn push_task(text: &mut String, task: &Task, level: usize) {
+    let keyword = if task.is_done() { "DONE" } else { "TODO" };
+    text.push_str(&format!("{} {} ", "*".repeat(level), keyword));
+    if let Some(p) = task.priority {
+        text.push_str(&format!("[#{}] ", p));
+    }
+    text.push_str(&task.title);
+    // org's own convention for contexts is `@` tags
+    let mut tags: Vec<String> = task.contexts.iter().map(|c| format!("@{}", c)).collect();
+    tags.extend(task.label.map(|l| l.name().to_string()));
+    if !tags.is_empty() {
+        text.push_str(&format!(" :{}:", tags.join(":")));
+    }
+    text.push('\n');
+    let mut planning = vec![];
+    if let Some(done) = task.completed {
+        planning.push(format!("CLOSED: [{}]", done.format(CLOSED)));
+    }
+    if let Some(due) = task.due {
+        planning.push(format!("DEADLINE: <{}>", due.format(DATE)));
+    }
+    if !planning.is_empty() {
+        text.push_str(&format!("{}{}\n", " ".repeat(level + 1), planning.join(" ")));
+    }
+    let indent = " ".repeat(level + 1);
+    text.push_str(&format!("{}:PROPERTIES:\n{}:ID: {}\n", indent, indent, task.id));
+    if let Some(place) = &task.location {
+        text.push_str(&format!("{}:LOCATION: {}\n", indent, place));
+    }
+    if let Some(minutes) = task.estimate {
+        text.push_str(&format!("{}:Effort: {}:{:02}\n", indent, minutes / 60, minutes % 60));
+    }
+    for (key, value) in &task.fields {
+        text.push_str(&format!("{}:{}: {}\n", indent, key, value));
+    }
+    text.push_str(&format!("{}:END:\n", indent));
+    for (done, item) in &task.checklist {
+        text.push_str(&format!("{}- [{}] {}\n", indent, if *done { 'X' } else { ' ' }, item));
+    }
+    for line in task.note.iter().flat_map(|note| note.lines()) {
+        match line {
+            "" => text.push('\n'),
+            line => text.push_str(&format!("{}{}\n", indent, line)),
+        }
+    }
+}
+
+// TODO/DONE headings become todo, other headings name the project of the
+// todo nested under them; `DEADLINE` (or else `SCHEDULED`) is the due date,
+// a tag naming a label sets the label, `@` tags are contexts and the first
+// other tag is the project
+pub fn import(text: &str, today: NaiveDate) -> Vec<Task> {
+    let mut tasks: Vec<Task> = vec![];
+    // titles of the plain headings above the current line, by level
+    let mut outline: Vec<(usize, String)> = vec![];
+    let mut current = false;
+    // the current todo's heading level, and whether its property drawer is open
+    let (mut level_of_task, mut drawer) = (0, false);
+    let mut notes: Vec<Vec<&str>> = vec![];
+    for line in text.lines() {
+        if let Some((level, heading)) = parse_heading(line) {
+            outline.retain(|(l, _)| *l < level);
+            current = false;
+            let (keyword, rest) = match heading.split_once(' ') {
+                Some((k, rest)) if k == "TODO" || k == "DONE" => (k, rest),
+                _ if heading == "TODO" || heading == "DONE" => (heading, ""),
+                _ => {
+                    let (title, _) = split_tags(heading);
+                    // a todo.txt project is one word
+                    outline.push((level, title.split_whitespace().collect::<Vec<_>>().join("-")));
+                    continue;
+                }
+            };
+            let (rest, priority) = match rest.strip_prefix("[#").and_then(|r| r.split_once(']')) {
+                Some((p, rest)) if p.len() == 1 && p.chars().all(|c| c.is_ascii_uppercase()) => (rest.trim_start(), p.chars().next()),
+                _ => (rest, None),
+            };
+            let (title, tags) = split_tags(rest);
+            let mut task = Task::parse("", today);
+            task.title = title.to_string();
+            task.priority = priority;
+            task.project = outline.last().map(|(_, project)| project.to_string());
+            for tag in tags {
+                match Label::parse(tag) {
+                    Some(label) => task.label = Some(label),
+                    None if tag.len() > 1 && tag.starts_with('@') => task.contexts.push(tag[1..].to_string()),
+                    None if task.project.is_none() => task.project = Some(tag.to_string()),
+                    None => {}
+                }
+            }
+            if keyword == "DONE" {
+                task.completed = Some(today.and_time(NaiveTime::MIN));
+            }
+            tasks.push(task);
+            notes.push(vec![]);
+            current = true;
+            (level_of_task, drawer) = (level, false);
+            continue;
+        }
+        let task = match tasks.last_mut() {
+            Some(task) if current => task,
+            _ => continue,
+        };
+        let raw = line;
+        let line = line.trim();
+        match line {
+            ":PROPERTIES:" => drawer = true,
+            ":END:" => drawer = false,
+            _ => {}
+        }
+        let planning = ["CLOSED:", "DEADLINE:", "SCHEDULED:"].iter().any(|k| line.starts_with(k));
+        let checkbox = ["- [ ] ", "- [X] ", "- [x] "].iter().any(|c| line.starts_with(c));
+        if !drawer && line != ":END:" && !planning && !checkbox {
+            // keep the note's own indentation, under what the heading adds
+            let indent = raw.len() - raw.trim_start_matches(' ').len();
+            if let Some(note) = notes.last_mut() {
+                note.push(&raw[indent.min(level_of_task + 1)..]);
+            }
+            continue;
+        }
+        if let Some(item) = line.strip_prefix("- [ ] ") {
+            task.checklist.push((false, item.trim().to_string()));
+        } else if let Some(item) = line.strip_prefix("- [X] ").or_else(|| line.strip_prefix("- [x] ")) {
+            task.checklist.push((true, item.trim().to_string()));
+        } else if let Some(id) = line.strip_prefix(":ID:").map(str::trim).filter(|id| !id.is_empty()) {
+            task.id = id.to_string();
+        } else if let Some(place) = line.strip_prefix(":LOCATION:").map(str::trim).filter(|p| !p.is_empty()) {
+            // one word in todo.txt
+            task.location = Some(place.split_whitespace().collect::<Vec<_>>().join("-"));
+        } else if let Some(effort) = line.strip_prefix(":Effort:").map(str::trim) {
+            task.estimate = parse_effort(effort);
+        } else if let Some((key, value)) = line.strip_prefix(':').and_then(|l| l.split_once(':')) {
+            // other properties are custom fields, when they fit in one
+            let value = value.trim();
+            if task::is_field_key(key) && !value.is_empty() && !value.contains([':', ' ']) {
+                task.fields.push((key.to_string(), value.to_string()));
+            }
+        }
+        if let Some(date) = stamp_after(line, "DEADLINE:").and_then(parse_date) {
+            task.due = Some(date);
+        } else if let Some(date) = stamp_after(line, "SCHEDULED:").and_then(parse_date) {
+            task.due = task.due.or(Some(date));
+        }
+        if let Some(done) = stamp_after(line, "CLOSED:").and_then(parse_closed) {
+            task.completed = Some(done);
+        }
+    }
+    for (task, note) in tasks.iter_mut().zip(notes) {
+        let note = note.join("\n");
+        let note = note.trim_matches('\n').trim_end();
+        if !note.is_empty() {
+            task.note = Some(note.to_string());
+        }
+    }
+    tasks
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 {
+        return None;
+    }
+    Some((level, line[level..].strip_prefix(' ')?.trim()))
+}
+
+// `Title :tag1:tag2:` into the title and its tags
+fn split_tags(heading: &str) -> (&str, Vec<&str>) {
+    if let Some((title, tags)) = heading.rsplit_once(' ') {
+        if tags.len() > 1 && tags.starts_with(':') && tags.ends_with(':') {
+            return (title.trim_end(), tags.split(':').filter(|t| !t.is_empty()).collect());
+        }
+    }
+    (heading, vec![])
+}
+
+// the text inside the `<...>` or `[...]` following `key`
+fn stamp_after<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let rest = line[line.find(key)? + key.len()..].trim_start();
+    let close = match rest.chars().next()? {
+        '<' => '>',
+        '[' => ']',
+        _ => return None,
+    };
+    rest[1..].split(close).next()
+}
+
+fn parse_date(stamp: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(stamp.split_whitespace().next()?, "%Y-%m-%d").ok()
+}
+
+// org's `H:MM`
+fn parse_effort(effort: &str) -> Option<u32> {
+    let (hours, minutes) = effort.split_once(':')?;
+    hours.parse::<u32>().ok()?.checked_mul(60)?.checked_add(minutes.parse::<u32>().ok()?)
+}
+
+fn parse_closed(stamp: &str) -> Option<NaiveDateTime> {
+    let date = parse_date(stamp)?;
+    let time = stamp.split_whitespace().find_map(|w| NaiveTime::parse_from_str(w, "%H:%M").ok());
+    Some(date.and_time(time.unwrap_or(NaiveTime::MIN)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    fn lines(tasks: &[Task]) -> Vec<String> {
+        tasks.iter().map(Task::to_line).collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let tasks: Vec<Task> = [
+            "(A) 2026-06-01 call mum @phone label:red due:2026-06-03 est:1h30m id:a",
+            "2026-06-01 pay rent +Home loc:bank remind:2026-06-02T09:00 url:example.org check:stamps checked:envelope id:b",
+            "x 2026-05-31T10:30 2026-06-01 file taxes +Home note:see\\nthe\\n\\nletter pri:B id:c",
+            "2026-06-01 ship it +Work @desk id:d",
+        ]
+        .iter()
+        .map(|line| Task::from_line(line, today()))
+        .collect();
+        let text = export(&tasks);
+        let back = import(&text, today());
+        // org has nothing for reminders
+        let mut expected = tasks.clone();
+        expected[1].remind = None;
+        let (mut expected, mut back) = (lines(&expected), lines(&back));
+        expected.sort();
+        back.sort();
+        assert_eq!(back, expected, "{}", text);
+    }
+
+    #[test]
+    fn nested_headings_name_the_project() {
+        let text = "* TODO inbox todo\n* Side projects\n** Garden shed\n*** TODO buy wood\n** TODO sketch it\n* TODO another\n";
+        let tasks = import(text, today());
+        let projects: Vec<(&str, Option<&str>)> = tasks.iter().map(|t| (t.title.as_str(), t.project.as_deref())).collect();
+        assert_eq!(
+            projects,
+            [("inbox todo", None), ("buy wood", Some("Garden-shed")), ("sketch it", Some("Side-projects")), ("another", None)]
+        );
+    }
+
+    #[test]
+    fn deadline_wins_over_scheduled() {
+        let tasks = import(
+            "* TODO a\n  SCHEDULED: <2026-06-05 Fri> DEADLINE: <2026-06-09 Tue>\n* TODO b\n  DEADLINE: <2026-06-09 Tue>\n  SCHEDULED: <2026-06-05 Fri>\n* TODO c\n  SCHEDULED: <2026-06-05 Fri>\n",
+            today(),
+        );
+        let due: Vec<String> = tasks.iter().map(|t| t.due.unwrap().to_string()).collect();
+        assert_eq!(due, ["2026-06-09", "2026-06-09", "2026-06-05"]);
+    }
+
+    #[test]
+    fn tags_set_contexts_label_and_project() {
+        let task = &import("* TODO water plants :@home:green:garden:errands:\n", today())[0];
+        assert_eq!(task.title, "water plants");
+        assert_eq!(task.contexts, ["home"]);
+        assert!(task.label == Some(Label::Green));
+        // the first other tag
+        assert_eq!(task.project.as_deref(), Some("garden"));
+    }
+
+    #[test]
+    fn done_items() {
+        let tasks = import("* DONE with a time\n  CLOSED: [2026-05-30 Sat 18:05]\n* DONE without\n* DONE\n", today());
+        let done: Vec<String> = tasks.iter().map(|t| t.completed.unwrap().to_string()).collect();
+        assert_eq!(done, ["2026-05-30 18:05:00", "2026-06-01 00:00:00", "2026-06-01 00:00:00"]);
+        assert!(export(&tasks).starts_with("* DONE with a time\n  CLOSED: [2026-05-30 Sat 18:05]\n"));
+    }
+
+    #[test]
+    fn efforts_too_big_are_dropped() {
+        let tasks = import("* TODO a\n  :PROPERTIES:\n  :Effort: 1:30\n  :END:\n* TODO b\n  :PROPERTIES:\n  :Effort: 99999999:00\n  :END:\n", today());
+        let estimates: Vec<Option<u32>> = tasks.iter().map(|t| t.estimate).collect();
+        assert_eq!(estimates, [Some(90), None]);
+    }
+}
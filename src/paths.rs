@@ -0,0 +1,98 @@
+use std::{env, io, path::PathBuf};
+
+use directories::{BaseDirs, ProjectDirs};
+
+// everything lives in $TODO_TUI_DIR, falling back to ~/.todo-tui; on
+// Windows to the roaming app data (%APPDATA%\todo-tui\data), unless a
+// ~/.todo-tui is there already. None without a home directory
+fn locate() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("TODO_TUI_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let dotted = home()?.join(".todo-tui");
+    match ProjectDirs::from("", "", "todo-tui") {
+        Some(dirs) if cfg!(windows) && !dotted.exists() => Some(dirs.data_dir().to_path_buf()),
+        _ => Some(dotted),
+    }
+}
+
+// run first, so no command reads or writes the data relative to the working
+// directory when there is nowhere else to keep it
+pub fn check() -> io::Result<()> {
+    match locate() {
+        Some(_) => Ok(()),
+        None => Err(io::Error::new(io::ErrorKind::NotFound, "no home directory to keep the data in; set TODO_TUI_DIR")),
+    }
+}
+
+pub fn data_dir() -> PathBuf {
+    locate().expect("the data directory is checked at startup")
+}
+
+pub fn home() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+}
+
+// `~/notes` in the config means the home directory, as does `~\notes`;
+// without one the path is kept as written
+pub fn expand(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")), home()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn state_file() -> PathBuf {
+    data_dir().join("state")
+}
+
+pub fn todo_file() -> PathBuf {
+    data_dir().join("todo.txt")
+}
+
+// completed todo moved out of todo.txt
+pub fn archive_file() -> PathBuf {
+    data_dir().join("done.txt")
+}
+
+// changes being saved, see journal.rs
+pub fn journal_file() -> PathBuf {
+    data_dir().join("journal")
+}
+
+pub fn activity_file() -> PathBuf {
+    data_dir().join("activity.log")
+}
+
+pub fn token_file() -> PathBuf {
+    data_dir().join("api_token")
+}
+
+pub fn config_file() -> PathBuf {
+    data_dir().join("config.toml")
+}
+
+// Microsoft's refresh token from `todo-tui mstodo login`
+pub fn mstodo_token_file() -> PathBuf {
+    data_dir().join("mstodo_token")
+}
+
+// the merged list as of the last sync with a provider
+pub fn sync_base(name: &str) -> PathBuf {
+    let file: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    data_dir().join("sync").join(format!("{}.txt", file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_the_home_directory() {
+        let home = home().unwrap();
+        assert_eq!(expand("~/notes"), home.join("notes"));
+        assert_eq!(expand("~\\notes"), home.join("notes"));
+        assert_eq!(expand("notes/~/a"), PathBuf::from("notes/~/a"));
+        assert_eq!(expand("~notes"), PathBuf::from("~notes"));
+    }
+}
@@ -0,0 +1,151 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{style::Color, widgets::ListState};
+
+use crate::input::TextInput;
+
+// one row to pick from, with the line shown under the list while it is selected
+pub struct Item {
+    pub label: String,
+    pub preview: String,
+    // drawn as a dot before the label
+    pub color: Option<Color>,
+}
+
+impl Item {
+    pub fn new(label: impl Into<String>, preview: impl Into<String>) -> Item {
+        Item { label: label.into(), preview: preview.into(), color: None }
+    }
+}
+
+pub enum Picked {
+    // by its index in the items given
+    Item(usize),
+    Cancelled,
+}
+
+// a list narrowed down by typing, best match first; the label, project and
+// context pickers are each one of these
+pub struct Picker {
+    pub title: String,
+    items: Vec<Item>,
+    pub input: TextInput,
+    // the items matching what is typed, with the byte offsets of the
+    // characters that matched
+    shown: Vec<(usize, Vec<usize>)>,
+    pub state: ListState,
+}
+
+impl Picker {
+    // with `selected` selected, as long as nothing is typed
+    pub fn new(title: impl Into<String>, items: Vec<Item>, selected: usize) -> Picker {
+        let mut picker = Picker { title: title.into(), items, input: TextInput::default(), shown: vec![], state: ListState::default() };
+        picker.narrow();
+        picker.state.select(Some(selected.min(picker.shown.len().saturating_sub(1))));
+        picker
+    }
+    // the shown rows: the item, and which of its label's bytes matched
+    pub fn rows(&self) -> impl Iterator<Item = (&Item, &[usize])> {
+        self.shown.iter().map(|(i, hits)| (&self.items[*i], hits.as_slice()))
+    }
+    pub fn selected(&self) -> Option<&Item> {
+        let row = self.state.selected()?;
+        self.shown.get(row).map(|(i, _)| &self.items[*i])
+    }
+    // pasted text narrows the rows as typed text would, on one line
+    pub fn paste(&mut self, text: &str) {
+        self.input.insert(&text.replace(['\r', '\n'], " "));
+        self.narrow();
+    }
+    // Up and Down (or Ctrl+P and Ctrl+N) move, wrapping round, Enter picks and
+    // Esc cancels; the rest edits the text. None while it stays open
+    pub fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<Picked> {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Esc => return Some(Picked::Cancelled),
+            KeyCode::Enter => {
+                let row = self.state.selected()?;
+                return self.shown.get(row).map(|(i, _)| Picked::Item(*i));
+            }
+            KeyCode::Down | KeyCode::Tab => self.step(true),
+            KeyCode::Up | KeyCode::BackTab => self.step(false),
+            KeyCode::Char('n') if ctrl => self.step(true),
+            KeyCode::Char('p') if ctrl => self.step(false),
+            _ if self.input.handle(code, modifiers) => self.narrow(),
+            KeyCode::Char(c) if !ctrl => {
+                self.input.insert(c.encode_utf8(&mut [0; 4]));
+                self.narrow();
+            }
+            _ => {}
+        }
+        None
+    }
+    fn step(&mut self, down: bool) {
+        let rows = self.shown.len();
+        if rows > 0 {
+            let row = self.state.selected().unwrap_or(0);
+            self.state.select(Some(if down { (row + 1) % rows } else { (row + rows - 1) % rows }));
+        }
+    }
+    // every item in order while nothing is typed
+    fn narrow(&mut self) {
+        let pattern = self.input.text().trim();
+        let mut shown: Vec<(i32, usize, Vec<usize>)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| fuzzy(pattern, &item.label).map(|(score, hits)| (score, i, hits)))
+            .collect();
+        if !pattern.is_empty() {
+            shown.sort_by_key(|(score, i, _)| (-score, *i));
+        }
+        self.shown = shown.into_iter().map(|(_, i, hits)| (i, hits)).collect();
+        self.state.select((!self.shown.is_empty()).then_some(0));
+    }
+}
+
+// the pattern's characters in order somewhere in the text, ignoring case:
+// how well they match, runs of them and word starts counting most, and the
+// byte offsets they matched at. Tried from each place the first one is found,
+// keeping the best
+pub fn fuzzy(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let Some(&first) = pattern.first() else {
+        return Some((0, vec![]));
+    };
+    let mut best: Option<(i32, Vec<usize>)> = None;
+    for start in (0..chars.len()).filter(|&i| lower(chars[i].1) == first) {
+        let mut hits = vec![start];
+        let mut at = start + 1;
+        for &c in &pattern[1..] {
+            match (at..chars.len()).find(|&i| lower(chars[i].1) == c) {
+                Some(i) => {
+                    hits.push(i);
+                    at = i + 1;
+                }
+                None => break,
+            }
+        }
+        if hits.len() < pattern.len() {
+            // later starts leave even less text to find the rest in
+            break;
+        }
+        let mut score = 0;
+        for (n, &i) in hits.iter().enumerate() {
+            score += 1;
+            if n > 0 && hits[n - 1] + 1 == i {
+                score += 5;
+            }
+            if i == 0 || !chars[i - 1].1.is_alphanumeric() {
+                score += 3;
+            }
+        }
+        // the tighter the better
+        score -= (hits[hits.len() - 1] - hits[0]) as i32 / 2;
+        if best.as_ref().is_none_or(|(b, _)| score > *b) {
+            best = Some((score, hits.iter().map(|&i| chars[i].0).collect()));
+        }
+    }
+    best
+}
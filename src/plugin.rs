@@ -0,0 +1,291 @@
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc, thread::JoinHandle};
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+
+use chrono::Local;
+
+use crate::{config::Config, hooks, paths, task::Task};
+
+// what a script asks the app to do; applied after the script returns
+pub enum Action {
+    Add(String),
+    Complete(String),
+    // replace a task's text, as typed in the popup
+    Edit(String, String),
+    Status(String),
+}
+
+#[derive(Default)]
+struct Registry {
+    // key, script, function
+    bindings: Vec<(char, usize, String)>,
+    // command name, script, function
+    commands: Vec<(String, usize, String)>,
+    decorators: Vec<(usize, String)>,
+    // decorators that failed since last asked, each turned off
+    failed: Vec<String>,
+    actions: Vec<Action>,
+    tasks: Array,
+    loading: usize,
+}
+
+// the `.rhai` scripts in the plugins directory
+//
+// a script registers itself when loaded:
+//   bind("X", "fn_name")        run `fn_name()` on X in the list
+//   command("name", "fn_name")  run `fn_name(args)` with `todo-tui run name args...`
+//   decorate("fn_name")         `fn_name(task)` returns text shown after each row
+// and while running can call:
+//   tasks()                     every task as a map: id, title, project, contexts, priority,
+//                               due, label, estimate, location, done, created,
+//                               checklist, note, fields
+//   add(text)  complete(id)  edit(id, text)  status(message)
+pub struct Plugins {
+    engine: Engine,
+    scripts: Vec<AST>,
+    registry: Rc<RefCell<Registry>>,
+}
+
+fn task_map(task: &Task) -> Dynamic {
+    let text = |value: Option<String>| value.map_or(Dynamic::UNIT, Dynamic::from);
+    let mut map = Map::new();
+    map.insert("id".into(), task.id.to_string().into());
+    map.insert("title".into(), task.title.to_string().into());
+    map.insert("project".into(), text(task.project.clone()));
+    let contexts: Array = task.contexts.iter().map(|c| Dynamic::from(c.to_string())).collect();
+    map.insert("contexts".into(), contexts.into());
+    map.insert("priority".into(), text(task.priority.map(String::from)));
+    map.insert("due".into(), text(task.due.map(|d| d.to_string())));
+    map.insert("label".into(), text(task.label.map(|l| l.name().to_string())));
+    map.insert("done".into(), task.is_done().into());
+    map.insert("location".into(), text(task.location.clone()));
+    map.insert("estimate".into(), task.estimate.map_or(Dynamic::UNIT, |m| Dynamic::from(m as i64)));
+    map.insert("created".into(), task.created.to_string().into());
+    let checklist: Array = task
+        .checklist
+        .iter()
+        .map(|(done, item)| {
+            let mut row = Map::new();
+            row.insert("text".into(), item.to_string().into());
+            row.insert("done".into(), (*done).into());
+            Dynamic::from(row)
+        })
+        .collect();
+    map.insert("checklist".into(), checklist.into());
+    map.insert("note".into(), text(task.note.clone()));
+    let fields: Map = task.fields.iter().map(|(k, v)| (k.into(), v.to_string().into())).collect();
+    map.insert("fields".into(), fields.into());
+    map.into()
+}
+
+// how much a script may do in one call before it is stopped, so a loop in
+// one (a decorator runs for every row) can't hang the app
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+
+pub fn dir() -> PathBuf {
+    paths::data_dir().join("plugins")
+}
+
+impl Plugins {
+    pub fn empty() -> Plugins {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        Plugins { engine, scripts: vec![], registry: Rc::default() }
+    }
+    // errors are per script, so one broken plugin doesn't stop the rest
+    pub fn load() -> (Plugins, Vec<String>) {
+        let mut plugins = Plugins::empty();
+        plugins.register_api();
+        let mut errors = vec![];
+        let mut files: Vec<PathBuf> = match fs::read_dir(dir()) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "rhai")).collect(),
+            Err(_) => vec![],
+        };
+        files.sort();
+        for file in files {
+            let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            plugins.registry.borrow_mut().loading = plugins.scripts.len();
+            match plugins.engine.compile_file(file) {
+                Ok(ast) => {
+                    if let Err(err) = plugins.engine.run_ast(&ast) {
+                        errors.push(format!("{}: {}", name, err));
+                    }
+                    plugins.scripts.push(ast);
+                }
+                Err(err) => errors.push(format!("{}: {}", name, err)),
+            }
+        }
+        (plugins, errors)
+    }
+    fn register_api(&mut self) {
+        let r = self.registry.clone();
+        self.engine.register_fn("bind", move |key: &str, function: &str| {
+            let mut r = r.borrow_mut();
+            if let Some(key) = key.chars().next() {
+                let script = r.loading;
+                r.bindings.push((key, script, function.to_string()));
+            }
+        });
+        let r = self.registry.clone();
+        self.engine.register_fn("command", move |name: &str, function: &str| {
+            let mut r = r.borrow_mut();
+            let script = r.loading;
+            r.commands.push((name.to_string(), script, function.to_string()));
+        });
+        let r = self.registry.clone();
+        self.engine.register_fn("decorate", move |function: &str| {
+            let mut r = r.borrow_mut();
+            let script = r.loading;
+            r.decorators.push((script, function.to_string()));
+        });
+        let r = self.registry.clone();
+        self.engine.register_fn("tasks", move || r.borrow().tasks.clone());
+        let r = self.registry.clone();
+        self.engine.register_fn("add", move |text: &str| r.borrow_mut().actions.push(Action::Add(text.to_string())));
+        let r = self.registry.clone();
+        self.engine.register_fn("complete", move |id: &str| r.borrow_mut().actions.push(Action::Complete(id.to_string())));
+        let r = self.registry.clone();
+        self.engine.register_fn("edit", move |id: &str, text: &str| {
+            r.borrow_mut().actions.push(Action::Edit(id.to_string(), text.to_string()))
+        });
+        let r = self.registry.clone();
+        self.engine.register_fn("status", move |message: &str| r.borrow_mut().actions.push(Action::Status(message.to_string())));
+    }
+    pub fn bound(&self, key: char) -> bool {
+        self.registry.borrow().bindings.iter().any(|(k, _, _)| *k == key)
+    }
+    // without re-running the script's top level, which would register it again
+    fn call_fn(&self, script: usize, function: &str, args: impl FuncArgs) -> Result<Dynamic, Box<rhai::EvalAltResult>> {
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine.call_fn_with_options(options, &mut Scope::new(), &self.scripts[script], function, args)
+    }
+    fn call(&self, script: usize, function: &str, tasks: &[Task], args: impl FuncArgs) -> Result<Vec<Action>, String> {
+        self.registry.borrow_mut().tasks = tasks.iter().map(task_map).collect();
+        let result = self.call_fn(script, function, args);
+        let actions = std::mem::take(&mut self.registry.borrow_mut().actions);
+        result.map(|_| actions).map_err(|err| format!("{}: {}", function, err))
+    }
+    pub fn run_key(&self, key: char, tasks: &[Task]) -> Result<Vec<Action>, String> {
+        let binding = self.registry.borrow().bindings.iter().find(|(k, _, _)| *k == key).map(|(_, s, f)| (*s, f.to_string()));
+        match binding {
+            Some((script, function)) => self.call(script, &function, tasks, ()),
+            None => Ok(vec![]),
+        }
+    }
+    pub fn has_command(&self, name: &str) -> bool {
+        self.registry.borrow().commands.iter().any(|(n, _, _)| n == name)
+    }
+    pub fn run_command(&self, name: &str, args: &[String], tasks: &[Task]) -> Result<Vec<Action>, String> {
+        let command = self.registry.borrow().commands.iter().find(|(n, _, _)| n == name).map(|(_, s, f)| (*s, f.to_string()));
+        let args: Array = args.iter().map(|a| Dynamic::from(a.to_string())).collect();
+        match command {
+            Some((script, function)) => self.call(script, &function, tasks, (args,)),
+            None => Err(format!("no plugin registers a `{}` command", name)),
+        }
+    }
+    // text the decorators add after a row; one that fails is turned off
+    // rather than failing again on every row, see `take_failed`
+    pub fn decorate(&self, task: &Task) -> String {
+        let decorators = self.registry.borrow().decorators.iter().map(|(s, f)| (*s, f.to_string())).collect::<Vec<_>>();
+        let mut text = String::new();
+        for (script, function) in decorators {
+            match self.call_fn(script, &function, (task_map(task),)).map(Dynamic::into_string) {
+                Ok(Ok(s)) if !s.is_empty() => text.push_str(&format!("  {}", s)),
+                Ok(Ok(_)) => {}
+                failed => {
+                    let error = match failed {
+                        Err(err) => err.to_string(),
+                        _ => "did not return a string".to_string(),
+                    };
+                    let mut r = self.registry.borrow_mut();
+                    r.decorators.retain(|(s, f)| (*s, f.as_str()) != (script, function.as_str()));
+                    r.failed.push(format!("{}: {}", function, error));
+                }
+            }
+        }
+        text
+    }
+    // the decorators that failed since the last call
+    pub fn take_failed(&self) -> Vec<String> {
+        std::mem::take(&mut self.registry.borrow_mut().failed)
+    }
+}
+
+#[derive(Default)]
+pub struct Applied {
+    pub changed: bool,
+    pub messages: Vec<String>,
+    // hooks the changes fired, for the command line to wait on
+    pub hooks: Vec<JoinHandle<()>>,
+}
+
+// carry out what a script asked for; the TUI and `todo-tui run` both go
+// through here
+pub fn apply(actions: Vec<Action>, tasks: &mut Vec<Task>, config: &Config) -> Applied {
+    let now = Local::now().naive_local();
+    let mut applied = Applied::default();
+    for action in actions {
+        match action {
+            Action::Add(text) => {
+                let task = config.new_task(&text, None, now.date());
+                applied.hooks.extend(hooks::fire(&config.hooks, hooks::Event::Added, &task));
+                tasks.push(task);
+                applied.changed = true;
+            }
+            Action::Complete(id) => match tasks.iter_mut().find(|t| t.id == id) {
+                Some(task) if !task.is_done() => {
+                    task.completed = Some(now);
+                    task.touched = now;
+                    applied.hooks.extend(hooks::fire(&config.hooks, hooks::Event::Completed, task));
+                    applied.changed = true;
+                }
+                Some(_) => {}
+                None => applied.messages.push(format!("complete: no task {}", id)),
+            },
+            Action::Edit(id, text) => match tasks.iter_mut().find(|t| t.id == id) {
+                Some(task) => {
+                    task.apply_edit(Task::parse(&text, now.date()), now);
+                    applied.changed = true;
+                }
+                None => applied.messages.push(format!("edit: no task {}", id)),
+            },
+            Action::Status(message) => applied.messages.push(message),
+        }
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_script(source: &str) -> Plugins {
+        let mut plugins = Plugins::empty();
+        plugins.register_api();
+        let ast = plugins.engine.compile(source).unwrap();
+        plugins.engine.run_ast(&ast).unwrap();
+        plugins.scripts.push(ast);
+        plugins
+    }
+
+    #[test]
+    fn endless_loops_are_stopped() {
+        let plugins = with_script(r#"command("spin", "spin"); fn spin(args) { loop { } }"#);
+        let task = Task::parse("call mum", Local::now().date_naive());
+        assert!(plugins.run_command("spin", &[], &[task]).is_err());
+    }
+
+    #[test]
+    fn a_failing_decorator_is_turned_off() {
+        let plugins = with_script(r#"decorate("deep"); fn deep(task) { deep(task) }"#);
+        let task = Task::parse("call mum", Local::now().date_naive());
+        assert_eq!(plugins.decorate(&task), "");
+        let failed = plugins.take_failed();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].starts_with("deep: "));
+        assert_eq!(plugins.decorate(&task), "");
+        assert!(plugins.take_failed().is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::NaiveDate;
+
+use crate::task::{self, Task};
+
+// ANSI styles, matching the list pane
+const BOLD: &str = "1";
+const DIM: &str = "2;9";
+const RED: &str = "31";
+const BLUE: &str = "34";
+const CYAN: &str = "36";
+
+// `title`, then one row per todo with its columns (done box, priority, title,
+// project, contexts, due date, estimate) lined up; `color` adds ANSI styles
+pub fn render(title: &str, tasks: &[&Task], today: NaiveDate, color: bool) -> String {
+    let rows: Vec<Vec<(String, Option<&str>)>> = tasks
+        .iter()
+        .map(|t| {
+            let line = if t.is_done() {
+                Some(DIM)
+            } else if t.is_overdue(today) {
+                Some(RED)
+            } else {
+                None
+            };
+            vec![
+                (if t.is_done() { "[x]" } else { "[ ]" }.to_string(), line),
+                (t.priority.map(|p| format!("({})", p)).unwrap_or_default(), line.or(Some(BOLD))),
+                (t.title.to_string(), line),
+                (t.project.as_ref().map(|p| format!("+{}", p)).unwrap_or_default(), line.or(Some(BLUE))),
+                (t.contexts.iter().map(|c| format!("@{}", c)).collect::<Vec<_>>().join(" "), line.or(Some(CYAN))),
+                (t.due.map(|d| format!("due {}", d)).unwrap_or_default(), line),
+                (t.estimate.map(|m| format!("est {}", task::format_minutes(m))).unwrap_or_default(), line),
+            ]
+        })
+        .collect();
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns).map(|c| rows.iter().map(|r| r[c].0.chars().count()).max().unwrap_or(0)).collect();
+    let mut text = match color {
+        true => format!("\x1b[{}m{}\x1b[0m ({})\n\n", BOLD, title, tasks.len()),
+        false => format!("{} ({})\n\n", title, tasks.len()),
+    };
+    if tasks.is_empty() {
+        text.push_str("nothing here\n");
+    }
+    for row in rows {
+        let mut line = String::new();
+        // empty columns take no room, so most lists don't end up with gaps
+        for ((cell, style), &width) in row.iter().zip(&widths).filter(|(_, &w)| w > 0) {
+            match (color, style) {
+                (true, Some(style)) if !cell.is_empty() => line.push_str(&format!("\x1b[{}m{}\x1b[0m", style, cell)),
+                _ => line.push_str(cell),
+            }
+            line.push_str(&" ".repeat(width - cell.chars().count() + 2));
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,46 @@
+use chrono::NaiveDate;
+
+use crate::{filter::Filter, task::Task};
+
+// which archived todo `purge` deletes: `older-than 90d` (or `12w`) and
+// filter terms, e.g. `purge older-than 90d +work`
+pub struct Purge {
+    older_than: Option<i64>,
+    filter: Filter,
+}
+
+impl Purge {
+    pub fn parse(args: &[&str]) -> Result<Purge, String> {
+        let mut older_than = None;
+        let mut terms = vec![];
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            if arg != "older-than" {
+                terms.push(arg);
+                continue;
+            }
+            let age = args.next().ok_or("older-than needs an age, e.g. 90d")?;
+            older_than = Some(parse_days(age).ok_or_else(|| format!("not an age: {} (try 90d or 12w)", age))?);
+        }
+        if older_than.is_none() && terms.is_empty() {
+            return Err("purge needs older-than <age> or filter terms, it won't empty the whole archive".to_string());
+        }
+        Ok(Purge { older_than, filter: Filter::parse(&terms.join(" ")) })
+    }
+    pub fn matches(&self, task: &Task, today: NaiveDate, stale_days: i64) -> bool {
+        let old = match (self.older_than, task.completed) {
+            (Some(days), Some(done)) => (today - done.date()).num_days() > days,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        old && self.filter.matches(task, today, stale_days)
+    }
+}
+
+fn parse_days(age: &str) -> Option<i64> {
+    match (age.strip_suffix('d'), age.strip_suffix('w')) {
+        (Some(days), _) => days.parse().ok(),
+        (_, Some(weeks)) => weeks.parse::<i64>().ok().map(|w| w * 7),
+        _ => None,
+    }
+}
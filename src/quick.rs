@@ -0,0 +1,303 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+
+use crate::task;
+
+// what a stretch of quick entry text was read as
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Tag,
+    Priority(char),
+    Project,
+    // any other todo.txt `key:value` word
+    Field,
+}
+
+// a recognized stretch, by byte offsets into the typed text
+pub struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub kind: Kind,
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+// `Submit report tomorrow 3pm #work !high +ProjectX`: dates (`today`,
+// `tomorrow`, `on friday`, `next friday`, `next week`, `in 3 days`), times
+// (`3pm`, `at 9:30am`, `15:00`), `#tags`, `!high` priorities and `+projects`;
+// only the first date and time count. Words already in todo.txt form
+// (`due:2024-05-02`, `@work`, `(A)`, `est:30m`) are tokens too, and the only
+// ones without `natural`
+pub fn tokens(input: &str, today: NaiveDate, natural: bool) -> Vec<Token> {
+    let words: Vec<(usize, &str)> = words(input);
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < words.len() {
+        let (start, word) = words[i];
+        let rest: Vec<String> = words[i..].iter().take(3).map(|(_, w)| w.to_lowercase()).collect();
+        let rest: Vec<&str> = rest.iter().map(String::as_str).collect();
+        if !natural {
+            if let Some(kind) = literal(word) {
+                tokens.push(Token { start, end: start + word.len(), kind });
+            }
+            i += 1;
+            continue;
+        }
+        let found = match date(&rest, today) {
+            Some((date, n)) if !tokens.iter().any(|t: &Token| matches!(t.kind, Kind::Date(_))) => Some((Kind::Date(date), n)),
+            _ => None,
+        };
+        let timed = tokens.iter().any(|t| matches!(t.kind, Kind::Time(_)));
+        let found = found.or_else(|| match rest[..] {
+            ["at", word, ..] if !timed => Some((Kind::Time(time(word)?), 2)),
+            [word, ..] if !timed && time(word).is_some() => Some((Kind::Time(time(word)?), 1)),
+            _ => Some((single(word)?, 1)),
+        });
+        match found {
+            Some((kind, n)) => {
+                let (last, text) = words[i + n - 1];
+                tokens.push(Token { start, end: last + text.len(), kind });
+                i += n;
+            }
+            None => i += 1,
+        }
+    }
+    tokens
+}
+
+// the typed text with the recognized words turned into todo.txt ones: a date
+// into `due:`, a time into a `remind:` on the due day, `#tag` into `@tag`
+// and `!high` into `(A)`
+pub fn expand(input: &str, today: NaiveDate) -> String {
+    let mut text = String::new();
+    let mut at = 0;
+    for token in tokens(input, today, true) {
+        text.push_str(&input[at..token.start]);
+        let word = &input[token.start..token.end];
+        match token.kind {
+            Kind::Date(date) => text.push_str(&format!("due:{}", date)),
+            Kind::Time(time) => text.push_str(&format!("remind:{}", time.format("%H:%M"))),
+            Kind::Tag => text.push_str(&format!("@{}", &word[1..])),
+            Kind::Priority(p) => text.push_str(&format!("({})", p)),
+            Kind::Project | Kind::Field => text.push_str(word),
+        }
+        at = token.end;
+    }
+    text.push_str(&input[at..]);
+    text
+}
+
+fn words(input: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, c) in input.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                words.push((s, &input[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &input[s..]));
+    }
+    words
+}
+
+// a date from the words starting here, and how many words it took
+fn date(words: &[&str], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let weekday = |name: &str| WEEKDAYS.iter().find(|(n, _)| *n == name).map(|(_, day)| *day);
+    // the coming one, a week off when it is today
+    let next = |day: Weekday| {
+        let days = (day.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64).rem_euclid(7);
+        today + Duration::days(if days == 0 { 7 } else { days })
+    };
+    match words {
+        ["on" | "by", rest @ ..] => date(rest, today).map(|(date, n)| (date, n + 1)),
+        ["today", ..] => Some((today, 1)),
+        ["tomorrow", ..] => Some((today + Duration::days(1), 1)),
+        ["next", "week", ..] => Some((next(Weekday::Mon), 2)),
+        ["next", name, ..] => weekday(name).map(|day| (next(day), 2)),
+        ["in", n, unit, ..] => {
+            let n: i64 = n.parse().ok().filter(|n| (1..1000).contains(n))?;
+            let days = match *unit {
+                "day" | "days" => n,
+                "week" | "weeks" => n * 7,
+                _ => return None,
+            };
+            Some((today + Duration::days(days), 3))
+        }
+        [name, ..] => weekday(name).map(|day| (next(day), 1)),
+        [] => None,
+    }
+}
+
+// `3pm`, `9:30am`, `15:00`
+fn time(word: &str) -> Option<NaiveTime> {
+    let (clock, offset) = match word.strip_suffix("am").or_else(|| word.strip_suffix("a.m.")) {
+        Some(clock) => (clock, Some(0)),
+        None => match word.strip_suffix("pm").or_else(|| word.strip_suffix("p.m.")) {
+            Some(clock) => (clock, Some(12)),
+            None => (word, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // a bare number is only a time with am or pm
+        None if offset.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// words in todo.txt form, read as they are
+fn literal(word: &str) -> Option<Kind> {
+    if let Some(name) = word.strip_prefix('+') {
+        return (!name.is_empty()).then_some(Kind::Project);
+    }
+    if let Some(name) = word.strip_prefix('@') {
+        return (!name.is_empty()).then_some(Kind::Tag);
+    }
+    if let [b'(', p, b')'] = word.as_bytes() {
+        return p.is_ascii_uppercase().then_some(Kind::Priority(*p as char));
+    }
+    if let Some(date) = word.strip_prefix("due:") {
+        return NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().map(Kind::Date);
+    }
+    let (key, value) = word.split_once(':')?;
+    let known = ["remind", "est", "loc", "label"].contains(&key) && !value.is_empty();
+    (known || task::parse_field(word).is_some()).then_some(Kind::Field)
+}
+
+fn single(word: &str) -> Option<Kind> {
+    if let Some(kind) = literal(word) {
+        return Some(kind);
+    }
+    if let Some(tag) = word.strip_prefix('#') {
+        // `#42` is an issue number, not a tag
+        return tag.starts_with(|c: char| c.is_alphabetic()).then_some(Kind::Tag);
+    }
+    let priority = match word.strip_prefix('!')?.to_lowercase().as_str() {
+        "high" | "1" => 'A',
+        "medium" | "med" | "2" => 'B',
+        "low" | "3" => 'C',
+        p if p.len() == 1 && p.as_bytes()[0].is_ascii_lowercase() => p.to_ascii_uppercase().chars().next()?,
+        _ => return None,
+    };
+    Some(Kind::Priority(priority))
+}
+
+// what the tokens will set, for the line under the input:
+// `due Thu 2024-05-02 · at 15:00 · @work · (A) · +ProjectX`
+pub fn summary(input: &str, tokens: &[Token]) -> String {
+    let parts: Vec<String> = tokens
+        .iter()
+        .map(|token| match token.kind {
+            Kind::Date(date) => format!("due {}", date.format("%a %Y-%m-%d")),
+            Kind::Time(time) => format!("at {}", time.format("%H:%M")),
+            Kind::Tag => format!("@{}", &input[token.start + 1..token.end]),
+            Kind::Priority(p) => format!("({})", p),
+            Kind::Project | Kind::Field => input[token.start..token.end].to_string(),
+        })
+        .collect();
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a Monday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    fn due(input: &str) -> Option<NaiveDate> {
+        tokens(input, today(), true).iter().find_map(|t| match t.kind {
+            Kind::Date(date) => Some(date),
+            _ => None,
+        })
+    }
+
+    fn day(d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2026, 6, d)
+    }
+
+    #[test]
+    fn reads_the_example() {
+        let input = "Submit report tomorrow 3pm #work !high +ProjectX";
+        assert_eq!(expand(input, today()), "Submit report due:2026-06-02 remind:15:00 @work (A) +ProjectX");
+        let tokens = tokens(input, today(), true);
+        assert_eq!(summary(input, &tokens), "due Tue 2026-06-02 · at 15:00 · @work · (A) · +ProjectX");
+        let spans: Vec<&str> = tokens.iter().map(|t| &input[t.start..t.end]).collect();
+        assert_eq!(spans, ["tomorrow", "3pm", "#work", "!high", "+ProjectX"]);
+    }
+
+    #[test]
+    fn noon_and_midnight() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(time("12am"), at(0, 0));
+        assert_eq!(time("12pm"), at(12, 0));
+        assert_eq!(time("12:30am"), at(0, 30));
+        assert_eq!(time("1pm"), at(13, 0));
+        assert_eq!(time("9:30a.m."), at(9, 30));
+        assert_eq!(time("15:00"), at(15, 0));
+        assert_eq!(time("0am"), None);
+        assert_eq!(time("13pm"), None);
+        assert_eq!(time("24:00"), None);
+        assert_eq!(time("15"), None);
+    }
+
+    #[test]
+    fn weekdays_are_the_coming_ones() {
+        // today is a Monday, so Monday is a week off
+        assert_eq!(due("call mum next monday"), day(8));
+        assert_eq!(due("call mum monday"), day(8));
+        assert_eq!(due("call mum on Monday"), day(8));
+        assert_eq!(due("call mum next tuesday"), day(2));
+        assert_eq!(due("call mum sunday"), day(7));
+        assert_eq!(due("call mum next week"), day(8));
+    }
+
+    #[test]
+    fn days_and_weeks_ahead() {
+        assert_eq!(due("renew in 1 day"), day(2));
+        assert_eq!(due("renew in 3 days"), day(4));
+        assert_eq!(due("renew in 2 weeks"), day(15));
+        assert_eq!(due("renew in 0 days"), None);
+        assert_eq!(due("renew in 3 months"), None);
+        // only the first date counts
+        assert_eq!(expand("today or tomorrow", today()), "due:2026-06-01 or tomorrow");
+    }
+
+    #[test]
+    fn ordinary_words_stay_in_the_title() {
+        for input in ["put the cup on table", "fix #42", "meet at home", "buy 3 apples", "in the next room", "next steps", "!important"] {
+            assert_eq!(expand(input, today()), input);
+            assert!(tokens(input, today(), true).is_empty(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn without_natural_only_todo_txt_words() {
+        let input = "pay rent tomorrow #home @home (B) due:2026-06-05 est:30m +Flat";
+        let kinds: Vec<&str> = tokens(input, today(), false).iter().map(|t| &input[t.start..t.end]).collect();
+        assert_eq!(kinds, ["@home", "(B)", "due:2026-06-05", "est:30m", "+Flat"]);
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::task::Task;
+
+// tasks completed on `day`, in the order they were done
+pub fn done_on(tasks: &[Task], day: NaiveDate) -> Vec<&Task> {
+    let mut done: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| matches!(t.completed, Some(done) if done.date() == day))
+        .collect();
+    done.sort_by_key(|t| t.completed);
+    done
+}
+
+// how far back the burndown looks
+#[derive(Clone, Copy, PartialEq)]
+pub enum Window {
+    Week,
+    Month,
+}
+
+impl Window {
+    pub fn days(self) -> i64 {
+        match self {
+            Window::Week => 7,
+            Window::Month => 30,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Window::Week => "week",
+            Window::Month => "month",
+        }
+    }
+}
+
+// one day of the burndown: the todo open at its end, and those created and
+// completed since the window began
+pub struct Day {
+    pub date: NaiveDate,
+    pub open: usize,
+    pub created: usize,
+    pub completed: usize,
+}
+
+// the days of the window up to `today`
+pub fn burndown(tasks: &[&Task], today: NaiveDate, window: Window) -> Vec<Day> {
+    let start = today - Duration::days(window.days() - 1);
+    start
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .map(|date| {
+            let done_by = |t: &Task| t.completed.is_some_and(|done| done.date() <= date);
+            Day {
+                date,
+                open: tasks.iter().filter(|t| t.created <= date && !done_by(t)).count(),
+                created: tasks.iter().filter(|t| t.created >= start && t.created <= date).count(),
+                completed: tasks.iter().filter(|t| t.completed.is_some_and(|done| done.date() >= start) && done_by(t)).count(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Text,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Text => "txt",
+        }
+    }
+}
+
+// what got done on `day`, ready to paste into a standup
+pub fn daily_summary(tasks: &[Task], day: NaiveDate, format: Format) -> String {
+    let done = done_on(tasks, day);
+    let mut text = match format {
+        Format::Markdown => format!("# Done {}\n\n", day),
+        Format::Text => format!("Done {}\n", day),
+    };
+    if done.is_empty() {
+        text.push_str("Nothing completed.\n");
+    }
+    for task in done {
+        let project = task.project.as_ref().map(|p| format!(" +{}", p)).unwrap_or_default();
+        match format {
+            Format::Markdown => text.push_str(&format!("- {}{}\n", task.title, project)),
+            Format::Text => text.push_str(&format!("  * {}{}\n", task.title, project)),
+        }
+    }
+    text
+}
+
+// the last working day before `today`, so Monday looks back to Friday
+fn previous_workday(today: NaiveDate) -> NaiveDate {
+    let mut day = today.pred_opt().unwrap_or(today);
+    while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        day = day.pred_opt().unwrap_or(day);
+    }
+    day
+}
+
+// "done yesterday" and "planned today" for posting in chat
+pub fn standup(tasks: &[Task], today: NaiveDate) -> String {
+    let since = previous_workday(today);
+    let mut done: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| matches!(t.completed, Some(done) if done.date() >= since && done.date() < today))
+        .collect();
+    done.sort_by_key(|t| t.completed);
+    let planned: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !t.is_done() && matches!(t.due, Some(due) if due <= today))
+        .collect();
+
+    let mut text = format!("## Done since {}\n", since.format("%a"));
+    push_list(&mut text, &done);
+    text.push_str("\n## Planned today\n");
+    push_list(&mut text, &planned);
+    text
+}
+
+fn push_list(text: &mut String, tasks: &[&Task]) {
+    if tasks.is_empty() {
+        text.push_str("- nothing\n");
+    }
+    for task in tasks {
+        let project = task.project.as_ref().map(|p| format!(" +{}", p)).unwrap_or_default();
+        text.push_str(&format!("- {}{}\n", task.title, project));
+    }
+}
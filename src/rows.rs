@@ -0,0 +1,121 @@
+use chrono::NaiveDate;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::ListItem,
+};
+
+use crate::{label_color, report, sorting::SortMode, task, urgency, App, View};
+
+// a row of the todo list as it is drawn, worked out when the list changes
+// rather than on every frame
+pub struct Row {
+    // a day or group header first, when the row starts one
+    pub lines: Vec<Line<'static>>,
+    pub style: Style,
+    // of the todo's own line, 0 for a folded group
+    pub width: usize,
+}
+
+impl Row {
+    pub fn item(&self) -> ListItem<'static> {
+        ListItem::new(self.lines.clone()).style(self.style)
+    }
+}
+
+// the rows of the list in `app.items`, filtered, sorted and grouped already
+pub fn build(app: &App, today: NaiveDate) -> Vec<Row> {
+    let show_project = !matches!(app.view(), View::Project(_));
+    let done_log = matches!(app.view(), View::DoneLog);
+    let mut prev_day = None;
+    let by_location = app.group.is_none() && app.shown_sort() == SortMode::Location && !done_log;
+    let mut prev_group = None;
+    app.items
+        .items
+        .iter()
+        .map(|&i| &app.tasks[i])
+        .map(|i| {
+            let mut lines = vec![];
+            let mut spans = vec![];
+            // the done log starts a new group for each day
+            if let (true, Some(done)) = (done_log, i.completed) {
+                if prev_day != Some(done.date()) {
+                    prev_day = Some(done.date());
+                    let day = report::done_on(&app.tasks, done.date());
+                    let minutes = day.iter().filter_map(|t| t.estimate).fold(0, u32::saturating_add);
+                    let effort = if minutes > 0 { format!(", est {}", task::format_minutes(minutes)) } else { String::new() };
+                    lines.push(Line::from(Span::styled(
+                        format!("{} ({} done{})", done.format("%a %Y-%m-%d"), day.len(), effort),
+                        Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    )));
+                }
+                spans.push(Span::raw(format!("{}  ", done.format("%H:%M"))));
+            }
+            // and a grouped list one for each group; a folded group is
+            // just that
+            let group = app.group_of(i, today).map(|(_, name)| name);
+            if let (Some(name), true) = (&group, group != prev_group) {
+                let folded = app.folded.contains(name);
+                lines.push(Line::from(Span::styled(
+                    format!("{} {} ({})", if folded { "▸" } else { "▾" }, name, app.group_len(name, today)),
+                    Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                )));
+                prev_group = group;
+                if folded {
+                    return Row { lines, style: Style::default().fg(Color::Black).bg(Color::White), width: 0 };
+                }
+            }
+            if app.marked.contains(&i.id) {
+                spans.push(Span::styled("* ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+            }
+            if let Some(label) = i.label {
+                spans.push(Span::styled("● ", Style::default().fg(label_color(label))));
+            }
+            if let Some(p) = i.priority {
+                spans.push(Span::styled(format!("({}) ", p), Style::default().add_modifier(Modifier::BOLD)));
+            }
+            spans.push(Span::raw(i.title.to_string()));
+            if let Some(progress) = i.progress() {
+                spans.push(Span::styled(format!(" {}", progress), Style::default().fg(Color::DarkGray)));
+            }
+            if let (true, Some(project)) = (show_project, &i.project) {
+                spans.push(Span::styled(format!("  +{}", project), Style::default().fg(Color::Blue)));
+            }
+            for context in &i.contexts {
+                spans.push(Span::styled(format!("  @{}", context), Style::default().fg(Color::Cyan)));
+            }
+            if let Some(due) = i.due {
+                spans.push(Span::raw(format!("  due: {}", due)));
+            }
+            if let Some(minutes) = i.estimate {
+                spans.push(Span::raw(format!("  est: {}", task::format_minutes(minutes))));
+            }
+            if let (false, Some(place)) = (by_location, &i.location) {
+                spans.push(Span::raw(format!("  loc: {}", place)));
+            }
+            if app.show_age {
+                spans.push(Span::raw(format!("  {}", i.age(today))));
+            }
+            if let (SortMode::Urgency, false) = (app.shown_sort(), i.is_done()) {
+                spans.push(Span::styled(format!("  {:.1}", urgency::score(i, today, &app.config.urgency)), Style::default().fg(Color::DarkGray)));
+            }
+            let decoration = app.plugins.decorate(i);
+            if !decoration.is_empty() {
+                spans.push(Span::styled(decoration, Style::default().fg(Color::Magenta)));
+            }
+            let style = if i.is_done() && !done_log {
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)
+            } else if i.is_overdue(today) {
+                Style::default().fg(Color::Red)
+            } else if i.is_stale(today, app.config.stale_days) {
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)
+            } else {
+                Style::default().fg(Color::Black)
+            };
+            let spans = Line::from(spans);
+            let width = spans.width();
+            lines.push(spans);
+            Row { lines, style: style.bg(Color::White), width }
+        })
+        .collect()
+}
@@ -0,0 +1,184 @@
+use crate::task::Task;
+
+// `[rules]` in the config: `errands = "if tag=errand then project=Personal"`,
+// checked in order on every added or imported todo
+pub struct Rule {
+    pub name: String,
+    when: Vec<Condition>,
+    then: Vec<Change>,
+}
+
+enum Condition {
+    Tag(String),
+    Project(String),
+    Priority(char),
+    TitleContains(String),
+    Field(String, String),
+}
+
+enum Change {
+    Tag(String),
+    Project(String),
+    Priority(char),
+    Field(String, String),
+}
+
+impl Rule {
+    // `if <condition> and <condition> then <change>, <change>`; None when
+    // any part doesn't parse
+    pub fn parse(name: &str, text: &str) -> Option<Rule> {
+        let text = text.trim().strip_prefix("if ")?;
+        let (when, then) = text.split_once(" then ")?;
+        let when = when.split(" and ").map(condition).collect::<Option<Vec<_>>>()?;
+        let then = then.split(',').map(change).collect::<Option<Vec<_>>>()?;
+        Some(Rule { name: name.to_string(), when, then })
+    }
+    pub fn matches(&self, task: &Task) -> bool {
+        self.when.iter().all(|condition| match condition {
+            Condition::Tag(tag) => task.contexts.iter().any(|c| c.eq_ignore_ascii_case(tag)),
+            Condition::Project(project) => task.project.as_ref().is_some_and(|p| p.eq_ignore_ascii_case(project)),
+            Condition::Priority(p) => task.priority == Some(*p),
+            Condition::TitleContains(text) => task.title.to_lowercase().contains(&text.to_lowercase()),
+            Condition::Field(key, value) => task.field(key) == Some(value.as_str()),
+        })
+    }
+    fn apply(&self, task: &mut Task) {
+        for change in &self.then {
+            match change {
+                Change::Tag(tag) => {
+                    if !task.contexts.contains(tag) {
+                        task.contexts.push(tag.to_string());
+                    }
+                }
+                Change::Project(project) => task.project = Some(project.to_string()),
+                Change::Priority(p) => task.priority = Some(*p),
+                Change::Field(key, value) => match task.fields.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, v)) => *v = value.to_string(),
+                    None => task.fields.push((key.to_string(), value.to_string())),
+                },
+            }
+        }
+    }
+}
+
+// run every matching rule on the todo, later rules seeing what earlier ones
+// changed; the names of the rules that fired
+pub fn apply(rules: &[Rule], task: &mut Task) -> Vec<String> {
+    let mut fired = vec![];
+    for rule in rules {
+        if rule.matches(task) {
+            rule.apply(task);
+            fired.push(rule.name.to_string());
+        }
+    }
+    fired
+}
+
+fn condition(text: &str) -> Option<Condition> {
+    let text = text.trim();
+    if let Some(quoted) = text.strip_prefix("title contains ") {
+        return Some(Condition::TitleContains(unquote(quoted.trim())?));
+    }
+    let (key, value) = pair(text)?;
+    Some(match key.as_str() {
+        "tag" | "context" => Condition::Tag(tag(&value)),
+        "project" => Condition::Project(value.trim_start_matches('+').to_string()),
+        "priority" => Condition::Priority(priority(&value)?),
+        _ => Condition::Field(key, value),
+    })
+}
+
+fn change(text: &str) -> Option<Change> {
+    let (key, value) = pair(text.trim())?;
+    Some(match key.as_str() {
+        "tag" | "context" => Change::Tag(tag(&value)),
+        "project" => Change::Project(value.trim_start_matches('+').to_string()),
+        "priority" => Change::Priority(priority(&value)?),
+        _ => Change::Field(key, value),
+    })
+}
+
+// `key=value`, the value quoted when it has spaces
+fn pair(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once('=')?;
+    let value = value.trim();
+    let value = unquote(value).unwrap_or_else(|| value.to_string());
+    Some((key.trim().to_lowercase(), value)).filter(|(key, value)| !key.is_empty() && !value.is_empty())
+}
+
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\''));
+    let inner = inner.or_else(|| text.strip_prefix('"').and_then(|t| t.strip_suffix('"')))?;
+    Some(inner.to_string())
+}
+
+// `#errand` and `@errand` are both the context
+fn tag(value: &str) -> String {
+    value.trim_start_matches(['#', '@']).to_string()
+}
+
+// a letter, or high, medium and low for A, B and C
+fn priority(value: &str) -> Option<char> {
+    match value.to_lowercase().as_str() {
+        "high" => Some('A'),
+        "medium" => Some('B'),
+        "low" => Some('C'),
+        _ => {
+            let mut chars = value.chars();
+            chars.next().filter(|c| c.is_ascii_uppercase() && chars.next().is_none())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn task(text: &str) -> Task {
+        Task::parse(text, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+    }
+
+    #[test]
+    fn tag_sets_project() {
+        let rules = [Rule::parse("errands", "if tag=#errand then project=Personal").unwrap()];
+        let mut errand = task("buy milk @errand");
+        assert_eq!(apply(&rules, &mut errand), ["errands"]);
+        assert_eq!(errand.project.as_deref(), Some("Personal"));
+        let mut other = task("write report @office");
+        assert!(apply(&rules, &mut other).is_empty());
+        assert_eq!(other.project, None);
+    }
+
+    #[test]
+    fn title_sets_priority_and_field() {
+        let rules = [Rule::parse("reviews", "if title contains 'review PR' and project=Work then priority=high, kind=review").unwrap()];
+        let mut review = task("Review pr 42 +Work");
+        apply(&rules, &mut review);
+        assert_eq!(review.priority, Some('A'));
+        assert_eq!(review.field("kind"), Some("review"));
+        let mut elsewhere = task("review PR 7 +Home");
+        apply(&rules, &mut elsewhere);
+        assert_eq!(elsewhere.priority, None);
+    }
+
+    #[test]
+    fn later_rules_see_earlier_changes() {
+        let rules = [
+            Rule::parse("errands", "if tag=errand then project=Personal").unwrap(),
+            Rule::parse("personal", "if project=Personal then tag=home").unwrap(),
+        ];
+        let mut errand = task("post letter @errand");
+        assert_eq!(apply(&rules, &mut errand), ["errands", "personal"]);
+        assert_eq!(errand.contexts, ["errand", "home"]);
+    }
+
+    #[test]
+    fn bad_rules_are_rejected() {
+        assert!(Rule::parse("a", "tag=errand then project=Personal").is_none());
+        assert!(Rule::parse("b", "if tag=errand").is_none());
+        assert!(Rule::parse("c", "if priority=urgent then tag=x").is_none());
+        assert!(Rule::parse("d", "if title contains review then tag=x").is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+
+use crate::{
+    task::Task,
+    urgency::{self, Coefficients},
+};
+
+// one term of a sort expression: `due asc`, `priority desc`, or any custom
+// field, `pinned desc`
+#[derive(Clone)]
+pub struct Key {
+    field: String,
+    descending: bool,
+}
+
+// `"pinned desc, due asc, priority desc"`, ascending when not said; terms
+// that don't parse are left out
+pub fn parse(text: &str) -> Vec<Key> {
+    text.split(',')
+        .filter_map(|term| {
+            let mut words = term.split_whitespace();
+            let field = words.next()?.to_lowercase();
+            let descending = match words.next() {
+                None | Some("asc") => false,
+                Some("desc") => true,
+                Some(_) => return None,
+            };
+            Some(Key { field, descending }).filter(|_| words.next().is_none())
+        })
+        .collect()
+}
+
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+// what a todo sorts by for `field`; `priority desc` puts (A) first
+fn value(task: &Task, field: &str, today: NaiveDate, coefficients: &Coefficients) -> Option<Value> {
+    let date = |date: NaiveDate| Value::Text(date.to_string());
+    match field {
+        "due" => task.due.map(date),
+        "priority" => task.priority.map(|p| Value::Number(f64::from(b'Z' - p as u8))),
+        "created" | "age" => Some(date(task.created)),
+        "touched" => Some(Value::Text(task.touched.to_string())),
+        "completed" | "done" => task.completed.map(|done| Value::Text(done.to_string())),
+        "title" => Some(Value::Text(task.title.to_lowercase())),
+        "project" => task.project.as_ref().map(|p| Value::Text(p.to_lowercase())),
+        "label" => task.label.map(|l| Value::Number(l as u8 as f64)),
+        "location" => task.location.as_ref().map(|l| Value::Text(l.to_lowercase())),
+        "estimate" | "est" => task.estimate.map(|minutes| Value::Number(minutes as f64)),
+        "urgency" => Some(Value::Number(urgency::score(task, today, coefficients))),
+        _ => task.field(field).map(|v| match v.parse() {
+            Ok(n) => Value::Number(n),
+            Err(_) => Value::Text(v.to_lowercase()),
+        }),
+    }
+}
+
+// term by term; a todo without the value goes last either way
+pub fn compare(keys: &[Key], a: &Task, b: &Task, today: NaiveDate, coefficients: &Coefficients) -> Ordering {
+    for key in keys {
+        let order = match (value(a, &key.field, today, coefficients), value(b, &key.field, today, coefficients)) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => {
+                let order = match (a, b) {
+                    (Value::Number(a), Value::Number(b)) => a.total_cmp(&b),
+                    (Value::Text(a), Value::Text(b)) => a.cmp(&b),
+                    (Value::Number(_), Value::Text(_)) => Ordering::Less,
+                    (Value::Text(_), Value::Number(_)) => Ordering::Greater,
+                };
+                if key.descending { order.reverse() } else { order }
+            }
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    Ordering::Equal
+}
+
+// the orders s cycles through
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortMode {
+    Manual,
+    Age,
+    Due,
+    Label,
+    // grouped by location, for errands
+    Location,
+    // the most urgent first, see `urgency::score`
+    Urgency,
+    // the config's sort expression
+    Custom,
+}
+
+impl SortMode {
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Manual => SortMode::Age,
+            SortMode::Age => SortMode::Due,
+            SortMode::Due => SortMode::Label,
+            SortMode::Label => SortMode::Location,
+            SortMode::Location => SortMode::Urgency,
+            SortMode::Urgency => SortMode::Custom,
+            SortMode::Custom => SortMode::Manual,
+        }
+    }
+    pub fn parse(name: &str) -> Option<SortMode> {
+        [SortMode::Manual, SortMode::Age, SortMode::Due, SortMode::Label, SortMode::Location, SortMode::Urgency].into_iter().find(|s| s.name() == name)
+    }
+    pub fn order(self, a: &Task, b: &Task, today: NaiveDate, coefficients: &Coefficients, keys: &[Key]) -> Ordering {
+        match self {
+            SortMode::Manual => Ordering::Equal,
+            SortMode::Age => a.created.cmp(&b.created),
+            SortMode::Due => (a.due.is_none(), a.due).cmp(&(b.due.is_none(), b.due)),
+            SortMode::Label => (a.label.is_none(), a.label).cmp(&(b.label.is_none(), b.label)),
+            SortMode::Location => {
+                let key = |t: &Task| (t.location.is_none(), t.location.as_ref().map(|l| l.to_lowercase()));
+                key(a).cmp(&key(b))
+            }
+            SortMode::Urgency => urgency::score(b, today, coefficients).total_cmp(&urgency::score(a, today, coefficients)),
+            SortMode::Custom => compare(keys, a, b, today, coefficients),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Age => "age",
+            SortMode::Due => "due",
+            SortMode::Label => "label",
+            SortMode::Location => "location",
+            SortMode::Urgency => "urgency",
+            SortMode::Custom => "custom",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    fn sorted<'a>(lines: &[&'a str], by: impl Fn(&Task, &Task) -> Ordering) -> Vec<&'a str> {
+        let tasks: Vec<Task> = lines.iter().map(|line| Task::parse(line, today())).collect();
+        let mut order: Vec<usize> = (0..lines.len()).collect();
+        order.sort_by(|&a, &b| by(&tasks[a], &tasks[b]));
+        order.into_iter().map(|i| lines[i]).collect()
+    }
+
+    fn terms(keys: &[Key]) -> Vec<(&str, bool)> {
+        keys.iter().map(|k| (k.field.as_str(), k.descending)).collect()
+    }
+
+    #[test]
+    fn parses_terms() {
+        assert_eq!(terms(&parse("pinned desc, due asc, priority")), [("pinned", true), ("due", false), ("priority", false)]);
+        assert_eq!(terms(&parse("Due desc")), [("due", true)]);
+        // a term that doesn't parse is left out, the rest still count
+        assert_eq!(terms(&parse("due sideways, , title asc again, est")), [("est", false)]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn missing_values_go_last_both_ways() {
+        let lines = ["no priority", "(B) second", "(A) first"];
+        let by = |text: &str| {
+            let keys = parse(text);
+            sorted(&lines, move |a, b| compare(&keys, a, b, today(), &Coefficients::default()))
+        };
+        assert_eq!(by("priority desc"), ["(A) first", "(B) second", "no priority"]);
+        assert_eq!(by("priority asc"), ["(B) second", "(A) first", "no priority"]);
+    }
+
+    #[test]
+    fn custom_fields_sort_numbers_before_text() {
+        let lines = ["c size:10", "a size:9", "b size:big", "d"];
+        let keys = parse("size, title");
+        assert_eq!(sorted(&lines, |a, b| compare(&keys, a, b, today(), &Coefficients::default())), ["a size:9", "c size:10", "b size:big", "d"]);
+    }
+
+    #[test]
+    fn later_terms_break_ties() {
+        let lines = ["b due:2026-06-02", "c", "a due:2026-06-02"];
+        let keys = parse("due, title desc");
+        assert_eq!(sorted(&lines, |a, b| compare(&keys, a, b, today(), &Coefficients::default())), ["b due:2026-06-02", "a due:2026-06-02", "c"]);
+    }
+
+    fn by_mode<'a>(mode: SortMode, lines: &[&'a str]) -> Vec<&'a str> {
+        sorted(lines, |a, b| mode.order(a, b, today(), &Coefficients::default(), &[]))
+    }
+
+    #[test]
+    fn modes_order_the_list() {
+        let lines = ["c", "b due:2026-06-09", "a due:2026-06-02"];
+        // a stable sort, so manual leaves todo.txt's order
+        assert_eq!(by_mode(SortMode::Manual, &lines), lines);
+        assert_eq!(by_mode(SortMode::Due, &lines), ["a due:2026-06-02", "b due:2026-06-09", "c"]);
+        let lines = ["none", "blue label:blue", "red label:red"];
+        assert_eq!(by_mode(SortMode::Label, &lines), ["red label:red", "blue label:blue", "none"]);
+        let lines = ["home", "shop loc:Shop", "bank loc:bank"];
+        assert_eq!(by_mode(SortMode::Location, &lines), ["bank loc:bank", "shop loc:Shop", "home"]);
+        let lines = ["later", "(A) now due:2026-06-01"];
+        assert_eq!(by_mode(SortMode::Urgency, &lines), ["(A) now due:2026-06-01", "later"]);
+    }
+
+    #[test]
+    fn modes_cycle_and_parse_by_name() {
+        let mut mode = SortMode::Manual;
+        let mut names = vec![];
+        loop {
+            names.push(mode.name());
+            mode = mode.next();
+            if mode == SortMode::Manual {
+                break;
+            }
+        }
+        assert_eq!(names, ["manual", "age", "due", "label", "location", "urgency", "custom"]);
+        for name in &names[..6] {
+            assert!(SortMode::parse(name).is_some_and(|mode| mode.name() == *name));
+        }
+        // custom is the config's expression, not a name to give
+        assert!(SortMode::parse("custom").is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::BTreeMap, fs, io};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::paths;
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+// small `key=value` file for things remembered between runs
+#[derive(Default)]
+pub struct State {
+    pub version: Option<String>,
+    // last successful sync with each peer
+    pub synced: BTreeMap<String, NaiveDateTime>,
+    // per remote service, local task id -> the service's id for it
+    pub links: BTreeMap<String, BTreeMap<String, String>>,
+    // the day the overdue hook last ran
+    pub overdue_hooked: Option<NaiveDate>,
+    // the sidebar's project order, including projects without todo yet
+    pub projects: Vec<String>,
+    // projects hidden from the other views
+    pub archived: Vec<String>,
+    // pane widths set with Ctrl+arrows, over the config's
+    pub sidebar_width: Option<u16>,
+    pub detail_width: Option<u16>,
+    // jump marks, a letter -> the todo's id
+    pub marks: BTreeMap<char, String>,
+}
+
+impl State {
+    pub fn load() -> io::Result<State> {
+        let text = match fs::read_to_string(paths::state_file()) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(State::default()),
+            Err(err) => return Err(err),
+        };
+        let mut state = State::default();
+        for line in text.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let (key, value) = (key.trim(), value.trim());
+                if key == "version" {
+                    state.version = Some(value.to_string());
+                } else if key == "projects.order" {
+                    state.projects = value.split_whitespace().map(str::to_string).collect();
+                } else if key == "projects.archived" {
+                    state.archived = value.split_whitespace().map(str::to_string).collect();
+                } else if key == "layout.sidebar" {
+                    state.sidebar_width = value.parse().ok();
+                } else if key == "layout.detail" {
+                    state.detail_width = value.parse().ok();
+                } else if key == "hooks.overdue" {
+                    state.overdue_hooked = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+                } else if let Some(mark) = key.strip_prefix("mark.").and_then(|k| k.parse().ok()) {
+                    state.marks.insert(mark, value.to_string());
+                } else if let Some(peer) = key.strip_prefix("synced.") {
+                    if let Ok(time) = NaiveDateTime::parse_from_str(value, TIME_FORMAT) {
+                        state.synced.insert(peer.to_string(), time);
+                    }
+                } else if let Some((service, id)) = key.strip_prefix("link.").and_then(|k| k.split_once('.')) {
+                    state.links.entry(service.to_string()).or_default().insert(id.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(state)
+    }
+    pub fn save(&self) -> io::Result<()> {
+        let mut text = String::new();
+        if let Some(version) = &self.version {
+            text.push_str(&format!("version={}\n", version));
+        }
+        if let Some(day) = self.overdue_hooked {
+            text.push_str(&format!("hooks.overdue={}\n", day));
+        }
+        if !self.projects.is_empty() {
+            text.push_str(&format!("projects.order={}\n", self.projects.join(" ")));
+        }
+        if !self.archived.is_empty() {
+            text.push_str(&format!("projects.archived={}\n", self.archived.join(" ")));
+        }
+        if let Some(width) = self.sidebar_width {
+            text.push_str(&format!("layout.sidebar={}\n", width));
+        }
+        if let Some(width) = self.detail_width {
+            text.push_str(&format!("layout.detail={}\n", width));
+        }
+        for (mark, id) in &self.marks {
+            text.push_str(&format!("mark.{}={}\n", mark, id));
+        }
+        for (peer, time) in &self.synced {
+            text.push_str(&format!("synced.{}={}\n", peer, time.format(TIME_FORMAT)));
+        }
+        for (service, links) in &self.links {
+            for (id, remote) in links {
+                text.push_str(&format!("link.{}.{}={}\n", service, id, remote));
+            }
+        }
+        fs::create_dir_all(paths::data_dir())?;
+        fs::write(paths::state_file(), text)
+    }
+}
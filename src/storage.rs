@@ -0,0 +1,113 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use chrono::NaiveDate;
+
+use crate::{format, paths, task::Task};
+
+pub fn load(today: NaiveDate) -> io::Result<Vec<Task>> {
+    read(&paths::todo_file(), today)
+}
+
+pub fn load_archive(today: NaiveDate) -> io::Result<Vec<Task>> {
+    read(&paths::archive_file(), today)
+}
+
+// a file in an older format is upgraded in place, after copying it to
+// `<file>.v<format>.bak`
+fn read(path: &Path, today: NaiveDate) -> io::Result<Vec<Task>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let version = format::version(&text);
+    if version > format::VERSION {
+        return Err(io::Error::other(format!(
+            "{} is in format {}, this todo-tui only knows up to {}; upgrade it",
+            path.display(),
+            version,
+            format::VERSION
+        )));
+    }
+    let body = format::upgrade(&text);
+    if version < format::VERSION && !body.is_empty() {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", version));
+        fs::copy(path, backup)?;
+        write_text(path, &(format::header() + body.as_str()))?;
+    }
+    Ok(body.lines().map(|line| Task::from_line(line, today)).collect())
+}
+
+fn write(path: &Path, tasks: &[Task]) -> io::Result<()> {
+    let mut text = format::header();
+    for task in tasks {
+        text.push_str(&task.to_line());
+        text.push('\n');
+    }
+    fs::create_dir_all(paths::data_dir())?;
+    write_text(path, &text)
+}
+
+// through a temporary file, so a crash leaves the old file or the new one
+fn write_text(path: &Path, text: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+}
+
+// a token or password, readable by the owner alone
+pub fn write_secret(path: &Path, secret: &str) -> io::Result<()> {
+    fs::create_dir_all(paths::data_dir())?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // the mode only applies to new files
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(secret.as_bytes())
+}
+
+pub fn save(tasks: &[Task]) -> io::Result<()> {
+    write(&paths::todo_file(), tasks)
+}
+
+// add completed todo to done.txt; ids already there are skipped, so an undo
+// that brings some back doesn't archive them twice
+pub fn archive(tasks: &[Task], today: NaiveDate) -> io::Result<()> {
+    let mut archived = load_archive(today)?;
+    let new: Vec<Task> = tasks.iter().filter(|t| !archived.iter().any(|a| a.id == t.id)).cloned().collect();
+    archived.extend(new);
+    save_archive(&archived)
+}
+
+pub fn save_archive(tasks: &[Task]) -> io::Result<()> {
+    write(&paths::archive_file(), tasks)
+}
+
+// add one line to the end without rewriting the file
+pub fn append(task: &Task) -> io::Result<()> {
+    fs::create_dir_all(paths::data_dir())?;
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(paths::todo_file())?;
+    let mut line = String::new();
+    if file.seek(SeekFrom::End(0))? == 0 {
+        line.push_str(&format::header());
+    } else {
+        let mut last = [0];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            line.push('\n');
+        }
+    }
+    line.push_str(&task.to_line());
+    line.push('\n');
+    file.write_all(line.as_bytes())
+}
@@ -0,0 +1,464 @@
+use std::{
+    fs, io,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+
+use crate::{
+    api,
+    config::Config,
+    format, git, http,
+    mstodo::MsTodo,
+    paths,
+    state::State,
+    storage,
+    task::Task,
+    todoist::Todoist,
+    webdav::WebDav,
+};
+
+pub struct Merge {
+    pub tasks: Vec<Task>,
+    // changes taken from the other side
+    pub pulled: usize,
+    // changes the other side was missing
+    pub pushed: usize,
+    // edited on both sides since the last sync; the later edit won for now
+    pub conflicts: Vec<Conflict>,
+}
+
+// both versions of a task edited on both sides
+pub struct Conflict {
+    pub local: Task,
+    pub remote: Task,
+}
+
+// join two copies of the list by task id, against `base`, the merged list of
+// the last sync. With a base a side changed a task when its copy differs
+// from the base's, and deleted it when it is gone from that side; a delete
+// wins over the other side leaving the task alone and loses to an edit
+// there. Without one, on the first sync, a side changed a task when it
+// touched it after `last_sync` and nothing counts as deleted
+pub fn merge(local: &[Task], remote: &[Task], base: Option<&[Task]>, last_sync: Option<NaiveDateTime>) -> Merge {
+    let line = |tasks: &[Task], id: &str| tasks.iter().find(|t| t.id == id).map(Task::to_line);
+    let changed = |task: &Task| match base {
+        Some(base) => line(base, &task.id) != Some(task.to_line()),
+        None => last_sync.is_none_or(|t| task.touched > t),
+    };
+    let deleted = |task: &Task| base.is_some_and(|base| line(base, &task.id).is_some()) && !changed(task);
+    let mut merge = Merge { tasks: vec![], pulled: 0, pushed: 0, conflicts: vec![] };
+    for l in local {
+        let r = match remote.iter().find(|r| r.id == l.id) {
+            Some(r) => r,
+            None if deleted(l) => {
+                merge.pulled += 1;
+                continue;
+            }
+            None => {
+                merge.pushed += 1;
+                merge.tasks.push(l.clone());
+                continue;
+            }
+        };
+        if l.to_line() == r.to_line() {
+            merge.tasks.push(l.clone());
+            continue;
+        }
+        let take_remote = match (changed(l), changed(r)) {
+            (false, true) => true,
+            (true, false) => false,
+            (both, _) => {
+                if both {
+                    merge.conflicts.push(Conflict { local: l.clone(), remote: r.clone() });
+                }
+                r.touched > l.touched
+            }
+        };
+        if take_remote {
+            merge.pulled += 1;
+            merge.tasks.push(r.clone());
+        } else {
+            merge.pushed += 1;
+            merge.tasks.push(l.clone());
+        }
+    }
+    for r in remote.iter().filter(|r| !local.iter().any(|l| l.id == r.id)) {
+        if deleted(r) {
+            merge.pushed += 1;
+        } else {
+            merge.pulled += 1;
+            merge.tasks.push(r.clone());
+        }
+    }
+    merge
+}
+
+pub fn to_text(tasks: &[Task]) -> String {
+    tasks.iter().map(|t| t.to_line() + "\n").collect()
+}
+
+// also reads a todo.txt with its format header, upgrading older lines
+pub fn from_text(text: &str, today: NaiveDate) -> Vec<Task> {
+    format::upgrade(text).lines().map(|l| Task::from_line(l, today)).collect()
+}
+
+// one place the list is kept in sync with: a peer, a WebDAV file, a service
+pub trait SyncProvider: Send {
+    // also the key its last sync time and base copy are stored under
+    fn name(&self) -> String;
+    // the provider's copy of the list; services that keep only some fields
+    // fill in the rest from `known`, the list as of the last sync
+    fn pull(&mut self, known: &[Task]) -> io::Result<Vec<Task>>;
+    // bring the provider in line with the merged list, deleting what isn't
+    // in it
+    fn push(&mut self, tasks: &[Task]) -> io::Result<()>;
+    // whether the provider keeps `task` at all, as of the last pull: services
+    // that keep only some projects or some todo lack the rest, which doesn't
+    // make them deleted there
+    fn holds(&self, _task: &Task) -> bool {
+        true
+    }
+}
+
+// the merged list after the last successful sync with `name`
+fn load_base(name: &str, today: NaiveDate) -> io::Result<Option<Vec<Task>>> {
+    match fs::read_to_string(paths::sync_base(name)) {
+        Ok(text) => Ok(Some(from_text(&text, today))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// pull, merge, push; the caller saves the merged list
+pub fn once(provider: &mut dyn SyncProvider, local: &[Task]) -> io::Result<Merge> {
+    let now = Local::now().naive_local();
+    let name = provider.name();
+    let last = State::load()?.synced.get(&name).copied();
+    let base = load_base(&name, now.date())?;
+    let mut known = base.clone().unwrap_or_default();
+    let missing: Vec<Task> = local.iter().filter(|l| !known.iter().any(|k| k.id == l.id)).cloned().collect();
+    known.extend(missing);
+    let mut remote = provider.pull(&known)?;
+    if let (Some(last), Some(base)) = (last, &base) {
+        for r in &mut remote {
+            // services without edit times: what differs from the base changed there
+            if r.touched <= last && base.iter().find(|b| b.id == r.id).is_none_or(|b| b.to_line() != r.to_line()) {
+                r.touched = now;
+            }
+        }
+    }
+    let pulled = remote.clone();
+    if let Some(base) = &base {
+        // what the provider doesn't keep is as it was, as far as it knows
+        let kept: Vec<Task> = base.iter().filter(|b| !provider.holds(b) && !remote.iter().any(|r| r.id == b.id)).cloned().collect();
+        remote.extend(kept);
+    }
+    let mut merge = merge(local, &remote, base.as_deref(), last);
+    if let Some(base) = &base {
+        // providers that keep only part of the list lack the rest on every run;
+        // count what is new since the last sync instead, and what went
+        let line = |tasks: &[Task], id: &str| tasks.iter().find(|t| t.id == id).map(Task::to_line);
+        let changed = merge
+            .tasks
+            .iter()
+            .filter(|t| Some(t.to_line()) != line(base, &t.id) && Some(t.to_line()) != line(&pulled, &t.id))
+            .count();
+        let deleted = pulled.iter().filter(|r| line(base, &r.id).is_some() && line(&merge.tasks, &r.id).is_none()).count();
+        merge.pushed = changed + deleted;
+    }
+    provider.push(&merge.tasks)?;
+    fs::create_dir_all(paths::sync_base(&name).parent().unwrap_or(&paths::data_dir()))?;
+    fs::write(paths::sync_base(&name), to_text(&merge.tasks))?;
+    // reloaded, `push` may have stored links
+    let mut state = State::load()?;
+    state.synced.insert(name, now);
+    state.save()?;
+    Ok(merge)
+}
+
+// a single sync from the command line
+pub fn run(provider: &mut dyn SyncProvider) -> io::Result<Merge> {
+    let merge = once(provider, &storage::load(Local::now().date_naive())?)?;
+    storage::save(&merge.tasks)?;
+    git::commit_if_enabled(&Config::load()?, &format!("sync with {}", provider.name()))?;
+    Ok(merge)
+}
+
+pub struct Peer {
+    addr: String,
+    token: String,
+    // the peer's list as last pulled, to skip pushing it back unchanged
+    seen: String,
+    // its version, which a push has to be based on
+    etag: Option<String>,
+}
+
+impl Peer {
+    pub fn new(addr: &str, token: Option<&str>) -> io::Result<Peer> {
+        let token = match token {
+            Some(token) => token.to_string(),
+            None => api::token()?,
+        };
+        Ok(Peer { addr: addr.to_string(), token, seen: String::new(), etag: None })
+    }
+}
+
+// the other machine's `serve`
+impl SyncProvider for Peer {
+    fn name(&self) -> String {
+        self.addr.to_string()
+    }
+    fn pull(&mut self, _known: &[Task]) -> io::Result<Vec<Task>> {
+        let reply = http::request(&self.addr, "GET", "/sync", &self.token, None, "")?;
+        if reply.status != 200 {
+            return Err(io::Error::other(format!("peer answered {}: {}", reply.status, reply.body.trim())));
+        }
+        let tasks = from_text(&reply.body, Local::now().date_naive());
+        self.seen = to_text(&tasks);
+        self.etag = reply.etag;
+        Ok(tasks)
+    }
+    fn push(&mut self, tasks: &[Task]) -> io::Result<()> {
+        let text = to_text(tasks);
+        if text != self.seen {
+            let reply = http::request(&self.addr, "PUT", "/sync", &self.token, self.etag.as_deref(), &text)?;
+            match reply.status {
+                200 => self.etag = reply.etag,
+                // the next sync pulls what was added there meanwhile and merges again
+                412 => return Err(io::Error::other("the peer's list changed while syncing")),
+                status => return Err(io::Error::other(format!("peer answered {}: {}", status, reply.body.trim()))),
+            }
+            self.seen = text;
+        }
+        Ok(())
+    }
+}
+
+// every provider the config sets up, for the background engine
+pub fn providers(config: &Config) -> io::Result<Vec<Box<dyn SyncProvider>>> {
+    let mut providers: Vec<Box<dyn SyncProvider>> = vec![];
+    for peer in &config.sync_peers {
+        providers.push(Box::new(Peer::new(peer, None)?));
+    }
+    if let Some(webdav) = &config.webdav {
+        providers.push(Box::new(WebDav::new(webdav.clone())));
+    }
+    if let Some(token) = &config.todoist_token {
+        providers.push(Box::new(Todoist::new(token, &config.mstodo_projects)));
+    }
+    if let (Some(client_id), false) = (&config.mstodo_client_id, config.mstodo_projects.is_empty()) {
+        providers.push(Box::new(MsTodo::new(client_id, &config.mstodo_projects)));
+    }
+    Ok(providers)
+}
+
+pub enum Update {
+    // `merge.tasks` is the list numbered `generation` merged with `name`
+    Synced { name: String, generation: u64, merge: Merge },
+    Failed { name: String, error: String, retry: Duration, pending: usize },
+    // a local change while `name` is unreachable
+    Pending { name: String, retry: Duration, pending: usize },
+}
+
+// local changes `name` hasn't seen yet: what differs from the base copy of
+// the last sync; they go out with the next sync that gets through
+pub fn pending(name: &str, local: &[Task]) -> io::Result<usize> {
+    let base = match load_base(name, Local::now().date_naive())? {
+        Some(base) => base,
+        None => return Ok(local.len()),
+    };
+    let changed = local.iter().filter(|l| base.iter().find(|b| b.id == l.id).is_none_or(|b| b.to_line() != l.to_line())).count();
+    let removed = base.iter().filter(|b| !local.iter().any(|l| l.id == b.id)).count();
+    Ok(changed + removed)
+}
+
+// how long to wait after the `failures`th failure in a row
+fn backoff(failures: u32) -> Duration {
+    let secs = 5u64.saturating_mul(1 << failures.saturating_sub(1).min(10));
+    Duration::from_secs(secs.min(600))
+}
+
+struct Slot {
+    provider: Box<dyn SyncProvider>,
+    due: Instant,
+    failures: u32,
+}
+
+// the app's end of the background engine
+pub struct Engine {
+    changes: Sender<(u64, Vec<Task>)>,
+    updates: Receiver<Update>,
+}
+
+impl Engine {
+    // the list after a local change, numbered `generation`
+    pub fn send(&self, generation: u64, tasks: &[Task]) {
+        let _ = self.changes.send((generation, tasks.to_vec()));
+    }
+    pub fn updates(&self) -> Vec<Update> {
+        self.updates.try_iter().collect()
+    }
+}
+
+// sync every provider each `interval`, and shortly after each local change;
+// failures back off exponentially
+pub fn spawn(providers: Vec<Box<dyn SyncProvider>>, interval: Duration, tasks: Vec<Task>) -> Engine {
+    let (changes, inbox) = mpsc::channel::<(u64, Vec<Task>)>();
+    let (updates, outbox) = mpsc::channel();
+    thread::spawn(move || {
+        let mut slots: Vec<Slot> = providers.into_iter().map(|provider| Slot { provider, due: Instant::now(), failures: 0 }).collect();
+        let (mut generation, mut local) = (0, tasks.clone());
+        // the list as the app last sent it, and whether `local` has had
+        // syncs merged in since
+        let (mut sent, mut merged) = (tasks, false);
+        loop {
+            let next = slots.iter().map(|s| s.due).min().unwrap_or_else(|| Instant::now() + interval);
+            match inbox.recv_timeout(next.saturating_duration_since(Instant::now())) {
+                Ok((g, tasks)) => {
+                    // the app made the change without what those syncs
+                    // brought, so it goes on top of them; taking the list as
+                    // it is would delete what they added
+                    if merged {
+                        local = merge(&tasks, &local, Some(&sent), None).tasks;
+                    } else {
+                        local = tasks.clone();
+                    }
+                    (generation, sent, merged) = (g, tasks, false);
+                    let soon = Instant::now() + Duration::from_secs(2);
+                    for slot in slots.iter_mut() {
+                        if slot.failures == 0 {
+                            slot.due = slot.due.min(soon);
+                            continue;
+                        }
+                        let name = slot.provider.name();
+                        let pending = pending(&name, &local).unwrap_or_default();
+                        let retry = slot.due.saturating_duration_since(Instant::now());
+                        if updates.send(Update::Pending { name, retry, pending }).is_err() {
+                            return;
+                        }
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            for slot in slots.iter_mut().filter(|s| s.due <= Instant::now()) {
+                let name = slot.provider.name();
+                let update = match once(slot.provider.as_mut(), &local) {
+                    Ok(merge) => {
+                        slot.failures = 0;
+                        slot.due = Instant::now() + interval;
+                        local = merge.tasks.clone();
+                        merged = true;
+                        Update::Synced { name, generation, merge }
+                    }
+                    Err(err) => {
+                        slot.failures += 1;
+                        let retry = backoff(slot.failures);
+                        slot.due = Instant::now() + retry;
+                        let pending = pending(&name, &local).unwrap_or_default();
+                        Update::Failed { name, error: err.to_string(), retry, pending }
+                    }
+                };
+                if updates.send(update).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Engine { changes, updates: outbox }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    // `a`, or `a: call mum @2026-05-02T10:00:00` for the todo with id `a`
+    // with that title and touched time
+    fn list(tasks: &[&str]) -> Vec<Task> {
+        tasks
+            .iter()
+            .map(|t| {
+                let (id, rest) = t.split_once(": ").unwrap_or((t, "title"));
+                let (title, touched) = rest.split_once(" @").unwrap_or((rest, "2026-05-01T09:00:00"));
+                Task::from_line(&format!("2026-05-01 {} touched:{} id:{}", title, touched, id), today())
+            })
+            .collect()
+    }
+
+    fn ids(merge: &Merge) -> Vec<String> {
+        merge.tasks.iter().map(|t| format!("{}: {}", t.id, t.title)).collect()
+    }
+
+    #[test]
+    fn first_sync_joins_both() {
+        let merge = merge(&list(&["a", "b"]), &list(&["b", "c"]), None, None);
+        assert_eq!(ids(&merge), ["a: title", "b: title", "c: title"]);
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn takes_the_side_that_changed() {
+        let base = list(&["a", "b"]);
+        let local = list(&["a: call mum @2026-05-02T10:00:00", "b"]);
+        let remote = list(&["a", "b: pay rent @2026-05-02T10:00:00"]);
+        let merge = merge(&local, &remote, Some(&base), None);
+        assert_eq!(ids(&merge), ["a: call mum", "b: pay rent"]);
+        assert_eq!((merge.pushed, merge.pulled), (1, 1));
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn deletes_go_both_ways() {
+        let base = list(&["a", "b", "c"]);
+        // cut here, archived over there
+        let merge = merge(&list(&["a", "c"]), &list(&["a", "b"]), Some(&base), None);
+        assert_eq!(ids(&merge), ["a: title"]);
+        assert_eq!((merge.pushed, merge.pulled), (1, 1));
+    }
+
+    #[test]
+    fn an_edit_beats_a_delete() {
+        let base = list(&["a", "b"]);
+        let local = list(&["a: call mum @2026-05-02T10:00:00"]);
+        let remote = list(&["b: pay rent @2026-05-02T10:00:00"]);
+        let merge = merge(&local, &remote, Some(&base), None);
+        assert_eq!(ids(&merge), ["a: call mum", "b: pay rent"]);
+    }
+
+    #[test]
+    fn added_on_both_sides() {
+        let base = list(&["a"]);
+        let merge = merge(&list(&["a", "b"]), &list(&["a", "c"]), Some(&base), None);
+        assert_eq!(ids(&merge), ["a: title", "b: title", "c: title"]);
+    }
+
+    #[test]
+    fn edits_on_both_sides_are_a_conflict() {
+        let base = list(&["a"]);
+        let local = list(&["a: call mum @2026-05-02T10:00:00"]);
+        let remote = list(&["a: call dad @2026-05-02T11:00:00"]);
+        let merge = merge(&local, &remote, Some(&base), None);
+        // the later edit, for now
+        assert_eq!(ids(&merge), ["a: call dad"]);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].local.title, "call mum");
+    }
+
+    #[test]
+    fn without_a_base_the_edit_time_decides() {
+        let last = NaiveDate::from_ymd_opt(2026, 5, 2).unwrap().and_hms_opt(0, 0, 0);
+        let local = list(&["a: call mum @2026-05-03T10:00:00"]);
+        let remote = list(&["a"]);
+        let merge = merge(&local, &remote, None, last);
+        assert_eq!(ids(&merge), ["a: call mum"]);
+        assert!(merge.conflicts.is_empty());
+    }
+}
@@ -0,0 +1,469 @@
+use std::{
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const TOUCHED_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+const REMIND_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Label {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+pub const LABELS: [Label; 6] = [Label::Red, Label::Orange, Label::Yellow, Label::Green, Label::Blue, Label::Purple];
+
+impl Label {
+    pub fn name(self) -> &'static str {
+        match self {
+            Label::Red => "red",
+            Label::Orange => "orange",
+            Label::Yellow => "yellow",
+            Label::Green => "green",
+            Label::Blue => "blue",
+            Label::Purple => "purple",
+        }
+    }
+    pub fn parse(name: &str) -> Option<Label> {
+        LABELS.into_iter().find(|l| l.name() == name)
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    // stable across edits, stored as `id:`
+    pub id: String,
+    pub title: String,
+    // `None` means the task still sits in the inbox
+    pub project: Option<String>,
+    // `@phone`, `@errands`: where or in what state the task can be done,
+    // stored without the `@`
+    pub contexts: Vec<String>,
+    // todo.txt priority, 'A' is the highest
+    pub priority: Option<char>,
+    pub due: Option<NaiveDate>,
+    pub label: Option<Label>,
+    // effort estimate in minutes, stored as `est:1h30m`
+    pub estimate: Option<u32>,
+    // where it gets done, one word: `loc:hardware-store`
+    pub location: Option<String>,
+    // when to pop up a reminder, typed as `remind:14:30` for that time on the
+    // due day, or today without one, and stored as `remind:2024-05-01T14:30`
+    pub remind: Option<NaiveDateTime>,
+    pub created: NaiveDate,
+    // last time the task was changed
+    pub touched: NaiveDateTime,
+    pub completed: Option<NaiveDateTime>,
+    // other `key:value` words, in the order they were written
+    pub fields: Vec<(String, String)>,
+    // lightweight steps, ticked or not; `check:` and `checked:` words with
+    // the spaces escaped
+    pub checklist: Vec<(bool, String)>,
+    // free-form Markdown, stored escaped as one `note:` word
+    pub note: Option<String>,
+}
+
+impl Task {
+    // split `+Project`, `(A)` and `due:YYYY-MM-DD` out of the typed text
+    pub fn parse(input: &str, today: NaiveDate) -> Task {
+        let mut project = None;
+        let mut contexts = vec![];
+        let mut priority = None;
+        let mut due = None;
+        let mut label = None;
+        let mut estimate = None;
+        let mut location = None;
+        let mut remind = None;
+        let mut touched = today.and_time(NaiveTime::MIN);
+        let mut id = None;
+        let mut fields = vec![];
+        let mut checklist = vec![];
+        let mut note = None;
+        let mut words = vec![];
+        for word in input.split_whitespace() {
+            if let (None, Some(name)) = (&project, word.strip_prefix('+').filter(|name| !name.is_empty())) {
+                project = Some(name.to_string());
+            } else if let Some(name) = word.strip_prefix('@').filter(|name| !name.is_empty() && !name.contains('@')) {
+                if !contexts.iter().any(|c| c == name) {
+                    contexts.push(name.to_string());
+                }
+            } else if let Some(p) = parse_priority(word).or_else(|| parse_pri_tag(word)) {
+                priority = Some(p);
+            } else if let Some(date) = word.strip_prefix("due:").and_then(parse_date) {
+                due = Some(date);
+            } else if let Some(l) = word.strip_prefix("label:").and_then(Label::parse) {
+                label = Some(l);
+            } else if let Some(minutes) = word.strip_prefix("est:").and_then(parse_minutes) {
+                estimate = Some(minutes);
+            } else if let Some(place) = word.strip_prefix("loc:").filter(|p| !p.is_empty()) {
+                location = Some(place.to_string());
+            } else if let Some(time) = word.strip_prefix("remind:").and_then(parse_remind) {
+                remind = Some(time);
+            } else if let Some(time) = word.strip_prefix("touched:").and_then(parse_touched) {
+                touched = time;
+            } else if let Some(value) = word.strip_prefix("id:").filter(|v| !v.is_empty()) {
+                id = Some(value.to_string());
+            } else if let Some(item) = word.strip_prefix("check:").filter(|i| !i.is_empty()) {
+                checklist.push((false, unescape(item)));
+            } else if let Some(text) = word.strip_prefix("note:").filter(|t| !t.is_empty()) {
+                note = Some(unescape(text));
+            } else if let Some(item) = word.strip_prefix("checked:").filter(|i| !i.is_empty()) {
+                checklist.push((true, unescape(item)));
+            } else if let Some((key, value)) = parse_field(word) {
+                // a key given twice keeps the later value
+                match fields.iter_mut().find(|(k, _)| k == key) {
+                    Some(field) => field.1 = value.to_string(),
+                    None => fields.push((key.to_string(), value.to_string())),
+                }
+            } else {
+                words.push(word);
+            }
+        }
+        Task {
+            id: id.unwrap_or_else(new_id),
+            title: words.join(" "),
+            project,
+            contexts,
+            priority,
+            due,
+            label,
+            estimate,
+            location,
+            // a bare time is for the due day, which may come after it
+            remind: remind.map(|time| match time {
+                Remind::At(time) => time,
+                Remind::Time(time) => due.unwrap_or(today).and_time(time),
+            }),
+            created: today,
+            touched,
+            completed: None,
+            fields,
+            checklist,
+            note,
+        }
+    }
+    // one todo.txt line: `(A) <created> <title> +<project> due:<due> touched:<touched>`,
+    // or `x <done> <created> <title> ... pri:A done:<time>` once completed
+    pub fn from_line(line: &str, today: NaiveDate) -> Task {
+        let mut rest = line;
+        let mut completed_on = None;
+        if let Some((word, tail)) = rest.strip_prefix("x ").and_then(|tail| tail.split_once(' ')) {
+            if let Some(date) = parse_date(word) {
+                completed_on = Some(date);
+                rest = tail;
+            }
+        }
+        let mut priority = None;
+        if let Some((word, tail)) = rest.split_once(' ') {
+            if let Some(p) = parse_priority(word) {
+                priority = Some(p);
+                rest = tail;
+            }
+        }
+        let mut created = today;
+        if let Some((word, tail)) = rest.split_once(' ') {
+            if let Some(date) = parse_date(word) {
+                created = date;
+                rest = tail;
+            }
+        }
+        let mut time = NaiveTime::MIN;
+        let rest: Vec<&str> = rest
+            .split_whitespace()
+            .filter(|word| match word.strip_prefix("done:").and_then(|t| NaiveTime::parse_from_str(t, TIME_FORMAT).ok()) {
+                Some(t) => {
+                    time = t;
+                    false
+                },
+                None => true,
+            })
+            .collect();
+        let task = Task::parse(&rest.join(" "), created);
+        Task {
+            priority: priority.or(task.priority),
+            touched: task.touched.max(created.and_time(NaiveTime::MIN)),
+            completed: completed_on.map(|date| date.and_time(time)),
+            ..task
+        }
+    }
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+    // ` +<project> @<context> due:<due> label:<label> est:<estimate> loc:<location> remind:<time> <key>:<value>`, shared
+    // by `text` and `to_line`
+    fn tags(&self) -> String {
+        let mut tags = String::new();
+        if let Some(project) = &self.project {
+            tags.push_str(&format!(" +{}", project));
+        }
+        for context in &self.contexts {
+            tags.push_str(&format!(" @{}", context));
+        }
+        if let Some(due) = self.due {
+            tags.push_str(&format!(" due:{}", due.format(DATE_FORMAT)));
+        }
+        if let Some(label) = self.label {
+            tags.push_str(&format!(" label:{}", label.name()));
+        }
+        if let Some(minutes) = self.estimate {
+            tags.push_str(&format!(" est:{}", format_minutes(minutes)));
+        }
+        if let Some(place) = &self.location {
+            tags.push_str(&format!(" loc:{}", place));
+        }
+        if let Some(time) = self.remind {
+            tags.push_str(&format!(" remind:{}", time.format(REMIND_FORMAT)));
+        }
+        for (key, value) in &self.fields {
+            tags.push_str(&format!(" {}:{}", key, value));
+        }
+        tags
+    }
+    // takes over what `text` holds from `edited`, the same text changed;
+    // the id, dates, checklist and note stay
+    pub fn apply_edit(&mut self, edited: Task, now: NaiveDateTime) {
+        self.title = edited.title;
+        self.project = edited.project;
+        self.contexts = edited.contexts;
+        self.priority = edited.priority;
+        self.due = edited.due;
+        self.label = edited.label;
+        self.estimate = edited.estimate;
+        self.location = edited.location;
+        self.remind = edited.remind;
+        self.fields = edited.fields;
+        self.touched = now;
+    }
+    // what the user types in the popup to get this task back
+    pub fn text(&self) -> String {
+        let mut text = self.title.to_string();
+        if let Some(p) = self.priority {
+            text.push_str(&format!(" ({})", p));
+        }
+        text.push_str(&self.tags());
+        text
+    }
+    pub fn to_line(&self) -> String {
+        let mut line = String::new();
+        match (self.completed, self.priority) {
+            (Some(done), _) => line.push_str(&format!("x {} ", done.format(DATE_FORMAT))),
+            (None, Some(p)) => line.push_str(&format!("({}) ", p)),
+            (None, None) => {},
+        }
+        line.push_str(&format!("{} {}", self.created.format(DATE_FORMAT), self.title));
+        line.push_str(&self.tags());
+        // not in `text`, the popup edits the task, not its steps or notes
+        for (done, item) in &self.checklist {
+            line.push_str(&format!(" {}:{}", if *done { "checked" } else { "check" }, escape(item)));
+        }
+        if let Some(note) = &self.note {
+            line.push_str(&format!(" note:{}", escape(note)));
+        }
+        if self.touched.time() != NaiveTime::MIN {
+            line.push_str(&format!(" touched:{}", self.touched.format(TOUCHED_FORMAT)));
+        } else if self.touched.date() != self.created {
+            line.push_str(&format!(" touched:{}", self.touched.format(DATE_FORMAT)));
+        }
+        line.push_str(&format!(" id:{}", self.id));
+        if let Some(done) = self.completed {
+            if let Some(p) = self.priority {
+                line.push_str(&format!(" pri:{}", p));
+            }
+            line.push_str(&format!(" done:{}", done.format(TIME_FORMAT)));
+        }
+        line
+    }
+    // `[2/6]`, or nothing without a checklist
+    pub fn progress(&self) -> Option<String> {
+        match self.checklist.len() {
+            0 => None,
+            n => Some(format!("[{}/{}]", self.checklist.iter().filter(|(done, _)| *done).count(), n)),
+        }
+    }
+    pub fn is_done(&self) -> bool {
+        self.completed.is_some()
+    }
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_done() && matches!(self.due, Some(due) if due < today)
+    }
+    pub fn is_stale(&self, today: NaiveDate, stale_days: i64) -> bool {
+        !self.is_done() && stale_days > 0 && (today - self.touched.date()).num_days() >= stale_days
+    }
+    pub fn age(&self, today: NaiveDate) -> String {
+        match (today - self.created).num_days() {
+            0 => "added today".to_string(),
+            days => format!("added {}d ago", days),
+        }
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, DATE_FORMAT).ok()
+}
+
+// short unique id from the clock, the process and a counter
+pub fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for n in [nanos, process::id() as u64, COUNTER.fetch_add(1, Ordering::Relaxed)] {
+        for byte in n.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:010x}", hash & 0xff_ffff_ffff)
+}
+
+// free text as one word: `%`, whitespace and `:` become `%XX`
+pub fn escape(text: &str) -> String {
+    let mut word = String::new();
+    for c in text.chars() {
+        match c {
+            '%' | ':' => word.push_str(&format!("%{:02X}", c as u32)),
+            c if c.is_ascii_whitespace() => word.push_str(&format!("%{:02X}", c as u32)),
+            c if c.is_whitespace() => word.push_str("%20"),
+            c => word.push(c),
+        }
+    }
+    word
+}
+
+pub fn unescape(word: &str) -> String {
+    let mut text = String::new();
+    let mut rest = word;
+    while let Some(i) = rest.find('%') {
+        text.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if byte < 0x80 => {
+                text.push(byte as char);
+                rest = &rest[i + 3..];
+            }
+            _ => {
+                text.push('%');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+// `30m`, `2h`, `1h30m`, or plain minutes
+pub fn parse_minutes(text: &str) -> Option<u32> {
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = text.parse() {
+        return Some(minutes);
+    }
+    let (hours, rest) = match text.split_once('h') {
+        Some((hours, rest)) => (hours.parse::<u32>().ok()?, rest),
+        None => (0, text),
+    };
+    let minutes = match rest {
+        "" => 0,
+        _ => rest.strip_suffix('m')?.parse::<u32>().ok()?,
+    };
+    // too big to count is no estimate rather than a wrapped one
+    hours.checked_mul(60)?.checked_add(minutes)
+}
+
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+// a `remind:` word: a full date and time, or only a time of day, on the due
+// day or else today
+enum Remind {
+    At(NaiveDateTime),
+    Time(NaiveTime),
+}
+
+fn parse_remind(text: &str) -> Option<Remind> {
+    match NaiveDateTime::parse_from_str(text, REMIND_FORMAT) {
+        Ok(time) => Some(Remind::At(time)),
+        Err(_) => NaiveTime::parse_from_str(text, TIME_FORMAT).ok().map(Remind::Time),
+    }
+}
+
+// `touched:` is a plain date in files written before it kept the time
+fn parse_touched(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, TOUCHED_FORMAT)
+        .ok()
+        .or_else(|| parse_date(text).map(|date| date.and_time(NaiveTime::MIN)))
+}
+
+// keys the line format itself uses; a word with one of these that doesn't
+// parse stays in the title
+const RESERVED: [&str; 12] = ["due", "label", "est", "loc", "remind", "check", "checked", "note", "touched", "id", "pri", "done"];
+
+// a key starts with a letter and holds letters, digits, `-` and `_`
+pub fn is_field_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !RESERVED.contains(&key)
+}
+
+// a todo.txt extension, `key:value`; neither side holds a colon, and values
+// starting with `/` are left alone so links stay in the title
+pub fn parse_field(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    if !is_field_key(key) || value.is_empty() || value.contains(':') || value.starts_with('/') {
+        return None;
+    }
+    Some((key, value))
+}
+
+// `pri:A`, how completed tasks keep their priority
+fn parse_pri_tag(word: &str) -> Option<char> {
+    match word.strip_prefix("pri:")?.as_bytes() {
+        [p] if p.is_ascii_uppercase() => Some(*p as char),
+        _ => None,
+    }
+}
+
+fn parse_priority(word: &str) -> Option<char> {
+    match word.as_bytes() {
+        [b'(', p, b')'] if p.is_ascii_uppercase() => Some(*p as char),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_minutes() {
+        assert_eq!(parse_minutes("45"), Some(45));
+        assert_eq!(parse_minutes("2h"), Some(120));
+        assert_eq!(parse_minutes("1h30m"), Some(90));
+        assert_eq!(parse_minutes("30m"), Some(30));
+        assert_eq!(parse_minutes(""), None);
+        assert_eq!(parse_minutes("1h30"), None);
+    }
+
+    #[test]
+    fn minutes_too_big_are_no_estimate() {
+        assert_eq!(parse_minutes("71582789h"), None);
+        assert_eq!(parse_minutes("71582788h4294967295m"), None);
+        assert_eq!(parse_minutes("4294967295m"), Some(u32::MAX));
+        let task = Task::parse("plan est:99999999h", NaiveDate::from_ymd_opt(2026, 6, 1).unwrap());
+        assert_eq!(task.estimate, None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, io};
+
+use chrono::{Local, NaiveDate};
+
+use crate::{
+    curl,
+    json::{self, Json},
+    state::State,
+    sync::SyncProvider,
+    task::Task,
+};
+
+const API: &str = "https://api.todoist.com/rest/v2";
+
+struct Project {
+    id: String,
+    name: String,
+    inbox: bool,
+}
+
+struct Remote {
+    id: String,
+    content: String,
+    project_id: String,
+    due: Option<NaiveDate>,
+    priority: Option<char>,
+}
+
+fn call(token: &str, method: &str, path: &str, body: Option<Json>) -> io::Result<Json> {
+    let mut options = vec![("request", method.to_string()), ("header", format!("Authorization: Bearer {}", token))];
+    if let Some(body) = body {
+        options.push(("header", "Content-Type: application/json".to_string()));
+        options.push(("data-raw", body.to_string()));
+    }
+    let reply = curl::run(&format!("{}{}", API, path), &options)?;
+    match reply.status {
+        200 => json::parse(&reply.body).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Todoist sent malformed JSON")),
+        204 => Ok(Json::Null),
+        status => Err(io::Error::other(format!("Todoist answered {}: {}", status, reply.body.trim()))),
+    }
+}
+
+fn str_field(value: &Json, key: &str) -> String {
+    value.get(key).and_then(Json::as_str).unwrap_or_default().to_string()
+}
+
+fn items(value: Json) -> Vec<Json> {
+    match value {
+        Json::Arr(items) => items,
+        _ => vec![],
+    }
+}
+
+// a todo.txt project is one word
+fn project_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+// Todoist's priority 4 is the most urgent, 1 means none
+fn to_priority(n: f64) -> Option<char> {
+    match n as u8 {
+        4 => Some('A'),
+        3 => Some('B'),
+        2 => Some('C'),
+        _ => None,
+    }
+}
+
+fn from_priority(p: Option<char>) -> f64 {
+    match p {
+        Some('A') => 4.0,
+        Some('B') => 3.0,
+        Some(_) => 2.0,
+        None => 1.0,
+    }
+}
+
+impl Remote {
+    fn from_json(value: &Json) -> Remote {
+        Remote {
+            id: str_field(value, "id"),
+            content: str_field(value, "content"),
+            project_id: str_field(value, "project_id"),
+            due: value
+                .get("due")
+                .and_then(|due| due.get("date"))
+                .and_then(Json::as_str)
+                .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok()),
+            priority: value.get("priority").and_then(Json::as_num).and_then(to_priority),
+        }
+    }
+    fn project<'a>(&self, projects: &'a [Project]) -> Option<&'a Project> {
+        projects.iter().find(|p| p.id == self.project_id && !p.inbox)
+    }
+    fn differs(&self, task: &Task, projects: &[Project]) -> bool {
+        self.content != task.title
+            || self.due != task.due
+            || from_priority(self.priority) != from_priority(task.priority)
+            || self.project(projects).map(|p| project_name(&p.name)) != task.project
+    }
+    fn apply(&self, task: &mut Task, projects: &[Project]) {
+        task.title = self.content.to_string();
+        task.due = self.due;
+        // Todoist only knows four levels, keep a finer local one on the same level
+        if from_priority(self.priority) != from_priority(task.priority) {
+            task.priority = self.priority;
+        }
+        task.project = self.project(projects).map(|p| project_name(&p.name));
+    }
+}
+
+fn body(task: &Task, project_id: Option<String>) -> Json {
+    let mut fields = vec![
+        ("content".to_string(), task.title.as_str().into()),
+        ("priority".to_string(), Json::Num(from_priority(task.priority))),
+        ("due_date".to_string(), task.due.map(|d| d.to_string()).into()),
+    ];
+    if let Some(id) = project_id {
+        fields.push(("project_id".to_string(), id.into()));
+    }
+    Json::Obj(fields)
+}
+
+// the Todoist project for a local one, created when it doesn't exist yet
+fn project_id(token: &str, projects: &mut Vec<Project>, task: &Task) -> io::Result<Option<String>> {
+    let name = match &task.project {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if let Some(project) = projects.iter().find(|p| !p.inbox && &project_name(&p.name) == name) {
+        return Ok(Some(project.id.to_string()));
+    }
+    let created = call(token, "POST", "/projects", Some(Json::Obj(vec![("name".to_string(), name.as_str().into())])))?;
+    let id = str_field(&created, "id");
+    projects.push(Project { id: id.to_string(), name: name.to_string(), inbox: false });
+    Ok(Some(id))
+}
+
+// `todo-tui todoist`: open tasks both ways; tasks Todoist stops listing were
+// completed (or deleted) there
+pub struct Todoist {
+    token: String,
+    // projects another provider keeps, left out of Todoist
+    skip: Vec<String>,
+    projects: Vec<Project>,
+    remote: Vec<Remote>,
+    // local task id -> Todoist task id
+    links: BTreeMap<String, String>,
+}
+
+impl Todoist {
+    pub fn new(token: &str, skip: &[String]) -> Todoist {
+        Todoist { token: token.to_string(), skip: skip.to_vec(), projects: vec![], remote: vec![], links: BTreeMap::new() }
+    }
+    fn push_all(&mut self, tasks: &[Task]) -> io::Result<()> {
+        for task in tasks {
+            match self.links.get(&task.id).cloned() {
+                Some(id) => match self.remote.iter().find(|r| r.id == id) {
+                    Some(_) if task.is_done() => {
+                        call(&self.token, "POST", &format!("/tasks/{}/close", id), None)?;
+                    }
+                    Some(r) if r.differs(task, &self.projects) => {
+                        let project = project_id(&self.token, &mut self.projects, task)?;
+                        call(&self.token, "POST", &format!("/tasks/{}", id), Some(body(task, project)))?;
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.links.remove(&task.id);
+                    }
+                },
+                None if !task.is_done() && !task.project.as_ref().is_some_and(|p| self.skip.contains(p)) => {
+                    let project = project_id(&self.token, &mut self.projects, task)?;
+                    let created = call(&self.token, "POST", "/tasks", Some(body(task, project)))?;
+                    self.links.insert(task.id.to_string(), str_field(&created, "id"));
+                }
+                None => {}
+            }
+        }
+        // deleted here: gone over there too, unless it's closed already
+        let gone: Vec<(String, String)> =
+            self.links.iter().filter(|(local, _)| !tasks.iter().any(|t| &t.id == *local)).map(|(l, r)| (l.to_string(), r.to_string())).collect();
+        for (local, id) in gone {
+            if self.remote.iter().any(|r| r.id == id) {
+                call(&self.token, "DELETE", &format!("/tasks/{}", id), None)?;
+            }
+            self.links.remove(&local);
+        }
+        Ok(())
+    }
+}
+
+impl SyncProvider for Todoist {
+    fn name(&self) -> String {
+        "todoist".to_string()
+    }
+    fn pull(&mut self, known: &[Task]) -> io::Result<Vec<Task>> {
+        let now = Local::now().naive_local();
+        self.projects = items(call(&self.token, "GET", "/projects", None)?)
+            .iter()
+            .map(|p| Project {
+                id: str_field(p, "id"),
+                name: str_field(p, "name"),
+                inbox: matches!(p.get("is_inbox_project"), Some(Json::Bool(true))),
+            })
+            .collect();
+        self.remote = items(call(&self.token, "GET", "/tasks", None)?).iter().map(Remote::from_json).collect();
+        self.links = State::load()?.links.remove("todoist").unwrap_or_default();
+        let mut tasks = vec![];
+        for r in &self.remote {
+            let id = self.links.iter().find(|(_, id)| **id == r.id).map(|(local, _)| local.to_string());
+            let mut task = match id.as_ref().and_then(|id| known.iter().find(|t| &t.id == id)) {
+                Some(task) => task.clone(),
+                None => {
+                    let mut task = Task::parse("", now.date());
+                    task.id = id.unwrap_or_else(|| task.id.to_string());
+                    self.links.insert(task.id.to_string(), r.id.to_string());
+                    task
+                }
+            };
+            r.apply(&mut task, &self.projects);
+            // Todoist only lists open tasks
+            task.completed = None;
+            tasks.push(task);
+        }
+        for task in known.iter().filter(|t| self.links.contains_key(&t.id)) {
+            if !self.remote.iter().any(|r| Some(&r.id) == self.links.get(&task.id)) {
+                let mut task = task.clone();
+                task.completed = task.completed.or(Some(now));
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+    fn push(&mut self, tasks: &[Task]) -> io::Result<()> {
+        let pushed = self.push_all(tasks);
+        // keep the links of whatever got created, even when a later call failed
+        let mut state = State::load()?;
+        state.links.insert("todoist".to_string(), self.links.clone());
+        state.save()?;
+        pushed
+    }
+    // a linked task gone from Todoist comes back from `pull` as completed
+    fn holds(&self, task: &Task) -> bool {
+        self.links.contains_key(&task.id)
+    }
+}
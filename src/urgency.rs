@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+
+use crate::task::Task;
+
+// `[urgency]`: how much each part counts, Taskwarrior's defaults
+pub struct Coefficients {
+    pub priority: f64,
+    pub due: f64,
+    pub age: f64,
+    pub tags: f64,
+    pub project: f64,
+}
+
+impl Default for Coefficients {
+    fn default() -> Coefficients {
+        Coefficients { priority: 6.0, due: 12.0, age: 2.0, tags: 1.0, project: 1.0 }
+    }
+}
+
+impl Coefficients {
+    pub fn set(&mut self, key: &str, value: f64) {
+        match key {
+            "priority" => self.priority = value,
+            "due" => self.due = value,
+            "age" => self.age = value,
+            "tags" => self.tags = value,
+            "project" => self.project = value,
+            _ => {}
+        }
+    }
+}
+
+// each part scaled to 0..1 before its coefficient, the way Taskwarrior does,
+// plus the todo's own `weight:` field
+pub fn score(task: &Task, today: NaiveDate, coefficients: &Coefficients) -> f64 {
+    let priority = match task.priority {
+        Some('A') => 1.0,
+        Some('B') => 0.65,
+        Some('C') => 0.3,
+        _ => 0.0,
+    };
+    // full a week overdue, 0.2 two weeks or more out
+    let due = match task.due {
+        Some(due) => {
+            let days = (due - today).num_days().clamp(-7, 14) as f64;
+            0.2 + (14.0 - days) * 0.8 / 21.0
+        }
+        None => 0.0,
+    };
+    // full at a year old
+    let age = ((today - task.created).num_days() as f64 / 365.0).clamp(0.0, 1.0);
+    // contexts are the tags here
+    let tags = match task.contexts.len() {
+        0 => 0.0,
+        1 => 0.8,
+        2 => 0.9,
+        _ => 1.0,
+    };
+    let project = if task.project.is_some() { 1.0 } else { 0.0 };
+    let weight = task.field("weight").and_then(|w| w.parse::<f64>().ok()).unwrap_or(0.0);
+    coefficients.priority * priority
+        + coefficients.due * due
+        + coefficients.age * age
+        + coefficients.tags * tags
+        + coefficients.project * project
+        + weight
+}
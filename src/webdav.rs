@@ -0,0 +1,130 @@
+use std::io;
+
+use chrono::Local;
+
+use crate::{
+    config::WebDavConfig,
+    curl,
+    sync::{self, SyncProvider},
+    task::Task,
+};
+
+pub enum Put {
+    // saved, with the file's new ETag
+    Saved(Option<String>),
+    // someone else changed the file since we read it
+    Conflict,
+}
+
+// todo.txt kept as one file on a WebDAV server
+pub struct WebDav {
+    config: WebDavConfig,
+    // whether the last pull found the file, and its ETag if the server sent one
+    exists: bool,
+    etag: Option<String>,
+    // the file as last pulled, to skip writing it back unchanged
+    seen: Option<String>,
+}
+
+impl WebDav {
+    pub fn new(config: WebDavConfig) -> WebDav {
+        WebDav { config, exists: false, etag: None, seen: None }
+    }
+    // `None` when the file isn't there yet
+    fn get(&self) -> io::Result<Option<(String, Option<String>)>> {
+        let reply = curl::run(&self.config.url, &self.auth())?;
+        match reply.status {
+            200 => Ok(Some((reply.body.to_string(), reply.header("etag").map(str::to_string)))),
+            404 => Ok(None),
+            status => Err(io::Error::other(format!("WebDAV GET answered {}", status))),
+        }
+    }
+    // what makes the server refuse the write when the file changed under us:
+    // `If-Match` its ETag, or `If-None-Match: *` when there was no file. An
+    // existing file without an ETag has none, `put` compares it instead
+    fn precondition(&self) -> Option<String> {
+        match (&self.etag, self.exists) {
+            (Some(etag), _) => Some(format!("If-Match: {}", etag)),
+            (None, false) => Some("If-None-Match: *".to_string()),
+            (None, true) => None,
+        }
+    }
+    fn put(&self, text: &str) -> io::Result<Put> {
+        let mut options = self.auth();
+        options.push(("request", "PUT".to_string()));
+        options.push(("header", "Content-Type: text/plain; charset=utf-8".to_string()));
+        options.push(("data-raw", text.to_string()));
+        match self.precondition() {
+            Some(header) => options.push(("header", header)),
+            // as close as a server without ETags allows: still the file pulled
+            None => {
+                if self.get()?.map(|(text, _)| text) != self.seen {
+                    return Ok(Put::Conflict);
+                }
+            }
+        }
+        let reply = curl::run(&self.config.url, &options)?;
+        match reply.status {
+            200 | 201 | 204 => Ok(Put::Saved(reply.header("etag").map(str::to_string))),
+            412 => Ok(Put::Conflict),
+            status => Err(io::Error::other(format!("WebDAV PUT answered {}", status))),
+        }
+    }
+    fn auth(&self) -> Vec<(&'static str, String)> {
+        match &self.config.user {
+            Some(user) => vec![("user", format!("{}:{}", user, self.config.password.as_deref().unwrap_or_default()))],
+            None => vec![],
+        }
+    }
+}
+
+impl SyncProvider for WebDav {
+    fn name(&self) -> String {
+        "webdav".to_string()
+    }
+    fn pull(&mut self, known: &[Task]) -> io::Result<Vec<Task>> {
+        let file = self.get()?;
+        self.exists = file.is_some();
+        self.etag = file.as_ref().and_then(|(_, etag)| etag.clone());
+        self.seen = file.as_ref().map(|(text, _)| text.to_string());
+        match file {
+            Some((text, _)) => Ok(sync::from_text(&text, Local::now().date_naive())),
+            // no file (yet, or any more) deletes nothing here; the push
+            // writes it again
+            None => Ok(known.to_vec()),
+        }
+    }
+    fn push(&mut self, tasks: &[Task]) -> io::Result<()> {
+        let text = sync::to_text(tasks);
+        if self.seen.as_ref() == Some(&text) {
+            return Ok(());
+        }
+        match self.put(&text)? {
+            Put::Saved(Some(etag)) => self.etag = Some(etag),
+            // not every server sends the new ETag back, or has any
+            Put::Saved(None) => self.etag = self.get()?.and_then(|(_, etag)| etag),
+            // the next sync pulls their version and merges again
+            Put::Conflict => return Err(io::Error::other("the WebDAV file changed while syncing")),
+        }
+        self.exists = true;
+        self.seen = Some(text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asks_for_no_file_only_after_a_404() {
+        let mut webdav = WebDav::new(WebDavConfig { url: "https://dav.example.com/todo.txt".to_string(), user: None, password: None });
+        assert_eq!(webdav.precondition().as_deref(), Some("If-None-Match: *"));
+        webdav.exists = true;
+        webdav.etag = Some("\"abc\"".to_string());
+        assert_eq!(webdav.precondition().as_deref(), Some("If-Match: \"abc\""));
+        // a server without ETags: no header that would always fail
+        webdav.etag = None;
+        assert_eq!(webdav.precondition(), None);
+    }
+}